@@ -0,0 +1,53 @@
+//! Per-tree generation cost of every species, run with `cargo bench --bench tree_gen`.
+//! Only the tree generator and its dependencies are compiled in, so this runs without a window.
+#![allow(dead_code)]
+
+#[path = "../src/voxel.rs"]
+mod voxel;
+
+#[path = "../src/utils"]
+mod utils {
+    pub mod interpolate;
+    pub mod tree_gen;
+    pub mod voxel_set;
+}
+
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use utils::tree_gen::TreeDefinition;
+
+/// Trees generated per species, each from its own seed like in a real world
+const TREES: u64 = 2000;
+
+fn main() {
+    println!("{:<12} {:>12} {:>12} {:>12}", "species", "per tree", "voxels", "per voxel");
+    for definition in TreeDefinition::defaults() {
+        // Warm up caches and the allocator before measuring
+        for seed in 0..TREES / 10 {
+            black_box(definition.generate((0, 0, 0), &mut Pcg32::seed_from_u64(seed)));
+        }
+
+        let mut elapsed = Duration::ZERO;
+        let mut voxels = 0;
+        for seed in 0..TREES {
+            let mut rng = Pcg32::seed_from_u64(seed);
+            let start = Instant::now();
+            let tree = black_box(definition.generate((0, 0, 0), &mut rng));
+            elapsed += start.elapsed();
+            voxels += tree.iter().count();
+        }
+
+        let per_tree = elapsed / TREES as u32;
+        let mean_voxels = voxels as f64 / TREES as f64;
+        let per_voxel = elapsed.as_nanos() as f64 / voxels.max(1) as f64;
+        println!(
+            "{:<12} {:>12.2?} {:>12.1} {:>10.1}ns",
+            format!("{:?}", definition.species),
+            per_tree,
+            mean_voxels,
+            per_voxel
+        );
+    }
+}
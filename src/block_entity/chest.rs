@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+use crate::inventory::ItemStack;
+
+pub const CHEST_COLUMNS: usize = 9;
+pub const CHEST_SLOTS: usize = 27;
+
+/// Storage for item stacks, laid out like the player's main inventory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Chest {
+    pub slots: [Option<ItemStack>; CHEST_SLOTS],
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inventory::ItemStack,
+    item::{Item, Material, ToolTier},
+    voxel::VoxelType,
+};
+
+pub const INPUT_SLOT: usize = 0;
+pub const FUEL_SLOT: usize = 1;
+pub const OUTPUT_SLOT: usize = 2;
+
+/// Seconds it takes to smelt a single item
+const SMELT_TIME: f32 = 10.0;
+
+/// Burns fuel to smelt its input one item at a time into the output slot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Furnace {
+    pub slots: [Option<ItemStack>; 3],
+    /// Seconds the current fuel item keeps burning, out of how long it burns in total
+    burn_left: f32,
+    burn_total: f32,
+    /// Seconds spent smelting the current input item
+    smelt_progress: f32,
+}
+
+/// What `item` turns into when smelted
+pub fn smelting_result(item: Item) -> Option<Item> {
+    match item {
+        Item::Block(VoxelType::Sand) => Some(Item::Block(VoxelType::Glass)),
+        Item::Block(VoxelType::Cobblestone) => Some(Item::Block(VoxelType::Stone)),
+        Item::Block(VoxelType::CopperOre) => Some(Item::Material(Material::CopperIngot)),
+        _ => None,
+    }
+}
+
+/// Seconds a single `item` burns for as fuel
+pub fn fuel_time(item: Item) -> Option<f32> {
+    match item {
+        Item::Block(
+            VoxelType::WalnutWood
+            | VoxelType::WalnutPlanks
+            | VoxelType::SpruceWood
+            | VoxelType::SprucePlanks
+            | VoxelType::MahoganyWood
+            | VoxelType::MahoganyPlanks
+            | VoxelType::BirchWood
+            | VoxelType::BirchPlanks,
+        ) => Some(15.0),
+        Item::Material(Material::Stick) => Some(5.0),
+        Item::Tool(tool) if tool.tier() == ToolTier::Wood => Some(10.0),
+        _ => None,
+    }
+}
+
+fn take_one(slot: &mut Option<ItemStack>) {
+    if let Some(stack) = slot {
+        stack.count -= 1;
+        if stack.count == 0 {
+            *slot = None;
+        }
+    }
+}
+
+impl Furnace {
+    pub fn tick(&mut self, delta_time: f32) {
+        let result = self.slots[INPUT_SLOT].and_then(|stack| smelting_result(stack.item));
+        let can_smelt = result.is_some_and(|result| match self.slots[OUTPUT_SLOT] {
+            Some(output) => output.item == result && !output.is_full(),
+            None => true,
+        });
+
+        // Only light new fuel when there is something to smelt
+        if self.burn_left <= 0.0 && can_smelt {
+            if let Some(time) = self.slots[FUEL_SLOT].and_then(|stack| fuel_time(stack.item)) {
+                take_one(&mut self.slots[FUEL_SLOT]);
+                self.burn_left = time;
+                self.burn_total = time;
+            }
+        }
+
+        if self.burn_left <= 0.0 {
+            // Without fuel a half smelted item cools back down
+            self.smelt_progress = (self.smelt_progress - 2.0 * delta_time).max(0.0);
+            return;
+        }
+        self.burn_left = (self.burn_left - delta_time).max(0.0);
+
+        let Some(result) = result.filter(|_| can_smelt) else {
+            self.smelt_progress = 0.0;
+            return;
+        };
+        self.smelt_progress += delta_time;
+        if self.smelt_progress >= SMELT_TIME {
+            self.smelt_progress = 0.0;
+            take_one(&mut self.slots[INPUT_SLOT]);
+            match &mut self.slots[OUTPUT_SLOT] {
+                Some(output) => output.count += 1,
+                output => *output = Some(ItemStack::new(result, 1)),
+            }
+        }
+    }
+
+    /// How much of the current fuel item is left, from 0 to 1
+    pub fn burn_fraction(&self) -> f32 {
+        if self.burn_total > 0.0 { self.burn_left / self.burn_total } else { 0.0 }
+    }
+
+    /// How far along smelting the current input item is, from 0 to 1
+    pub fn smelt_fraction(&self) -> f32 {
+        self.smelt_progress / SMELT_TIME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stack(block: VoxelType, count: u32) -> Option<ItemStack> {
+        Some(ItemStack::new(Item::Block(block), count))
+    }
+
+    fn furnace(input: Option<ItemStack>, fuel: Option<ItemStack>, output: Option<ItemStack>) -> Furnace {
+        Furnace { slots: [input, fuel, output], ..Default::default() }
+    }
+
+    #[test]
+    fn fuel_is_only_lit_with_something_to_smelt() {
+        let mut furnace = furnace(None, stack(VoxelType::WalnutPlanks, 2), None);
+        furnace.tick(1.0);
+        assert_eq!(furnace.slots[FUEL_SLOT], stack(VoxelType::WalnutPlanks, 2));
+        assert_eq!(furnace.burn_fraction(), 0.0);
+
+        furnace.slots[INPUT_SLOT] = stack(VoxelType::Dirt, 1);
+        furnace.tick(1.0);
+        assert_eq!(furnace.slots[FUEL_SLOT], stack(VoxelType::WalnutPlanks, 2));
+
+        furnace.slots[INPUT_SLOT] = stack(VoxelType::Sand, 1);
+        furnace.tick(1.0);
+        assert_eq!(furnace.slots[FUEL_SLOT], stack(VoxelType::WalnutPlanks, 1));
+        assert!(furnace.burn_fraction() > 0.0);
+    }
+
+    #[test]
+    fn smelting_cools_down_without_fuel() {
+        // A stick burns for 5 seconds, half of the smelt time
+        let mut furnace = furnace(stack(VoxelType::Sand, 1), Some(ItemStack::new(Item::Material(Material::Stick), 1)), None);
+        furnace.tick(5.0);
+        assert_eq!(furnace.smelt_fraction(), 0.5);
+        furnace.tick(1.0);
+        assert_eq!(furnace.smelt_fraction(), 0.3);
+        furnace.tick(10.0);
+        assert_eq!(furnace.smelt_fraction(), 0.0);
+        assert_eq!(furnace.slots[INPUT_SLOT], stack(VoxelType::Sand, 1));
+        assert_eq!(furnace.slots[OUTPUT_SLOT], None);
+    }
+
+    #[test]
+    fn full_or_mismatched_output_stops_smelting() {
+        let mut mismatched = furnace(stack(VoxelType::Sand, 1), stack(VoxelType::WalnutPlanks, 1), stack(VoxelType::Stone, 1));
+        mismatched.tick(SMELT_TIME);
+        assert_eq!(mismatched.slots, [stack(VoxelType::Sand, 1), stack(VoxelType::WalnutPlanks, 1), stack(VoxelType::Stone, 1)]);
+
+        let full = Item::Block(VoxelType::Glass).max_stack_size();
+        let mut full = furnace(stack(VoxelType::Sand, 1), stack(VoxelType::WalnutPlanks, 1), stack(VoxelType::Glass, full));
+        let before = full.slots;
+        full.tick(SMELT_TIME);
+        assert_eq!(full.slots, before);
+        assert_eq!(full.smelt_fraction(), 0.0);
+    }
+
+    #[test]
+    fn a_long_tick_smelts_one_item() {
+        let mut furnace = furnace(stack(VoxelType::Sand, 3), stack(VoxelType::WalnutPlanks, 1), None);
+        furnace.tick(SMELT_TIME * 2.5);
+        assert_eq!(furnace.slots[INPUT_SLOT], stack(VoxelType::Sand, 2));
+        assert_eq!(furnace.slots[OUTPUT_SLOT], stack(VoxelType::Glass, 1));
+    }
+}
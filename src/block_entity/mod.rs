@@ -0,0 +1,72 @@
+pub mod chest;
+pub mod furnace;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inventory::ItemStack,
+    voxel::{BlockState, VoxelType},
+};
+use chest::Chest;
+use furnace::Furnace;
+
+/// State attached to a single block, like the contents of a chest. Owned by the chunk
+/// the block is in, so it moves, ticks and unloads together with the chunk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BlockEntity {
+    Furnace(Furnace),
+    Chest(Chest),
+}
+
+/// A block entity with the local position and state of its block, as it is serialised
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBlockEntity {
+    pub position: [usize; 3],
+    #[serde(default)]
+    pub state: BlockState,
+    pub entity: BlockEntity,
+}
+
+impl BlockEntity {
+    /// The block entity a newly placed `block` starts out with, if it has one
+    pub fn for_block(block: VoxelType) -> Option<Self> {
+        match block {
+            VoxelType::Furnace => Some(BlockEntity::Furnace(Furnace::default())),
+            VoxelType::Chest => Some(BlockEntity::Chest(Chest::default())),
+            _ => None,
+        }
+    }
+
+    /// The block this entity belongs to
+    pub fn block(&self) -> VoxelType {
+        match self {
+            BlockEntity::Furnace(_) => VoxelType::Furnace,
+            BlockEntity::Chest(_) => VoxelType::Chest,
+        }
+    }
+
+    /// Advances the entity by `delta_time` seconds while its chunk is loaded
+    pub fn tick(&mut self, delta_time: f32) {
+        match self {
+            BlockEntity::Furnace(furnace) => furnace.tick(delta_time),
+            BlockEntity::Chest(_) => {}
+        }
+    }
+
+    /// Copies of everything stored in the entity
+    pub fn contents(&self) -> Vec<ItemStack> {
+        match self {
+            BlockEntity::Furnace(furnace) => furnace.slots.iter().flatten().copied().collect(),
+            BlockEntity::Chest(chest) => chest.slots.iter().flatten().copied().collect(),
+        }
+    }
+
+    /// Empties the entity, returning everything that was stored in it
+    pub fn take_contents(&mut self) -> Vec<ItemStack> {
+        match self {
+            BlockEntity::Furnace(furnace) => furnace.slots.iter_mut().filter_map(Option::take).collect(),
+            BlockEntity::Chest(chest) => chest.slots.iter_mut().filter_map(Option::take).collect(),
+        }
+    }
+}
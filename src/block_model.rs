@@ -0,0 +1,344 @@
+// block_model.rs
+// Geometry of blocks that aren't unit cubes, shared by chunk meshing and collision
+use glam::Vec3;
+
+use crate::{
+    utils::cube_face::cube_face,
+    voxel::{BlockState, Facing, Half, Shape, VoxelType},
+};
+
+/// An axis-aligned box within a block's cell, in block units from its minimum corner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl ModelBox {
+    pub const FULL: ModelBox = ModelBox { min: Vec3::ZERO, max: Vec3::ONE };
+
+    /// A box given in sixteenths of a block, the size of an atlas pixel
+    fn pixels(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min: Vec3::from(min) / 16.0, max: Vec3::from(max) / 16.0 }
+    }
+
+    pub fn intersects(&self, other: &ModelBox) -> bool {
+        self.min.cmplt(other.max).all() && other.min.cmplt(self.max).all()
+    }
+
+    pub fn translated(&self, offset: Vec3) -> Self {
+        Self { min: self.min + offset, max: self.max + offset }
+    }
+
+    /// Whether the box reaches the cell border on `face`, where a neighbour can hide it
+    fn touches_border(&self, face: usize) -> bool {
+        match face {
+            0 => self.max.y >= 1.0,
+            1 => self.min.y <= 0.0,
+            2 => self.max.z >= 1.0,
+            3 => self.min.z <= 0.0,
+            4 => self.max.x >= 1.0,
+            _ => self.min.x <= 0.0,
+        }
+    }
+}
+
+/// One textured quad of a model, in chunk-local coordinates like `cube_face`
+pub struct ModelQuad {
+    pub positions: Vec<[f32; 4]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub normal: [f32; 3],
+}
+
+/// How a block is meshed
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockModel {
+    Cube,
+    /// Boxes textured with the part of each face texture they cover
+    Boxes(Vec<ModelBox>),
+    /// Two double-sided quads crossing diagonally through the cell
+    Cross,
+}
+
+const FACE_NORMALS: [[f32; 3]; 6] = [
+    [0.0, 1.0, 0.0],  // Top
+    [0.0, -1.0, 0.0], // Bottom
+    [0.0, 0.0, 1.0],  // Front
+    [0.0, 0.0, -1.0], // Back
+    [1.0, 0.0, 0.0],  // Right
+    [-1.0, 0.0, 0.0], // Left
+];
+
+// The axis each texture coordinate follows across a face as laid out by `cube_face`, and
+// whether it runs backwards: (u axis, u flipped, v axis, v flipped)
+const FACE_UV_AXES: [(usize, bool, usize, bool); 6] = [
+    (0, false, 2, false), // Top
+    (0, false, 2, false), // Bottom
+    (0, false, 1, false), // Front
+    (0, false, 1, false), // Back
+    (2, true, 1, false),  // Right
+    (2, false, 1, false), // Left
+];
+
+// Corners of the cross quads in the XZ plane, kept off the cell edges like plant sprites
+const CROSS_DIAGONALS: [([f32; 2], [f32; 2]); 2] = [([0.1, 0.1], [0.9, 0.9]), ([0.1, 0.9], [0.9, 0.1])];
+
+/// Whether a fence reaches over to its `neighbour`
+fn fence_connects(neighbour: VoxelType) -> bool {
+    neighbour.shape() == Shape::Fence || neighbour.is_opaque_cube()
+}
+
+const HORIZONTAL_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// A fence arm from the post towards (dx, dz), `half_width` sixteenths to each side
+fn fence_arm((dx, dz): (i32, i32), bottom: f32, top: f32, half_width: f32) -> ModelBox {
+    let (near, far) = (8.0 - half_width, 8.0 + half_width);
+    let (x, z) = match (dx, dz) {
+        (1, _) => ((10.0, 16.0), (near, far)),
+        (-1, _) => ((0.0, 6.0), (near, far)),
+        (_, 1) => ((near, far), (10.0, 16.0)),
+        _ => ((near, far), (0.0, 6.0)),
+    };
+    ModelBox::pixels([x.0, bottom, z.0], [x.1, top, z.1])
+}
+
+/// The lower slab and the raised step on the back half of a stairs block
+fn stairs_boxes(state: BlockState) -> Vec<ModelBox> {
+    let ((slab_bottom, slab_top), (step_bottom, step_top)) = match state.half() {
+        Half::Bottom => ((0.0, 8.0), (8.0, 16.0)),
+        Half::Top => ((8.0, 16.0), (0.0, 8.0)),
+    };
+    // The front faces the player who placed it, so the step sits away from them
+    let ((x0, x1), (z0, z1)) = match state.facing() {
+        Facing::South => ((0.0, 16.0), (0.0, 8.0)),
+        Facing::North => ((0.0, 16.0), (8.0, 16.0)),
+        Facing::West => ((8.0, 16.0), (0.0, 16.0)),
+        Facing::East => ((0.0, 8.0), (0.0, 16.0)),
+    };
+    vec![
+        ModelBox::pixels([0.0, slab_bottom, 0.0], [16.0, slab_top, 16.0]),
+        ModelBox::pixels([x0, step_bottom, z0], [x1, step_top, z1]),
+    ]
+}
+
+fn slab_box(state: BlockState) -> ModelBox {
+    match state.half() {
+        Half::Bottom => ModelBox::pixels([0.0; 3], [16.0, 8.0, 16.0]),
+        Half::Top => ModelBox::pixels([0.0, 8.0, 0.0], [16.0; 3]),
+    }
+}
+
+impl BlockModel {
+    /// The model of `block` in `state`. `neighbour(dx, dz)` gives the horizontally
+    /// adjacent blocks, which fences connect to.
+    pub fn new(block: VoxelType, state: BlockState, neighbour: impl Fn(i32, i32) -> VoxelType) -> Self {
+        match block.shape() {
+            Shape::Cube => BlockModel::Cube,
+            Shape::Slab => BlockModel::Boxes(vec![slab_box(state)]),
+            Shape::Stairs => BlockModel::Boxes(stairs_boxes(state)),
+            Shape::Fence => {
+                let mut boxes = vec![ModelBox::pixels([6.0, 0.0, 6.0], [10.0, 16.0, 10.0])];
+                for direction in HORIZONTAL_DIRECTIONS {
+                    if fence_connects(neighbour(direction.0, direction.1)) {
+                        boxes.push(fence_arm(direction, 6.0, 9.0, 1.0));
+                        boxes.push(fence_arm(direction, 12.0, 15.0, 1.0));
+                    }
+                }
+                BlockModel::Boxes(boxes)
+            }
+            Shape::Cross => BlockModel::Cross,
+        }
+    }
+
+    /// Boxes that stop the player, relative to the cell. Fences stand taller than their
+    /// cell so they can't be jumped over.
+    pub fn collision_boxes(block: VoxelType, state: BlockState, neighbour: impl Fn(i32, i32) -> VoxelType) -> Vec<ModelBox> {
+        match block.shape() {
+            Shape::Cube => vec![ModelBox::FULL],
+            Shape::Slab => vec![slab_box(state)],
+            Shape::Stairs => stairs_boxes(state),
+            Shape::Fence => {
+                let mut boxes = vec![ModelBox::pixels([6.0, 0.0, 6.0], [10.0, 24.0, 10.0])];
+                for direction in HORIZONTAL_DIRECTIONS {
+                    if fence_connects(neighbour(direction.0, direction.1)) {
+                        boxes.push(fence_arm(direction, 0.0, 24.0, 2.0));
+                    }
+                }
+                boxes
+            }
+            Shape::Cross => Vec::new(),
+        }
+    }
+
+    /// The quads of the model for the block at local (x, y, z). Box faces on the cell
+    /// border are skipped where `hidden(face)` says a neighbour covers them.
+    pub fn quads(
+        &self,
+        block: VoxelType,
+        state: BlockState,
+        (x, y, z): (usize, usize, usize),
+        hidden: impl Fn(usize) -> bool,
+    ) -> Vec<ModelQuad> {
+        let origin = Vec3::new(x as f32, y as f32, z as f32);
+        match self {
+            BlockModel::Cube => Self::box_quads(&[ModelBox::FULL], block, state, origin, hidden),
+            BlockModel::Boxes(boxes) => Self::box_quads(boxes, block, state, origin, hidden),
+            BlockModel::Cross => Self::cross_quads(block.get_face_texture(0, state), origin),
+        }
+    }
+
+    fn box_quads(
+        boxes: &[ModelBox],
+        block: VoxelType,
+        state: BlockState,
+        origin: Vec3,
+        hidden: impl Fn(usize) -> bool,
+    ) -> Vec<ModelQuad> {
+        let mut quads = Vec::new();
+        for model_box in boxes {
+            let size = model_box.max - model_box.min;
+            for face in 0..6 {
+                if model_box.touches_border(face) && hidden(face) {
+                    continue;
+                }
+                let [u, v, tile_w, tile_h] = block.get_face_texture(face, state);
+                let (u_axis, u_flipped, v_axis, v_flipped) = FACE_UV_AXES[face];
+                let along = |corner: Vec3, axis: usize, flipped: bool| if flipped { 1.0 - corner[axis] } else { corner[axis] };
+
+                // Lay the unit cube face over the box and crop the texture to match
+                let (unit, _) = cube_face(0, 0, 0, [0.0; 4], face);
+                let corners: Vec<Vec3> = unit.iter().map(|p| model_box.min + Vec3::new(p[0], p[1], p[2]) * size).collect();
+                quads.push(ModelQuad {
+                    positions: corners.iter().map(|c| (origin + *c).extend(1.0).to_array()).collect(),
+                    uvs: corners
+                        .iter()
+                        .map(|&c| [u + along(c, u_axis, u_flipped) * tile_w, v + along(c, v_axis, v_flipped) * tile_h])
+                        .collect(),
+                    normal: FACE_NORMALS[face],
+                });
+            }
+        }
+        quads
+    }
+
+    fn cross_quads([u, v, tile_w, tile_h]: [f32; 4], origin: Vec3) -> Vec<ModelQuad> {
+        let mut quads = Vec::new();
+        for (start, end) in CROSS_DIAGONALS {
+            let corner = |[cx, cz]: [f32; 2], cy: f32| (origin + Vec3::new(cx, cy, cz)).extend(1.0).to_array();
+            let positions = vec![corner(end, 0.0), corner(end, 1.0), corner(start, 1.0), corner(start, 0.0)];
+            let uvs = vec![[u + tile_w, v], [u + tile_w, v + tile_h], [u, v + tile_h], [u, v]];
+            // Both windings so the quad shows from either side with face culling on. Plants
+            // are lit as if facing up so they don't darken with the view angle.
+            quads.push(ModelQuad {
+                positions: positions.iter().rev().copied().collect(),
+                uvs: uvs.iter().rev().copied().collect(),
+                normal: FACE_NORMALS[0],
+            });
+            quads.push(ModelQuad { positions, uvs, normal: FACE_NORMALS[0] });
+        }
+        quads
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FACINGS: [Facing; 4] = [Facing::South, Facing::West, Facing::North, Facing::East];
+
+    fn boxes(model: BlockModel) -> Vec<ModelBox> {
+        match model {
+            BlockModel::Boxes(boxes) => boxes,
+            other => panic!("expected boxes, got {:?}", other),
+        }
+    }
+
+    // Fence neighbours: another fence to the east, stone to the north, air elsewhere
+    fn fence_neighbour(dx: i32, dz: i32) -> VoxelType {
+        match (dx, dz) {
+            (1, 0) => VoxelType::WalnutFence,
+            (0, -1) => VoxelType::Stone,
+            _ => VoxelType::Air,
+        }
+    }
+
+    #[test]
+    fn stairs_step_sits_behind_the_front_on_the_other_half() {
+        for facing in FACINGS {
+            // Unit vector out of the front, in the XZ plane
+            let front = match facing {
+                Facing::South => Vec3::Z,
+                Facing::West => -Vec3::X,
+                Facing::North => -Vec3::Z,
+                Facing::East => Vec3::X,
+            };
+            for half in [Half::Bottom, Half::Top] {
+                let state = BlockState::default().with_facing(facing).with_half(half);
+                let [slab, step] = boxes(BlockModel::new(VoxelType::StoneStairs, state, |_, _| VoxelType::Air))[..] else {
+                    panic!("stairs are a slab and a step");
+                };
+                assert_eq!(BlockModel::collision_boxes(VoxelType::StoneStairs, state, |_, _| VoxelType::Air), vec![slab, step]);
+
+                let (slab_y, step_y) = match half {
+                    Half::Bottom => ((0.0, 0.5), (0.5, 1.0)),
+                    Half::Top => ((0.5, 1.0), (0.0, 0.5)),
+                };
+                assert_eq!((slab.min.y, slab.max.y), slab_y, "{:?} {:?}", facing, half);
+                assert_eq!((slab.min.x, slab.min.z, slab.max.x, slab.max.z), (0.0, 0.0, 1.0, 1.0));
+                assert_eq!((step.min.y, step.max.y), step_y, "{:?} {:?}", facing, half);
+
+                let step_centre = (step.min + step.max) / 2.0 - Vec3::splat(0.5);
+                assert_eq!(step_centre.dot(front), -0.25, "{:?} {:?}", facing, half);
+            }
+        }
+    }
+
+    #[test]
+    fn fences_reach_fences_and_opaque_blocks() {
+        let model = boxes(BlockModel::new(VoxelType::WalnutFence, BlockState::default(), fence_neighbour));
+        // The post and two rails towards each of the two neighbours
+        assert_eq!(model.len(), 5);
+        assert!(model.iter().any(|arm| arm.max.x == 1.0));
+        assert!(model.iter().any(|arm| arm.min.z == 0.0));
+        assert!(model.iter().all(|part| part.min.x > 0.0 && part.max.z < 1.0));
+
+        let collision = BlockModel::collision_boxes(VoxelType::WalnutFence, BlockState::default(), fence_neighbour);
+        assert_eq!(collision.len(), 3);
+        assert!(collision.iter().all(|part| part.max.y == 1.5));
+    }
+
+    #[test]
+    fn fences_stand_alone_next_to_transparent_blocks() {
+        for neighbour in [VoxelType::Air, VoxelType::Glass, VoxelType::Poppy, VoxelType::StoneSlab] {
+            let model = boxes(BlockModel::new(VoxelType::WalnutFence, BlockState::default(), |_, _| neighbour));
+            assert_eq!(model.len(), 1, "next to {:?}", neighbour);
+        }
+    }
+
+    #[test]
+    fn plants_are_crossed_quads_without_collision() {
+        let model = BlockModel::new(VoxelType::TallGrass, BlockState::default(), |_, _| VoxelType::Air);
+        assert_eq!(model, BlockModel::Cross);
+        assert!(BlockModel::collision_boxes(VoxelType::TallGrass, BlockState::default(), |_, _| VoxelType::Air).is_empty());
+        // Two diagonals, each showing from both sides
+        assert_eq!(model.quads(VoxelType::TallGrass, BlockState::default(), (0, 0, 0), |_| true).len(), 4);
+    }
+
+    #[test]
+    fn only_faces_on_the_cell_border_are_culled() {
+        let state = BlockState::default();
+        let cube = BlockModel::new(VoxelType::Stone, state, |_, _| VoxelType::Air);
+        assert_eq!(cube.quads(VoxelType::Stone, state, (0, 0, 0), |_| true).len(), 0);
+        assert_eq!(cube.quads(VoxelType::Stone, state, (0, 0, 0), |_| false).len(), 6);
+
+        // A bottom slab keeps its top face, which sits in the middle of the cell
+        let slab = BlockModel::new(VoxelType::StoneSlab, state, |_, _| VoxelType::Air);
+        let quads = slab.quads(VoxelType::StoneSlab, state, (0, 0, 0), |_| true);
+        assert_eq!(quads.len(), 1);
+        assert_eq!(quads[0].normal, [0.0, 1.0, 0.0]);
+        assert!(quads[0].positions.iter().all(|p| p[1] == 0.5));
+
+        // Hiding only the bottom leaves the other five faces
+        assert_eq!(slab.quads(VoxelType::StoneSlab, state, (0, 0, 0), |face| face == 1).len(), 5);
+    }
+}
@@ -1,18 +1,52 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    utils::{
-        cube_face::cube_face,
-        interpolate::{cosine_interpolate, exp_interpolate, power_curve, smoothstep_mid},
-        tree_gen::{generate_mahogany_tree, generate_spruce_tree, generate_walnut_tree},
-    },
-    voxel::VoxelType,
+    block_entity::{BlockEntity, SavedBlockEntity},
+    block_model::BlockModel,
+    utils::cube_face::cube_face,
+    voxel::{BlockState, Shape, VoxelType},
+    worldgen::{features::FeatureStart, pipeline::ChunkStatus},
 };
 use ferrousgl::{GlWindow, Mesh, Shader};
 use glam::{IVec3, Mat4, Vec3};
-use noise::{NoiseFn, Perlin, PerlinSurflet};
-use rand::Rng;
+
+// A face of a voxel at local (x, y, z), with its texture picked and rotated by the voxel's state
+fn oriented_cube_face(
+    voxel: VoxelType,
+    state: BlockState,
+    x: usize,
+    y: usize,
+    z: usize,
+    face: usize,
+) -> (Vec<[f32; 4]>, Vec<[f32; 2]>) {
+    let (positions, mut uvs) = cube_face(x, y, z, voxel.get_face_texture(face, state), face);
+    uvs.rotate_left(voxel.oriented_face(face, state).1);
+    (positions, uvs)
+}
+
+/// A block changed after generation, as it is serialised
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedEdit {
+    position: [usize; 3],
+    block: VoxelType,
+    #[serde(default)]
+    state: BlockState,
+}
+
+/// What an unloaded chunk keeps until it generates again
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedChunk {
+    #[serde(default)]
+    edits: Vec<SavedEdit>,
+    #[serde(default)]
+    block_entities: Vec<SavedBlockEntity>,
+}
 
 pub struct Chunk {
     pub voxels: Vec<VoxelType>, // Main chunk voxels (size x size x size)
+    pub states: Vec<BlockState>, // State bits of each main voxel, like the axis of a log
     pub extended_voxels: Vec<VoxelType>, // Extended voxels ((size+2) x (size+2) x (size+2))
     pub mesh: Option<Mesh>,
     pub vertices: Vec<f32>, // CPU-side vertex data (interleaved positions + UVs)
@@ -24,23 +58,24 @@ pub struct Chunk {
     // --- end ---
     pub size: usize,
     pub position: (i32, i32, i32),
-    pub seed: u32,
     pub(crate) needs_rebuild: bool,
-    /// Stores voxels generated by this chunk that are out of bounds (world coords)
-    pub out_of_bounds_voxels: Vec<(i32, i32, i32, VoxelType)>,
+    /// Last generation stage this chunk has completed
+    pub status: ChunkStatus,
+    /// Features starting in this chunk, placed by it and its neighbours in the features stage
+    pub feature_starts: Vec<FeatureStart>,
+    /// Local y of the highest non-air voxel per (x, z) column, -1 for empty columns
+    pub heightmap: Vec<i32>,
+    /// Per-block state like furnace contents, by local position
+    pub block_entities: HashMap<(usize, usize, usize), BlockEntity>,
+    /// Blocks mined or placed since generation, by local position
+    pub edits: HashMap<(usize, usize, usize), (VoxelType, BlockState)>,
 }
 
 impl Chunk {
-    pub fn new(
-        x: i32,
-        y: i32,
-        z: i32,
-        size: usize,
-        seed: u32,
-        pending_voxels: Option<&[(i32, i32, i32, VoxelType)]>,
-    ) -> Self {
-        let mut chunk = Self {
+    pub fn new(x: i32, y: i32, z: i32, size: usize) -> Self {
+        Self {
             voxels: vec![VoxelType::Air; size * size * size],
+            states: vec![BlockState::default(); size * size * size],
             extended_voxels: vec![VoxelType::Air; (size + 2) * (size + 2) * (size + 2)],
             mesh: None,
             vertices: Vec::new(),
@@ -50,16 +85,13 @@ impl Chunk {
             transparent_indices: Vec::new(),
             size,
             position: (x, y, z),
-            seed,
             needs_rebuild: true,
-            out_of_bounds_voxels: Vec::new(),
-        };
-
-        if let Some(voxels) = pending_voxels {
-            chunk.load_voxels_world(voxels);
+            status: ChunkStatus::Empty,
+            feature_starts: Vec::new(),
+            heightmap: vec![-1; size * size],
+            block_entities: HashMap::new(),
+            edits: HashMap::new(),
         }
-
-        chunk
     }
 
     pub fn get_extended_voxel(&self, x: i32, y: i32, z: i32) -> VoxelType {
@@ -130,397 +162,129 @@ impl Chunk {
         self.transparent_vertices.is_empty()
     }
 
+    pub fn get_state(&self, x: usize, y: usize, z: usize) -> BlockState {
+        self.states[self.index(x, y, z)]
+    }
+
+    /// Sets the voxel with its state bits reset
     pub fn set_voxel(&mut self, x: usize, y: usize, z: usize, v: VoxelType) {
+        self.set_voxel_with_state(x, y, z, v, BlockState::default());
+    }
+
+    pub fn set_voxel_with_state(&mut self, x: usize, y: usize, z: usize, v: VoxelType, state: BlockState) {
         let idx = self.index(x, y, z);
         self.voxels[idx] = v;
+        self.states[idx] = state;
         let ext_idx = self.extended_index(x, y, z);
         self.extended_voxels[ext_idx] = v;
+        self.update_heightmap(x, y, z);
+        self.update_block_entity(x, y, z, v);
     }
 
-    /// Generates voxel data for both main and extended areas
-    /// Stores out-of-bounds voxels in self.out_of_bounds_voxels.
-    pub fn generate_data(&mut self, world_x: i32, world_y: i32, world_z: i32) {
-        let perlin = Perlin::new(self.seed);
-        let height = Perlin::new(self.seed.wrapping_add(16));
-        let biome = Perlin::new(self.seed.wrapping_add(32));
-        let heat = Perlin::new(self.seed.wrapping_add(64));
-        let mut rng = rand::rng();
-
-        self.out_of_bounds_voxels.clear();
-
-        let main_biome_scale = 0.5;
-
-        // First pass: generate solid terrain (stone) and air
-        for x in 0..self.size + 2 {
-            for y in 0..self.size + 2 {
-                for z in 0..self.size + 2 {
-                    let wx = (world_x * self.size as i32) + (x as i32 - 1);
-                    let wy = (world_y * self.size as i32) + (y as i32 - 1);
-                    let wz = (world_z * self.size as i32) + (z as i32 - 1);
-
-                    // Add global height control noise
-                    let global_height_scale = 0.001; // Adjust this for larger/smaller features
-                    let global_height_factor = -64.0; // Adjust this for stronger/weaker effect
-                    let mut height_adjustment = height.get([
-                        wx as f64 * global_height_scale,
-                        wz as f64 * global_height_scale,
-                    ]);
-                    height_adjustment = height_adjustment / 2.0 + 0.5; // Normalize to 0..1
-                    height_adjustment = height_adjustment * 0.99;
-                    height_adjustment = smoothstep_mid(height_adjustment as f32, 32.0) as f64;
-                    let adjusted_wy = wy as f64 - (height_adjustment * global_height_factor);
-
-                    // Biome noise calculations (using adjusted_wy where height matters)
-                    let mut biome_value1 = biome.get([
-                        wx as f64 * 0.003 * main_biome_scale,
-                        wz as f64 * 0.003 * main_biome_scale,
-                    ]);
-                    biome_value1 = biome_value1 / 2.0 + 0.5;
-                    let mut biome_value2 = biome.get([
-                        wx as f64 * 0.003 * main_biome_scale,
-                        wz as f64 * 0.00 * main_biome_scale,
-                    ]);
-                    biome_value2 = biome_value2 / 2.0 + 0.5;
-                    let mut biome_value3 = biome.get([
-                        wx as f64 * 0.03 * main_biome_scale,
-                        wz as f64 * 0.03 * main_biome_scale,
-                    ]);
-                    biome_value3 = biome_value3 / 2.0 + 0.5;
-                    let biome_value = biome_value1 * 0.5 + biome_value2 * 0.3 + biome_value3 * 0.2;
-
-                    let mut plains_noise = perlin.get([wx as f64 * 0.01, wz as f64 * 0.01]);
-                    plains_noise = plains_noise / 2.0 + 0.5;
-                    let mut plains_height_controller =
-                        height.get([wx as f64 * 0.001, wz as f64 * 0.001]);
-                    plains_height_controller = plains_height_controller / 2.0 + 0.5;
-                    let plains = plains_noise - (adjusted_wy - 4.0) * 0.03; // Use adjusted_wy
-
-                    let mut mountain_noise = perlin.get([
-                        wx as f64 * 0.02 + 456.78,
-                        adjusted_wy * 0.003, // Use adjusted_wy
-                        wz as f64 * 0.02 + 234.56,
-                    ]);
-                    mountain_noise = mountain_noise / 2.0 + 0.5;
-                    let mut mountain_noise_2 = perlin.get([wx as f64 * 0.001, wz as f64 * 0.001]);
-                    mountain_noise_2 = mountain_noise_2 / 2.0 + 0.5;
-                    let mut mountain_noise_3 = perlin.get([
-                        wx as f64 * 0.06,
-                        adjusted_wy * 0.05, // Use adjusted_wy
-                        wz as f64 * 0.06,
-                    ]) - (adjusted_wy - 4.0) * 0.0056; // Use adjusted_wy
-                    mountain_noise_3 = mountain_noise_3 / 2.0 + 0.5;
-                    let mountain =
-                        (mountain_noise * 0.7 + mountain_noise_2 * 0.6 + mountain_noise_3 * 0.3)
-                            - (adjusted_wy - 4.0) * 0.005; // Use adjusted_wy
-
-                    let mut forest_noise = perlin.get([wx as f64 * 0.01, wz as f64 * 0.01]);
-                    forest_noise = forest_noise / 2.0 + 0.5;
-                    let forest = (forest_noise) - (adjusted_wy - 12.0) * 0.01; // Use adjusted_wy
-
-                    let mut v = if biome_value > 0.4 && biome_value < 0.6 {
-                        if plains > 0.0 {
-                            VoxelType::Stone
-                        } else {
-                            VoxelType::Air
-                        }
-                    } else if biome_value < 0.4 {
-                        let biome_blend = (0.4 - biome_value) / 0.4;
-                        if cosine_interpolate(
-                            plains as f32,
-                            mountain as f32,
-                            power_curve(biome_blend as f32, 0.3) as f32,
-                        ) > 0.0
-                        {
-                            VoxelType::Stone
-                        } else {
-                            VoxelType::Air
-                        }
-                    } else {
-                        let biome_blend = (biome_value - 0.6) / 0.4;
-                        let blend_amount = power_curve(biome_blend as f32, 0.3) as f32;
-                        if cosine_interpolate(
-                            plains as f32,
-                            forest as f32,
-                            power_curve(blend_amount, 1.6),
-                        ) > 0.0
-                        {
-                            VoxelType::Stone
-                        } else {
-                            VoxelType::Air
-                        }
-                    };
+    /// Sets a voxel on behalf of the player, remembering it so it survives unloading
+    pub fn edit_voxel(&mut self, x: usize, y: usize, z: usize, v: VoxelType, state: BlockState) {
+        self.edits.insert((x, y, z), (v, state));
+        self.set_voxel_with_state(x, y, z, v, state);
+    }
 
-                    // Place water under y < -32
-                    if wy < -16 && v == VoxelType::Air {
-                        v = VoxelType::Water;
-                    }
+    // Gives a newly placed block its block entity and drops the one of a replaced block
+    fn update_block_entity(&mut self, x: usize, y: usize, z: usize, v: VoxelType) {
+        let position = (x, y, z);
+        if self.block_entities.get(&position).is_some_and(|entity| entity.block() == v) {
+            return;
+        }
+        match BlockEntity::for_block(v) {
+            Some(entity) => {
+                self.block_entities.insert(position, entity);
+            }
+            None => {
+                self.block_entities.remove(&position);
+            }
+        }
+    }
 
-                    let ext_idx = x + (self.size + 2) * (y + (self.size + 2) * z);
-                    self.extended_voxels[ext_idx] = v;
+    pub fn block_entity_mut(&mut self, x: usize, y: usize, z: usize) -> Option<&mut BlockEntity> {
+        self.block_entities.get_mut(&(x, y, z))
+    }
 
-                    // Only store in main voxels if within bounds
-                    if x > 0 && y > 0 && z > 0 && x <= self.size && y <= self.size && z <= self.size
-                    {
-                        let idx = self.index(x - 1, y - 1, z - 1);
-                        self.voxels[idx] = v;
-                    }
-                }
-            }
+    pub fn tick_block_entities(&mut self, delta_time: f32) {
+        for entity in self.block_entities.values_mut() {
+            entity.tick(delta_time);
         }
+    }
 
-        // Second pass: replace surface blocks with appropriate types
-        for x in 0..self.size + 2 {
-            for y in 0..self.size + 2 {
-                for z in 0..self.size + 2 {
-                    let ext_idx = x + (self.size + 2) * (y + (self.size + 2) * z);
-                    if self.extended_voxels[ext_idx] == VoxelType::Stone {
-                        // Check if this block is adjacent to air (surface block)
-                        let mut is_surface = false;
-                        // Check all 6 directions
-                        for (dx, dy, dz) in &[
-                            (1, 0, 0),
-                            (-1, 0, 0),
-                            (0, 1, 0),
-                            (0, -1, 0),
-                            (0, 0, 1),
-                            (0, 0, -1),
-                        ] {
-                            let nx = x as i32 + dx;
-                            let ny = y as i32 + dy;
-                            let nz = z as i32 + dz;
-
-                            if nx >= 0
-                                && ny >= 0
-                                && nz >= 0
-                                && nx < (self.size + 2) as i32
-                                && ny < (self.size + 2) as i32
-                                && nz < (self.size + 2) as i32
-                            {
-                                let neighbor_idx = nx as usize
-                                    + (self.size + 2)
-                                        * (ny as usize + (self.size + 2) * nz as usize);
-                                if self.extended_voxels[neighbor_idx] == VoxelType::Air {
-                                    is_surface = true;
-                                    break;
-                                }
-                            } else {
-                                // Edge of the chunk - consider it surface
-                                is_surface = false;
-                                break;
-                            }
-                        }
+    /// Serialises the chunk's edits and block entities, None if it has neither
+    pub fn save(&self) -> Option<String> {
+        if self.edits.is_empty() && self.block_entities.is_empty() {
+            return None;
+        }
+        let saved = SavedChunk {
+            edits: self
+                .edits
+                .iter()
+                .map(|(&(x, y, z), &(block, state))| SavedEdit { position: [x, y, z], block, state })
+                .collect(),
+            block_entities: self
+                .block_entities
+                .iter()
+                .map(|(&(x, y, z), entity)| SavedBlockEntity {
+                    position: [x, y, z],
+                    state: self.get_state(x, y, z),
+                    entity: entity.clone(),
+                })
+                .collect(),
+        };
+        serde_json::to_string(&saved).ok()
+    }
 
-                        if is_surface {
-                            let wx = (world_x * self.size as i32) + (x as i32 - 1);
-                            let wy = (world_y * self.size as i32) + (y as i32 - 1);
-                            let wz = (world_z * self.size as i32) + (z as i32 - 1);
-
-                            let mut biome_value1 = biome.get([
-                                wx as f64 * 0.003 * main_biome_scale,
-                                wz as f64 * 0.003 * main_biome_scale,
-                            ]);
-                            biome_value1 = biome_value1 / 2.0 + 0.5;
-                            let mut biome_value2 = biome.get([
-                                wx as f64 * 0.003 * main_biome_scale,
-                                wz as f64 * 0.00 * main_biome_scale,
-                            ]);
-                            biome_value2 = biome_value2 / 2.0 + 0.5;
-                            let mut biome_value3 = biome.get([
-                                wx as f64 * 0.03 * main_biome_scale,
-                                wz as f64 * 0.03 * main_biome_scale,
-                            ]);
-                            biome_value3 = biome_value3 / 2.0 + 0.5;
-                            let biome_value =
-                                biome_value1 * 0.5 + biome_value2 * 0.3 + biome_value3 * 0.2;
-
-                            let mut mountain_detail_noise = 0.0;
-                            let mut frequency = 0.03;
-                            let mut amplitude = 1.0;
-                            let persistence = 0.5;
-
-                            for _ in 0..3 {
-                                mountain_detail_noise += perlin.get([
-                                    wx as f64 * frequency,
-                                    wy as f64 * frequency,
-                                    wz as f64 * frequency,
-                                ]) * amplitude;
-
-                                frequency *= 2.0;
-                                amplitude *= persistence;
-                            }
+    /// Puts back what `save` kept over the freshly generated blocks. Edits stay recorded
+    /// so they are saved again on the next unload.
+    pub fn restore(&mut self, data: &str) -> serde_json::Result<()> {
+        let saved: SavedChunk = serde_json::from_str(data)?;
+        for SavedEdit { position: [x, y, z], block, state } in saved.edits {
+            self.edit_voxel(x, y, z, block, state);
+        }
+        for SavedBlockEntity { position: [x, y, z], state, entity } in saved.block_entities {
+            self.set_voxel_with_state(x, y, z, entity.block(), state);
+            self.block_entities.insert((x, y, z), entity);
+        }
+        Ok(())
+    }
 
-                            let mut heat_value_1 = heat.get([wx as f64 * 0.001, wz as f64 * 0.001]);
-                            heat_value_1 = heat_value_1 / 2.0 + 0.5;
-                            let mut heat_value_2 = heat.get([wx as f64 * 0.01, wz as f64 * 0.01]);
-                            heat_value_2 = heat_value_2 / 2.0 + 0.5;
-                            let mut heat_value_3 = heat.get([wx as f64 * 0.1, wz as f64 * 0.1]);
-                            heat_value_3 = heat_value_3 / 2.0 + 0.5;
-
-                            let heat_value =
-                                heat_value_1 * 0.7 + heat_value_2 * 0.2 + heat_value_3 * 0.1;
-
-                            if biome_value > 0.4 && biome_value < 0.6 {
-                                // grass
-                                if heat_value < 0.55 {
-                                    self.extended_voxels[ext_idx] = VoxelType::Grass;
-                                } else {
-                                    self.extended_voxels[ext_idx] = VoxelType::Sand;
-                                }
-                            } else if biome_value < 0.4 {
-                                // mountains
-                                let snow_probability = if wy < 50 {
-                                    if rng.random_bool(0.01) {
-                                        let tree_voxels = generate_spruce_tree((wx, wy + 1, wz));
-                                        for (tx, ty, tz, vtype) in tree_voxels {
-                                            let lx = tx - world_x * self.size as i32;
-                                            let ly = ty - world_y * self.size as i32;
-                                            let lz = tz - world_z * self.size as i32;
-                                            if lx < 0
-                                                || lx >= self.size as i32
-                                                || ly < 0
-                                                || ly >= self.size as i32
-                                                || lz < 0
-                                                || lz >= self.size as i32
-                                            {
-                                                self.out_of_bounds_voxels.push((tx, ty, tz, vtype));
-                                            } else {
-                                                let idx = self.index(
-                                                    lx as usize,
-                                                    ly as usize,
-                                                    lz as usize,
-                                                );
-                                                self.voxels[idx] = vtype;
-                                                let ext_idx = self.extended_index(
-                                                    lx as usize,
-                                                    ly as usize,
-                                                    lz as usize,
-                                                );
-                                                self.extended_voxels[ext_idx] = vtype;
-                                            }
-                                        }
-                                    }
-                                    0.0
-                                } else if wy > 100 {
-                                    1.0
-                                } else {
-                                    (wy - 50) as f32 / 30.0 // Linear increase from 0 to 1 between Y=80 and Y=100
-                                };
-
-                                // Random chance based on snow probability
-                                if rng.random_range(0.0..1.0) < snow_probability {
-                                    if mountain_detail_noise > 0.0 {
-                                        self.extended_voxels[ext_idx] = VoxelType::Snow // Changed from Gravel to Snow
-                                    } else {
-                                        self.extended_voxels[ext_idx] = VoxelType::Ice // Changed from Stone to Ice
-                                    }
-                                } else {
-                                    if mountain_detail_noise > 0.3333 {
-                                        self.extended_voxels[ext_idx] = VoxelType::Gravel
-                                    } else if mountain_detail_noise < -0.3333 {
-                                        self.extended_voxels[ext_idx] = VoxelType::Grass
-                                    } else {
-                                        self.extended_voxels[ext_idx] = VoxelType::Stone
-                                    }
-                                }
-                            } else {
-                                // forest
-                                if heat_value < 0.575 {
-                                    self.extended_voxels[ext_idx] = VoxelType::Grass;
-
-                                    if heat_value < 0.575
-                                        && biome_value > 0.6
-                                        && rng.random_bool(0.05)
-                                    {
-                                        generate_walnut_tree((wx, wy + 1, wz))
-                                            .into_iter()
-                                            .for_each(|(tx, ty, tz, vtype)| {
-                                                let lx = tx - world_x * self.size as i32;
-                                                let ly = ty - world_y * self.size as i32;
-                                                let lz = tz - world_z * self.size as i32;
-                                                if lx < 0
-                                                    || lx >= self.size as i32
-                                                    || ly < 0
-                                                    || ly >= self.size as i32
-                                                    || lz < 0
-                                                    || lz >= self.size as i32
-                                                {
-                                                    self.out_of_bounds_voxels
-                                                        .push((tx, ty, tz, vtype));
-                                                } else {
-                                                    let idx = self.index(
-                                                        lx as usize,
-                                                        ly as usize,
-                                                        lz as usize,
-                                                    );
-                                                    self.voxels[idx] = vtype;
-                                                    let ext_idx = self.extended_index(
-                                                        lx as usize,
-                                                        ly as usize,
-                                                        lz as usize,
-                                                    );
-                                                    self.extended_voxels[ext_idx] = vtype;
-                                                }
-                                            });
-                                    }
-                                } else {
-                                    self.extended_voxels[ext_idx] = VoxelType::Grass;
-
-                                    if heat_value > 0.575
-                                        && biome_value > 0.6
-                                        && rng.random_bool(0.005)
-                                    {
-                                        generate_mahogany_tree((wx, wy + 1, wz))
-                                            .into_iter()
-                                            .for_each(|(tx, ty, tz, vtype)| {
-                                                let lx = tx - world_x * self.size as i32;
-                                                let ly = ty - world_y * self.size as i32;
-                                                let lz = tz - world_z * self.size as i32;
-                                                if lx < 0
-                                                    || lx >= self.size as i32
-                                                    || ly < 0
-                                                    || ly >= self.size as i32
-                                                    || lz < 0
-                                                    || lz >= self.size as i32
-                                                {
-                                                    self.out_of_bounds_voxels
-                                                        .push((tx, ty, tz, vtype));
-                                                } else {
-                                                    let idx = self.index(
-                                                        lx as usize,
-                                                        ly as usize,
-                                                        lz as usize,
-                                                    );
-                                                    self.voxels[idx] = vtype;
-                                                    let ext_idx = self.extended_index(
-                                                        lx as usize,
-                                                        ly as usize,
-                                                        lz as usize,
-                                                    );
-                                                    self.extended_voxels[ext_idx] = vtype;
-                                                }
-                                            });
-                                    }
-                                }
-                            }
+    /// Highest non-air voxel in column (x, z) as a local y
+    pub fn height_at(&self, x: usize, z: usize) -> Option<usize> {
+        let height = self.heightmap[x + self.size * z];
+        (height >= 0).then_some(height as usize)
+    }
 
-                            // Update main voxels if within bounds
-                            if x > 0
-                                && y > 0
-                                && z > 0
-                                && x <= self.size
-                                && y <= self.size
-                                && z <= self.size
-                            {
-                                let idx = self.index(x - 1, y - 1, z - 1);
-                                self.voxels[idx] = self.extended_voxels[ext_idx];
-                            }
-                        }
-                    }
-                }
+    /// Recomputes the heightmap of every column, after generation wrote the voxels
+    pub fn rebuild_heightmap(&mut self) {
+        for z in 0..self.size {
+            for x in 0..self.size {
+                self.heightmap[x + self.size * z] = self.column_top(x, self.size, z);
             }
         }
     }
 
+    // Keeps the heightmap in sync after the voxel at (x, y, z) changed
+    fn update_heightmap(&mut self, x: usize, y: usize, z: usize) {
+        let column = x + self.size * z;
+        let height = self.heightmap[column];
+        if self.get_voxel(x, y, z) != VoxelType::Air {
+            self.heightmap[column] = height.max(y as i32);
+        } else if height == y as i32 {
+            self.heightmap[column] = self.column_top(x, y, z);
+        }
+    }
+
+    // Highest non-air voxel below `below` in column (x, z), or -1
+    fn column_top(&self, x: usize, below: usize, z: usize) -> i32 {
+        (0..below)
+            .rev()
+            .find(|&y| self.get_voxel(x, y, z) != VoxelType::Air)
+            .map_or(-1, |y| y as i32)
+    }
+
     /// Prepares mesh data on CPU using extended voxel information
     // chunk.rs
     pub fn prepare_mesh(&mut self) {
@@ -545,6 +309,9 @@ impl Chunk {
             [1.0, 0.0, 0.0],  // Right
             [-1.0, 0.0, 0.0], // Left
         ];
+        // Offset to the neighbour across each face, in the same order
+        const FACE_DIRECTIONS: [(isize, isize, isize); 6] =
+            [(0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1), (1, 0, 0), (-1, 0, 0)];
 
         for x in 1..=self.size {
             for y in 1..=self.size {
@@ -553,6 +320,33 @@ impl Chunk {
                     if voxel == VoxelType::Air {
                         continue;
                     }
+                    let state = self.states[self.index(x - 1, y - 1, z - 1)];
+
+                    if voxel.shape() != Shape::Cube {
+                        // Slabs, stairs, fences and plants mesh from their block model
+                        let neighbour = |dx: isize, dy: isize, dz: isize| {
+                            let (nx, ny, nz) = (x as isize + dx, y as isize + dy, z as isize + dz);
+                            self.extended_voxels[nx as usize + (self.size + 2) * (ny as usize + (self.size + 2) * nz as usize)]
+                        };
+                        let model = BlockModel::new(voxel, state, |dx, dz| neighbour(dx as isize, 0, dz as isize));
+                        let hidden = |face: usize| {
+                            let (dx, dy, dz) = FACE_DIRECTIONS[face];
+                            neighbour(dx, dy, dz).is_opaque_cube()
+                        };
+                        let (target_positions, target_uvs, target_normals) = if voxel.is_transparent() {
+                            (&mut trans_positions, &mut trans_uvs, &mut trans_normals)
+                        } else {
+                            (&mut positions, &mut uvs, &mut normals)
+                        };
+                        for quad in model.quads(voxel, state, (x - 1, y - 1, z - 1), hidden) {
+                            target_positions.extend_from_slice(&quad.positions);
+                            target_uvs.extend_from_slice(&quad.uvs);
+                            for _ in 0..4 {
+                                target_normals.extend_from_slice(&quad.normal);
+                            }
+                        }
+                        continue;
+                    }
 
                     if voxel.is_transparent() {
                         if voxel.transparent_optimize_outer_only() {
@@ -578,13 +372,7 @@ impl Chunk {
                                     let local_y = y - 1;
                                     let local_z = z - 1;
 
-                                    let (face_verts, face_uvs) = cube_face(
-                                        local_x,
-                                        local_y,
-                                        local_z,
-                                        voxel.get_face_texture(face),
-                                        face,
-                                    );
+                                    let (face_verts, face_uvs) = oriented_cube_face(voxel, state, local_x, local_y, local_z, face);
                                     trans_positions.extend_from_slice(&face_verts);
                                     trans_uvs.extend_from_slice(&face_uvs);
                                     // Add normal for each vertex in this face (4 vertices)
@@ -625,13 +413,7 @@ impl Chunk {
                                 let local_z = z - 1;
 
                                 for face in 0..6 {
-                                    let (face_verts, face_uvs) = cube_face(
-                                        local_x,
-                                        local_y,
-                                        local_z,
-                                        voxel.get_face_texture(face),
-                                        face,
-                                    );
+                                    let (face_verts, face_uvs) = oriented_cube_face(voxel, state, local_x, local_y, local_z, face);
                                     trans_positions.extend_from_slice(&face_verts);
                                     trans_uvs.extend_from_slice(&face_uvs);
                                     // Add normal for each vertex in this face (4 vertices)
@@ -658,18 +440,13 @@ impl Chunk {
                             let neighbor = self.extended_voxels[nx as usize
                                 + (self.size + 2) * (ny as usize + (self.size + 2) * nz as usize)];
 
-                            if neighbor == VoxelType::Air || neighbor.is_transparent() {
+                            // Slabs, stairs and the like leave gaps, so only full opaque cubes hide a face
+                            if !neighbor.is_opaque_cube() {
                                 let local_x = x - 1;
                                 let local_y = y - 1;
                                 let local_z = z - 1;
 
-                                let (face_verts, face_uvs) = cube_face(
-                                    local_x,
-                                    local_y,
-                                    local_z,
-                                    voxel.get_face_texture(face),
-                                    face,
-                                );
+                                let (face_verts, face_uvs) = oriented_cube_face(voxel, state, local_x, local_y, local_z, face);
                                 positions.extend_from_slice(&face_verts);
                                 uvs.extend_from_slice(&face_uvs);
                                 // Add normal for each vertex in this face (4 vertices)
@@ -818,3 +595,61 @@ impl Chunk {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        inventory::ItemStack,
+        item::{Item, Material},
+        voxel::{Facing, Half},
+    };
+
+    #[test]
+    fn edits_and_block_entities_survive_save_and_restore() {
+        let generate = |chunk: &mut Chunk| chunk.set_voxel(4, 5, 6, VoxelType::Stone);
+        let mut chunk = Chunk::new(0, 0, 0, 16);
+        generate(&mut chunk);
+        let chest_state = BlockState::default().with_facing(Facing::East);
+        let furnace_state = BlockState::default().with_facing(Facing::North).with_half(Half::Top);
+        chunk.edit_voxel(1, 2, 3, VoxelType::Chest, chest_state);
+        chunk.edit_voxel(15, 0, 7, VoxelType::Furnace, furnace_state);
+        chunk.edit_voxel(4, 5, 6, VoxelType::Air, BlockState::default());
+
+        let stick = ItemStack::new(Item::Material(Material::Stick), 12);
+        let sand = ItemStack::new(Item::Block(VoxelType::Sand), 5);
+        let Some(BlockEntity::Chest(chest)) = chunk.block_entity_mut(1, 2, 3) else {
+            panic!("placing a chest adds its block entity");
+        };
+        chest.slots[4] = Some(stick);
+        let Some(BlockEntity::Furnace(furnace)) = chunk.block_entity_mut(15, 0, 7) else {
+            panic!("placing a furnace adds its block entity");
+        };
+        furnace.slots[0] = Some(sand);
+
+        let saved = chunk.save().expect("edits to save");
+        let mut restored = Chunk::new(0, 0, 0, 16);
+        generate(&mut restored);
+        restored.restore(&saved).unwrap();
+
+        assert_eq!(restored.get_voxel(4, 5, 6), VoxelType::Air);
+        assert_eq!(restored.height_at(4, 6), None);
+        assert_eq!(restored.get_voxel(1, 2, 3), VoxelType::Chest);
+        assert_eq!(restored.get_state(1, 2, 3), chest_state);
+        assert_eq!(restored.get_voxel(15, 0, 7), VoxelType::Furnace);
+        assert_eq!(restored.get_state(15, 0, 7), furnace_state);
+        let contents = |chunk: &mut Chunk, x, y, z| chunk.block_entity_mut(x, y, z).map(|entity| entity.contents());
+        assert_eq!(contents(&mut restored, 1, 2, 3), Some(vec![stick]));
+        assert_eq!(contents(&mut restored, 15, 0, 7), Some(vec![sand]));
+
+        // A second unload keeps the same edits
+        assert_eq!(restored.edits, chunk.edits);
+    }
+
+    #[test]
+    fn unedited_chunks_save_nothing() {
+        let mut chunk = Chunk::new(0, 0, 0, 16);
+        chunk.set_voxel(0, 0, 0, VoxelType::Stone);
+        assert_eq!(chunk.save(), None);
+    }
+}
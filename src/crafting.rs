@@ -0,0 +1,418 @@
+// crafting.rs
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{inventory::ItemStack, item::Item};
+
+/// Crafting grids are always this many slots wide and high, filled row by row
+pub const GRID_SIZE: usize = 3;
+pub const GRID_SLOTS: usize = GRID_SIZE * GRID_SIZE;
+
+/// What a recipe accepts in one slot: a specific item or any item out of a list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Ingredient {
+    Item(Item),
+    AnyOf(Vec<Item>),
+}
+
+impl Ingredient {
+    pub fn matches(&self, item: Item) -> bool {
+        match self {
+            Ingredient::Item(wanted) => *wanted == item,
+            Ingredient::AnyOf(items) => items.contains(&item),
+        }
+    }
+}
+
+/// A crafting recipe. Shaped recipes match their pattern anywhere in the grid and also
+/// mirrored left to right, shapeless recipes match their ingredients in any slots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Recipe {
+    Shaped {
+        /// Rows of key characters, a space is an empty slot
+        pattern: Vec<String>,
+        key: HashMap<char, Ingredient>,
+        result: ItemStack,
+    },
+    Shapeless {
+        ingredients: Vec<Ingredient>,
+        result: ItemStack,
+    },
+}
+
+/// Bounds of the occupied slots in a crafting grid as (min column, min row, width, height)
+fn occupied_bounds(grid: &[Option<ItemStack>; GRID_SLOTS]) -> Option<(usize, usize, usize, usize)> {
+    let occupied = (0..GRID_SLOTS).filter(|&i| grid[i].is_some());
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (GRID_SIZE, GRID_SIZE, 0, 0);
+    let mut any = false;
+    for i in occupied {
+        let (x, y) = (i % GRID_SIZE, i / GRID_SIZE);
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+        any = true;
+    }
+    any.then(|| (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1))
+}
+
+impl Recipe {
+    pub fn result(&self) -> ItemStack {
+        match self {
+            Recipe::Shaped { result, .. } | Recipe::Shapeless { result, .. } => *result,
+        }
+    }
+
+    fn pattern_size(pattern: &[String]) -> (usize, usize) {
+        let width = pattern.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        (width, pattern.len())
+    }
+
+    /// The key character at column `x` of `row`, or a space past the end of a short row
+    fn pattern_char(row: &str, x: usize) -> char {
+        row.chars().nth(x).unwrap_or(' ')
+    }
+
+    /// Checks that the recipe can be matched at all: the pattern fits in the grid, has no
+    /// blank rows or columns around it and every character in it has a key
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Recipe::Shaped { pattern, key, result } => {
+                let (width, height) = Self::pattern_size(pattern);
+                if width == 0 || height == 0 || width > GRID_SIZE || height > GRID_SIZE {
+                    return Err(format!("Pattern for {:?} must be between 1x1 and {}x{}", result.item, GRID_SIZE, GRID_SIZE));
+                }
+                let blank_row = |y: usize| pattern[y].chars().all(|c| c == ' ');
+                let blank_column = |x: usize| pattern.iter().all(|row| Self::pattern_char(row, x) == ' ');
+                if blank_row(0) || blank_row(height - 1) || blank_column(0) || blank_column(width - 1) {
+                    return Err(format!("Pattern for {:?} has blank rows or columns around it", result.item));
+                }
+                if let Some(c) = pattern.iter().flat_map(|row| row.chars()).find(|c| *c != ' ' && !key.contains_key(c)) {
+                    return Err(format!("Pattern for {:?} uses '{}' which is not in its key", result.item, c));
+                }
+            }
+            Recipe::Shapeless { ingredients, result } => {
+                if ingredients.is_empty() || ingredients.len() > GRID_SLOTS {
+                    return Err(format!("Shapeless recipe for {:?} needs 1 to {} ingredients", result.item, GRID_SLOTS));
+                }
+            }
+        }
+        if self.result().count == 0 {
+            return Err(format!("Recipe for {:?} produces nothing", self.result().item));
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, grid: &[Option<ItemStack>; GRID_SLOTS]) -> bool {
+        match self {
+            Recipe::Shaped { pattern, key, .. } => {
+                let Some((min_x, min_y, width, height)) = occupied_bounds(grid) else {
+                    return false;
+                };
+                if (width, height) != Self::pattern_size(pattern) {
+                    return false;
+                }
+                let fits = |mirrored: bool| {
+                    (0..height).all(|y| {
+                        (0..width).all(|x| {
+                            let column = if mirrored { width - 1 - x } else { x };
+                            let wanted = Self::pattern_char(&pattern[y], column);
+                            match (&grid[(min_y + y) * GRID_SIZE + min_x + x], key.get(&wanted)) {
+                                (None, None) => true,
+                                (Some(stack), Some(ingredient)) => ingredient.matches(stack.item),
+                                _ => false,
+                            }
+                        })
+                    })
+                };
+                fits(false) || fits(true)
+            }
+            Recipe::Shapeless { ingredients, .. } => {
+                let items: Vec<Item> = grid.iter().flatten().map(|stack| stack.item).collect();
+                items.len() == ingredients.len() && Self::assign(ingredients, &items).is_some()
+            }
+        }
+    }
+
+    /// The ingredient for every grid slot when the recipe is laid out from the top left
+    /// corner, as the recipe book places it
+    pub fn layout(&self) -> [Option<&Ingredient>; GRID_SLOTS] {
+        let mut layout = [None; GRID_SLOTS];
+        match self {
+            Recipe::Shaped { pattern, key, .. } => {
+                for (y, row) in pattern.iter().enumerate() {
+                    for (x, c) in row.chars().enumerate() {
+                        layout[y * GRID_SIZE + x] = key.get(&c);
+                    }
+                }
+            }
+            Recipe::Shapeless { ingredients, .. } => {
+                for (slot, ingredient) in layout.iter_mut().zip(ingredients) {
+                    *slot = Some(ingredient);
+                }
+            }
+        }
+        layout
+    }
+
+    /// Picks one of `items` for each ingredient, using each item at most once. Ingredients
+    /// are matched with augmenting paths, so an earlier pick is moved to another item when
+    /// a later ingredient needs it.
+    fn assign(ingredients: &[Ingredient], items: &[Item]) -> Option<Vec<Item>> {
+        // Tries to give ingredient `i` an item, moving the current owner of a taken item on
+        fn augment(i: usize, ingredients: &[Ingredient], items: &[Item], owner: &mut [Option<usize>], seen: &mut [bool]) -> bool {
+            for j in 0..items.len() {
+                if seen[j] || !ingredients[i].matches(items[j]) {
+                    continue;
+                }
+                seen[j] = true;
+                if owner[j].is_none_or(|other| augment(other, ingredients, items, owner, seen)) {
+                    owner[j] = Some(i);
+                    return true;
+                }
+            }
+            false
+        }
+
+        let mut owner = vec![None; items.len()];
+        for i in 0..ingredients.len() {
+            if !augment(i, ingredients, items, &mut owner, &mut vec![false; items.len()]) {
+                return None;
+            }
+        }
+        let mut chosen = vec![None; ingredients.len()];
+        for (item, owner) in items.iter().zip(owner) {
+            if let Some(i) = owner {
+                chosen[i] = Some(*item);
+            }
+        }
+        chosen.into_iter().collect()
+    }
+
+    /// The items to take out of `available` to lay this recipe out once, one per
+    /// ingredient in `layout` order, or `None` if there aren't enough
+    pub fn pick_ingredients(&self, available: &[ItemStack]) -> Option<Vec<Item>> {
+        let ingredients: Vec<Ingredient> = self.layout().into_iter().flatten().cloned().collect();
+        let items: Vec<Item> = available
+            .iter()
+            .flat_map(|stack| std::iter::repeat_n(stack.item, stack.count as usize))
+            .collect();
+        Self::assign(&ingredients, &items)
+    }
+}
+
+/// All crafting recipes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecipeBook {
+    pub recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    pub fn from_json(data: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let book: RecipeBook = serde_json::from_str(data)?;
+        for recipe in &book.recipes {
+            recipe.validate()?;
+        }
+        Ok(book)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// The first recipe matching what is in the crafting grid
+    pub fn find(&self, grid: &[Option<ItemStack>; GRID_SLOTS]) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.matches(grid))
+    }
+
+    /// Recipes that could be crafted once from the items in `available`
+    pub fn craftable(&self, available: &[ItemStack]) -> Vec<&Recipe> {
+        self.recipes
+            .iter()
+            .filter(|recipe| recipe.pick_ingredients(available).is_some())
+            .collect()
+    }
+}
+
+/// Uses up one item from every occupied grid slot after crafting
+pub fn consume_ingredients(grid: &mut [Option<ItemStack>; GRID_SLOTS]) {
+    for slot in grid.iter_mut() {
+        if let Some(stack) = slot {
+            stack.count -= 1;
+            if stack.count == 0 {
+                *slot = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{item::Material, voxel::VoxelType};
+
+    const RECIPES: &str = r#"{
+        "recipes": [
+            {
+                "type": "shaped",
+                "pattern": ["P", "P"],
+                "key": { "P": ["WalnutPlanks", "SprucePlanks"] },
+                "result": { "item": "Stick", "count": 4 }
+            },
+            {
+                "type": "shaped",
+                "pattern": ["SS", "S "],
+                "key": { "S": "Stone" },
+                "result": { "item": "Stonebrick", "count": 3 }
+            },
+            {
+                "type": "shapeless",
+                "ingredients": ["WalnutWood"],
+                "result": { "item": "WalnutPlanks", "count": 4 }
+            },
+            {
+                "type": "shapeless",
+                "ingredients": [["Sand", "Gravel"], "Sand"],
+                "result": { "item": "Sandstone", "count": 1 }
+            }
+        ]
+    }"#;
+
+    fn block(block: VoxelType) -> Option<ItemStack> {
+        Some(ItemStack::new(Item::Block(block), 1))
+    }
+
+    fn grid(slots: &[(usize, Option<ItemStack>)]) -> [Option<ItemStack>; GRID_SLOTS] {
+        let mut grid = [None; GRID_SLOTS];
+        for &(i, stack) in slots {
+            grid[i] = stack;
+        }
+        grid
+    }
+
+    fn crafted(book: &RecipeBook, grid: &[Option<ItemStack>; GRID_SLOTS]) -> Option<ItemStack> {
+        book.find(grid).map(Recipe::result)
+    }
+
+    #[test]
+    fn shaped_recipes_match_anywhere_in_the_grid() {
+        let book = RecipeBook::from_json(RECIPES).unwrap();
+        let sticks = Some(ItemStack::new(Item::Material(Material::Stick), 4));
+        let planks = block(VoxelType::WalnutPlanks);
+        assert_eq!(crafted(&book, &grid(&[(0, planks), (3, planks)])), sticks);
+        assert_eq!(crafted(&book, &grid(&[(5, planks), (8, planks)])), sticks);
+        // Ingredient lists accept any of their items, even mixed
+        assert_eq!(crafted(&book, &grid(&[(4, planks), (7, block(VoxelType::SprucePlanks))])), sticks);
+    }
+
+    #[test]
+    fn shaped_recipes_need_the_exact_shape() {
+        let book = RecipeBook::from_json(RECIPES).unwrap();
+        let planks = block(VoxelType::WalnutPlanks);
+        assert_eq!(crafted(&book, &grid(&[(0, planks), (1, planks)])), None);
+        assert_eq!(crafted(&book, &grid(&[(0, planks), (6, planks)])), None);
+        assert_eq!(crafted(&book, &grid(&[(0, planks), (3, planks), (6, planks)])), None);
+        assert_eq!(crafted(&book, &grid(&[(0, planks), (3, block(VoxelType::Stone))])), None);
+    }
+
+    #[test]
+    fn shaped_recipes_match_mirrored() {
+        let book = RecipeBook::from_json(RECIPES).unwrap();
+        let stone = block(VoxelType::Stone);
+        let bricks = Some(ItemStack::new(Item::Block(VoxelType::Stonebrick), 3));
+        assert_eq!(crafted(&book, &grid(&[(0, stone), (1, stone), (3, stone)])), bricks);
+        assert_eq!(crafted(&book, &grid(&[(0, stone), (1, stone), (4, stone)])), bricks);
+        assert_eq!(crafted(&book, &grid(&[(0, stone), (3, stone), (4, stone)])), None);
+    }
+
+    #[test]
+    fn shapeless_recipes_match_in_any_slots() {
+        let book = RecipeBook::from_json(RECIPES).unwrap();
+        let planks = Some(ItemStack::new(Item::Block(VoxelType::WalnutPlanks), 4));
+        assert_eq!(crafted(&book, &grid(&[(0, block(VoxelType::WalnutWood))])), planks);
+        assert_eq!(crafted(&book, &grid(&[(8, block(VoxelType::WalnutWood))])), planks);
+        assert_eq!(crafted(&book, &grid(&[(0, block(VoxelType::WalnutWood)), (1, block(VoxelType::Dirt))])), None);
+
+        // The specific ingredient must not be starved by the list that also accepts sand
+        let sandstone = Some(ItemStack::new(Item::Block(VoxelType::Sandstone), 1));
+        assert_eq!(crafted(&book, &grid(&[(2, block(VoxelType::Sand)), (7, block(VoxelType::Gravel))])), sandstone);
+        assert_eq!(crafted(&book, &grid(&[(2, block(VoxelType::Sand)), (7, block(VoxelType::Sand))])), sandstone);
+        assert_eq!(crafted(&book, &grid(&[(2, block(VoxelType::Gravel)), (7, block(VoxelType::Gravel))])), None);
+
+        // Sand has to move to the second ingredient once gravel can only go to the first
+        let book = RecipeBook::from_json(
+            r#"{ "recipes": [{
+                "type": "shapeless",
+                "ingredients": [["Sand", "Gravel"], ["Sand", "Dirt"]],
+                "result": { "item": "Sandstone", "count": 1 }
+            }] }"#,
+        )
+        .unwrap();
+        assert_eq!(crafted(&book, &grid(&[(0, block(VoxelType::Sand)), (1, block(VoxelType::Gravel))])), sandstone);
+        let available = [ItemStack::new(Item::Block(VoxelType::Sand), 1), ItemStack::new(Item::Block(VoxelType::Gravel), 1)];
+        assert_eq!(
+            book.recipes[0].pick_ingredients(&available),
+            Some(vec![Item::Block(VoxelType::Gravel), Item::Block(VoxelType::Sand)])
+        );
+    }
+
+    #[test]
+    fn empty_grid_crafts_nothing() {
+        let book = RecipeBook::from_json(RECIPES).unwrap();
+        assert_eq!(crafted(&book, &[None; GRID_SLOTS]), None);
+    }
+
+    #[test]
+    fn consuming_takes_one_from_each_slot() {
+        let mut crafting = grid(&[
+            (0, Some(ItemStack::new(Item::Block(VoxelType::WalnutPlanks), 3))),
+            (3, block(VoxelType::WalnutPlanks)),
+        ]);
+        consume_ingredients(&mut crafting);
+        assert_eq!(crafting[0], Some(ItemStack::new(Item::Block(VoxelType::WalnutPlanks), 2)));
+        assert_eq!(crafting[3], None);
+    }
+
+    #[test]
+    fn craftable_checks_ingredient_counts() {
+        let book = RecipeBook::from_json(RECIPES).unwrap();
+        let results = |available: &[ItemStack]| -> Vec<Item> {
+            book.craftable(available).iter().map(|recipe| recipe.result().item).collect()
+        };
+        let one_plank = [ItemStack::new(Item::Block(VoxelType::WalnutPlanks), 1)];
+        assert!(results(&one_plank).is_empty());
+        let mixed_planks = [
+            ItemStack::new(Item::Block(VoxelType::WalnutPlanks), 1),
+            ItemStack::new(Item::Block(VoxelType::SprucePlanks), 1),
+        ];
+        assert_eq!(results(&mixed_planks), vec![Item::Material(Material::Stick)]);
+        let stone = [ItemStack::new(Item::Block(VoxelType::Stone), 3)];
+        assert_eq!(results(&stone), vec![Item::Block(VoxelType::Stonebrick)]);
+    }
+
+    #[test]
+    fn invalid_recipes_are_rejected() {
+        let unknown_key = r#"{ "recipes": [{ "type": "shaped", "pattern": ["AB"], "key": { "A": "Stone" },
+            "result": { "item": "Stonebrick", "count": 1 } }] }"#;
+        assert!(RecipeBook::from_json(unknown_key).is_err());
+        let too_big = r#"{ "recipes": [{ "type": "shaped", "pattern": ["AAAA"], "key": { "A": "Stone" },
+            "result": { "item": "Stonebrick", "count": 1 } }] }"#;
+        assert!(RecipeBook::from_json(too_big).is_err());
+        let padded = r#"{ "recipes": [{ "type": "shaped", "pattern": ["A ", "A "], "key": { "A": "Stone" },
+            "result": { "item": "Stonebrick", "count": 1 } }] }"#;
+        assert!(RecipeBook::from_json(padded).is_err());
+        let unknown_item = r#"{ "recipes": [{ "type": "shapeless", "ingredients": ["Unobtainium"],
+            "result": { "item": "Stonebrick", "count": 1 } }] }"#;
+        assert!(RecipeBook::from_json(unknown_item).is_err());
+    }
+
+    #[test]
+    fn bundled_recipes_load() {
+        let book = RecipeBook::load(Path::new("assets/recipes/crafting.json")).unwrap();
+        let planks = grid(&[(4, block(VoxelType::BirchWood))]);
+        assert_eq!(crafted(&book, &planks), Some(ItemStack::new(Item::Block(VoxelType::BirchPlanks), 4)));
+    }
+}
@@ -1,178 +1,209 @@
-use ferrousgl::{GlWindow, Shader, Texture, WindowKey};
-use glam::{Mat4, Vec2};
-use crate::{ui::ui_quad::UiQuad, voxel::VoxelType};
+use serde::{Deserialize, Serialize};
 
+use crate::item::Item;
+
+pub const HOTBAR_SLOTS: usize = 9;
+pub const MAIN_SLOTS: usize = 27;
+
+/// A number of identical items occupying one inventory slot
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub fn new(item: Item, count: u32) -> Self {
+        Self { item, count }
+    }
+
+    pub fn max_size(&self) -> u32 {
+        self.item.max_stack_size()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.count >= self.max_size()
+    }
+
+    /// Whether `other` could be merged into this stack, ignoring how much room is left
+    pub fn can_stack_with(&self, other: &ItemStack) -> bool {
+        self.item == other.item
+    }
+}
+
+/// The player's items: a hotbar whose selected slot is used for placing, and a main grid.
+/// Empty slots are `None`, a stack never has a count of zero.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Inventory {
-    creative_slots: Vec<UiQuad>,  // Holds all voxel types
-    slot_selector: UiQuad,
-    selected_index: usize,
-    ui_scale: f32,
-    rows_visible: usize,         // How many rows are visible at once
-    current_row: usize,          // Current scroll position
+    pub hotbar: [Option<ItemStack>; HOTBAR_SLOTS],
+    pub main: [Option<ItemStack>; MAIN_SLOTS],
+    pub selected: usize,
 }
 
 impl Inventory {
     pub fn new() -> Self {
-        let ui_scale = 1.5;
-
-        let mut slot_selector = UiQuad::new();
-        slot_selector.set_size(Vec2::new(30.0 * ui_scale, 30.0 * ui_scale));
-        slot_selector.set_position(Vec2::new(15.0 * ui_scale, 15.0 * ui_scale));
-        slot_selector.set_uvs([
-            Vec2::new((1.0/28.4)*2.0, 1.0/28.4),
-            Vec2::new((1.0/28.4)*4.0, 1.0/28.4),
-            Vec2::new((1.0/28.4)*4.0, (1.0/28.4)*3.0),
-            Vec2::new((1.0/28.4)*2.0, (1.0/28.4)*3.0),
-        ]);
-
-        // Initialize creative slots with all voxel types (excluding Air)
-        let mut creative_slots = Vec::new();
-        let voxel_types = Self::get_all_voxel_types();
-        
-        for (i, voxel_type) in voxel_types.iter().enumerate() {
-            let mut slot = UiQuad::new();
-            let x = 15.0 * ui_scale + (i % 10) as f32 * 30.0 * ui_scale;
-            let y = 15.0 * ui_scale + (i / 10) as f32 * 30.0 * ui_scale;
-            
-            slot.set_size(Vec2::new(30.0 * ui_scale, 30.0 * ui_scale));
-            slot.set_position(Vec2::new(x, y));
-            slot.set_uvs([
-                Vec2::new(0.0, 1.0/28.4),
-                Vec2::new((1.0/28.4)*2.0, 1.0/28.4),
-                Vec2::new((1.0/28.4)*2.0, (1.0/28.4)*3.0),
-                Vec2::new(0.0, (1.0/28.4)*3.0),
-            ]);
-            
-            creative_slots.push(slot);
+        Self {
+            hotbar: [None; HOTBAR_SLOTS],
+            main: [None; MAIN_SLOTS],
+            selected: 0,
         }
+    }
 
-        Self {
-            creative_slots,
-            selected_index: 0,
-            slot_selector,
-            ui_scale,
-            rows_visible: 1,  // Show one row at a time
-            current_row: 0,
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(HOTBAR_SLOTS - 1);
+    }
+
+    /// Moves the hotbar selection by `offset` slots, wrapping around at either end
+    pub fn scroll_selection(&mut self, offset: i32) {
+        self.selected = (self.selected as i32 + offset).rem_euclid(HOTBAR_SLOTS as i32) as usize;
+    }
+
+    pub fn selected_stack(&self) -> Option<&ItemStack> {
+        self.hotbar[self.selected].as_ref()
+    }
+
+    /// Removes one item from the selected hotbar slot and returns it
+    pub fn take_selected(&mut self) -> Option<Item> {
+        let slot = &mut self.hotbar[self.selected];
+        let stack = slot.as_mut()?;
+        let item = stack.item;
+        stack.count -= 1;
+        if stack.count == 0 {
+            *slot = None;
         }
+        Some(item)
+    }
+
+    /// Every stack in the inventory, hotbar first
+    pub fn stacks(&self) -> impl Iterator<Item = &ItemStack> {
+        self.hotbar.iter().chain(self.main.iter()).flatten()
     }
 
-    pub fn update(&mut self, window: &GlWindow) -> Option<VoxelType> {
-        let voxel_types = Self::get_all_voxel_types();
-        
-        // Handle mouse scroll to change selection
-        let scroll = window.get_mouse_wheel_delta().1;
-        if scroll != 0.0 {
-            if scroll > 0.0 {
-                // Scroll up
-                if self.selected_index > 0 {
-                    self.selected_index -= 1;
-                } else {
-                    // Wrap around to end
-                    self.selected_index = voxel_types.len() - 1;
+    /// Takes up to `count` of `item` out of the inventory, from the main grid before the
+    /// hotbar. Returns how many were taken.
+    pub fn remove(&mut self, item: Item, count: u32) -> u32 {
+        let mut taken = 0;
+        for slot in self.main.iter_mut().chain(self.hotbar.iter_mut()) {
+            if let Some(stack) = slot.as_mut().filter(|stack| stack.item == item) {
+                let moved = stack.count.min(count - taken);
+                stack.count -= moved;
+                taken += moved;
+                if stack.count == 0 {
+                    *slot = None;
                 }
-            } else {
-                // Scroll down
-                if self.selected_index < voxel_types.len() - 1 {
-                    self.selected_index += 1;
-                } else {
-                    // Wrap around to start
-                    self.selected_index = 0;
+                if taken == count {
+                    break;
                 }
             }
+        }
+        taken
+    }
 
-            // Adjust the current row based on the selected index
-            let new_row = self.selected_index / 10;
-            if new_row != self.current_row {
-                self.current_row = new_row;
-            }
+    /// Adds `stack` to the inventory, topping up existing stacks before using empty slots
+    /// and the hotbar before the main grid. Returns whatever did not fit.
+    pub fn add(&mut self, stack: ItemStack) -> Option<ItemStack> {
+        let stack = merge_into(&mut self.hotbar, stack)?;
+        let stack = merge_into(&mut self.main, stack)?;
+        let stack = fill_into(&mut self.hotbar, stack)?;
+        fill_into(&mut self.main, stack)
+    }
+}
 
-            self.update_slot_selector_position();
-            return Some(voxel_types[self.selected_index].clone());
-        }
-        
-        // Handle number key selection for first row (1-0 keys)
-        for i in 0..10 {
-            let key = match i {
-                0 => WindowKey::Num0,
-                1 => WindowKey::Num1,
-                2 => WindowKey::Num2,
-                3 => WindowKey::Num3,
-                4 => WindowKey::Num4,
-                5 => WindowKey::Num5,
-                6 => WindowKey::Num6,
-                7 => WindowKey::Num7,
-                8 => WindowKey::Num8,
-                9 => WindowKey::Num9,
-                _ => continue,
-            };
-            
-            if window.is_key_held(key) && i < voxel_types.len() {
-                self.selected_index = i;
-                self.update_slot_selector_position();
-                return Some(voxel_types[i].clone());
+/// Tops up the stacks in `slots` that `stack` can join. Returns whatever is left over.
+pub fn merge_into(slots: &mut [Option<ItemStack>], mut stack: ItemStack) -> Option<ItemStack> {
+    for slot in slots.iter_mut().flatten() {
+        if slot.can_stack_with(&stack) && !slot.is_full() {
+            let moved = stack.count.min(slot.max_size() - slot.count);
+            slot.count += moved;
+            stack.count -= moved;
+            if stack.count == 0 {
+                return None;
             }
         }
-        
-        None
-    }
-
-    fn update_slot_selector_position(&mut self) {
-        let x = 15.0 * self.ui_scale + (self.selected_index % 10) as f32 * 30.0 * self.ui_scale;
-        let y = 15.0 * self.ui_scale + (self.selected_index / 10) as f32 * 30.0 * self.ui_scale;
-        self.slot_selector.set_position(Vec2::new(x, y));
-    }
-
-    // Helper function to get all voxel types except Air
-    fn get_all_voxel_types() -> Vec<VoxelType> {
-        VoxelType::get_all_voxel_types()
-    }
-
-    pub fn render(&self, window: &mut GlWindow, shader: &Shader, model: Mat4, projection: Mat4, ui_atlas: &Texture, block_atlas: &Texture) {
-        let voxel_types = Self::get_all_voxel_types();
-        
-        // Determine which slots to render based on current_row
-        let start_index = self.current_row * 10;
-        let end_index = (self.current_row + self.rows_visible) * 10;
-        
-        for i in start_index..end_index.min(self.creative_slots.len()) {
-            let slot = &self.creative_slots[i];
-            
-            // Render slot background
-            slot.render_with_texture(window, shader, model, projection, Some(ui_atlas));
-            
-            // Render block texture if available
-            if i < voxel_types.len() {
-                let voxel_type = &voxel_types[i];
-                let face_texture = voxel_type.get_face_texture(0); // Use front face for inventory
-                
-                if face_texture[0] != -1.0 {
-                    let mut block_quad = UiQuad::new();
-                    block_quad.set_size(Vec2::new(25.0 * self.ui_scale, 25.0 * self.ui_scale)); // Slightly smaller than slot
-                    block_quad.set_position(slot.position);
-                    
-                    // Set UVs for the block texture
-                    block_quad.set_uvs([
-                        Vec2::new(face_texture[0], face_texture[1]),
-                        Vec2::new(face_texture[0] + face_texture[2], face_texture[1]),
-                        Vec2::new(face_texture[0] + face_texture[2], face_texture[1] + face_texture[3]),
-                        Vec2::new(face_texture[0], face_texture[1] + face_texture[3]),
-                    ]);
-                    
-                    block_quad.render_with_texture(window, shader, model, projection, Some(block_atlas));
-                }
-            }
+    }
+    Some(stack)
+}
+
+/// Puts `stack` into the empty slots of `slots`. Returns whatever is left over.
+pub fn fill_into(slots: &mut [Option<ItemStack>], mut stack: ItemStack) -> Option<ItemStack> {
+    for slot in slots.iter_mut().filter(|slot| slot.is_none()) {
+        let moved = stack.count.min(stack.max_size());
+        *slot = Some(ItemStack::new(stack.item, moved));
+        stack.count -= moved;
+        if stack.count == 0 {
+            return None;
         }
-        
-        // Render slot selector
-        self.slot_selector.render_with_texture(window, shader, model, projection, Some(ui_atlas));
     }
+    Some(stack)
+}
 
-    pub fn get_selected_voxel(&self) -> Option<VoxelType> {
-        let voxel_types = Self::get_all_voxel_types();
-        if self.selected_index < voxel_types.len() {
-            Some(voxel_types[self.selected_index].clone())
-        } else {
-            None
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{item::Tool, voxel::VoxelType};
+
+    fn dirt(count: u32) -> ItemStack {
+        ItemStack::new(Item::Block(VoxelType::Dirt), count)
+    }
+
+    #[test]
+    fn adding_tops_up_partial_stacks_first() {
+        let mut inventory = Inventory::new();
+        inventory.main[3] = Some(dirt(60));
+        assert_eq!(inventory.add(dirt(10)), None);
+        assert_eq!(inventory.main[3], Some(dirt(64)));
+        // Only the six that didn't fit start a new stack
+        assert_eq!(inventory.hotbar[0], Some(dirt(6)));
+    }
+
+    #[test]
+    fn the_hotbar_fills_before_the_main_grid() {
+        let mut inventory = Inventory::new();
+        for i in 0..HOTBAR_SLOTS {
+            assert_eq!(inventory.add(ItemStack::new(Item::Tool(Tool::WoodenAxe), 1)), None);
+            assert!(inventory.hotbar[i].is_some());
         }
+        assert!(inventory.main.iter().all(Option::is_none));
+        assert_eq!(inventory.add(dirt(100)), None);
+        assert_eq!(inventory.main[0], Some(dirt(64)));
+        assert_eq!(inventory.main[1], Some(dirt(36)));
+    }
+
+    #[test]
+    fn a_full_inventory_returns_the_remainder() {
+        let mut inventory = Inventory::new();
+        inventory.hotbar = [Some(dirt(64)); HOTBAR_SLOTS];
+        inventory.main = [Some(dirt(64)); MAIN_SLOTS];
+        inventory.main[5] = Some(dirt(50));
+        assert_eq!(inventory.add(dirt(20)), Some(dirt(6)));
+        assert_eq!(inventory.main[5], Some(dirt(64)));
+        let sand = ItemStack::new(Item::Block(VoxelType::Sand), 3);
+        assert_eq!(inventory.add(sand), Some(sand));
+    }
+
+    #[test]
+    fn removing_takes_from_several_stacks() {
+        let mut inventory = Inventory::new();
+        inventory.hotbar[0] = Some(dirt(10));
+        inventory.main[0] = Some(dirt(5));
+        inventory.main[1] = Some(dirt(3));
+        // The main grid empties first, then the hotbar
+        assert_eq!(inventory.remove(Item::Block(VoxelType::Dirt), 12), 12);
+        assert_eq!(inventory.main[0], None);
+        assert_eq!(inventory.main[1], None);
+        assert_eq!(inventory.hotbar[0], Some(dirt(6)));
+        // Asking for more than there is takes what's left
+        assert_eq!(inventory.remove(Item::Block(VoxelType::Dirt), 10), 6);
+        assert!(inventory.stacks().next().is_none());
+    }
+
+    #[test]
+    fn unstackable_items_take_a_slot_each() {
+        let mut slots = [None; 3];
+        let axes = ItemStack::new(Item::Tool(Tool::StoneAxe), 2);
+        assert_eq!(merge_into(&mut slots, axes), Some(axes));
+        assert_eq!(fill_into(&mut slots, axes), None);
+        let axe = Some(ItemStack::new(Item::Tool(Tool::StoneAxe), 1));
+        assert_eq!(slots, [axe, axe, None]);
     }
 }
@@ -0,0 +1,242 @@
+// item.rs
+use std::{collections::HashMap, sync::LazyLock};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, EnumString};
+
+use crate::voxel::VoxelType;
+
+/// Items that exist only in inventories and can't be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, Serialize, Deserialize)]
+pub enum Material {
+    Stick,
+    CopperIngot,
+    AmethystShard,
+}
+
+/// The kind of block a tool is made for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ToolClass {
+    Pickaxe,
+    Axe,
+    Shovel,
+}
+
+/// What a tool is made of, better tiers break blocks faster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ToolTier {
+    Wood,
+    Stone,
+    Copper,
+}
+
+impl ToolTier {
+    /// How many times faster than by hand the tool breaks blocks of its class
+    pub fn speed(&self) -> f32 {
+        match self {
+            ToolTier::Wood => 2.0,
+            ToolTier::Stone => 4.0,
+            ToolTier::Copper => 6.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, Serialize, Deserialize)]
+pub enum Tool {
+    WoodenPickaxe,
+    StonePickaxe,
+    CopperPickaxe,
+    WoodenAxe,
+    StoneAxe,
+    CopperAxe,
+    WoodenShovel,
+    StoneShovel,
+    CopperShovel,
+}
+
+impl Tool {
+    pub fn class(&self) -> ToolClass {
+        match self {
+            Tool::WoodenPickaxe | Tool::StonePickaxe | Tool::CopperPickaxe => ToolClass::Pickaxe,
+            Tool::WoodenAxe | Tool::StoneAxe | Tool::CopperAxe => ToolClass::Axe,
+            Tool::WoodenShovel | Tool::StoneShovel | Tool::CopperShovel => ToolClass::Shovel,
+        }
+    }
+
+    pub fn tier(&self) -> ToolTier {
+        match self {
+            Tool::WoodenPickaxe | Tool::WoodenAxe | Tool::WoodenShovel => ToolTier::Wood,
+            Tool::StonePickaxe | Tool::StoneAxe | Tool::StoneShovel => ToolTier::Stone,
+            Tool::CopperPickaxe | Tool::CopperAxe | Tool::CopperShovel => ToolTier::Copper,
+        }
+    }
+
+    fn definition(&self) -> ItemDefinition {
+        let name = match self {
+            Tool::WoodenPickaxe => "Wooden Pickaxe",
+            Tool::StonePickaxe => "Stone Pickaxe",
+            Tool::CopperPickaxe => "Copper Pickaxe",
+            Tool::WoodenAxe => "Wooden Axe",
+            Tool::StoneAxe => "Stone Axe",
+            Tool::CopperAxe => "Copper Axe",
+            Tool::WoodenShovel => "Wooden Shovel",
+            Tool::StoneShovel => "Stone Shovel",
+            Tool::CopperShovel => "Copper Shovel",
+        };
+        // One row of the item atlas per class, one column per tier
+        ItemDefinition {
+            name,
+            icon: ItemIcon::Atlas(self.tier() as u32, 1 + self.class() as u32),
+            max_stack_size: 1,
+            places: None,
+            tool: Some(*self),
+        }
+    }
+}
+
+/// Anything that can sit in an inventory slot. Serialised as the bare block, material or
+/// tool name, e.g. `"WalnutPlanks"`, `"CopperIngot"` or `"StonePickaxe"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Item {
+    Block(VoxelType),
+    Material(Material),
+    Tool(Tool),
+}
+
+/// Where an item's inventory icon comes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemIcon {
+    /// The top face of a block in the block atlas
+    Block(VoxelType),
+    /// A (column, row) tile in the item atlas
+    Atlas(u32, u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct ItemDefinition {
+    pub name: &'static str,
+    pub icon: ItemIcon,
+    pub max_stack_size: u32,
+    /// The block placed when using the item on a block face
+    pub places: Option<VoxelType>,
+    /// Set for items that speed up breaking blocks
+    pub tool: Option<Tool>,
+}
+
+impl Material {
+    fn definition(&self) -> ItemDefinition {
+        let (name, icon) = match self {
+            Material::Stick => ("Stick", (0, 0)),
+            Material::CopperIngot => ("Copper Ingot", (1, 0)),
+            Material::AmethystShard => ("Amethyst Shard", (2, 0)),
+        };
+        ItemDefinition {
+            name,
+            icon: ItemIcon::Atlas(icon.0, icon.1),
+            max_stack_size: 64,
+            places: None,
+            tool: None,
+        }
+    }
+}
+
+/// Every item and its definition, blocks first in `VoxelType` order, then materials and tools
+pub struct ItemRegistry {
+    items: Vec<Item>,
+    definitions: HashMap<Item, ItemDefinition>,
+    /// Stands in for items that aren't registered, like Air
+    unknown: ItemDefinition,
+}
+
+static REGISTRY: LazyLock<ItemRegistry> = LazyLock::new(ItemRegistry::new);
+
+impl ItemRegistry {
+    fn new() -> Self {
+        let blocks = VoxelType::get_all_voxel_types().into_iter().map(|block| {
+            let definition = ItemDefinition {
+                name: block.item_name(),
+                icon: ItemIcon::Block(block),
+                max_stack_size: 64,
+                places: Some(block),
+                tool: None,
+            };
+            (Item::Block(block), definition)
+        });
+        let materials = Material::iter().map(|material| (Item::Material(material), material.definition()));
+        let tools = Tool::iter().map(|tool| (Item::Tool(tool), tool.definition()));
+
+        let mut items = Vec::new();
+        let mut definitions = HashMap::new();
+        for (item, definition) in blocks.chain(materials).chain(tools) {
+            items.push(item);
+            definitions.insert(item, definition);
+        }
+        let unknown = ItemDefinition {
+            name: "Unknown",
+            icon: ItemIcon::Block(VoxelType::Air),
+            max_stack_size: 64,
+            places: None,
+            tool: None,
+        };
+        Self { items, definitions, unknown }
+    }
+
+    pub fn get() -> &'static ItemRegistry {
+        &REGISTRY
+    }
+
+    /// All items in display order
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// The definition of `item`, or one that places nothing for unregistered items
+    pub fn definition(&self, item: Item) -> &ItemDefinition {
+        self.definitions.get(&item).unwrap_or(&self.unknown)
+    }
+}
+
+impl Item {
+    pub fn definition(&self) -> &'static ItemDefinition {
+        ItemRegistry::get().definition(*self)
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.definition().name
+    }
+
+    pub fn max_stack_size(&self) -> u32 {
+        self.definition().max_stack_size
+    }
+
+    pub fn places(&self) -> Option<VoxelType> {
+        self.definition().places
+    }
+
+    pub fn tool(&self) -> Option<Tool> {
+        self.definition().tool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unregistered_items_fall_back_to_a_definition() {
+        let air = Item::Block(VoxelType::Air);
+        assert!(!ItemRegistry::get().items().contains(&air));
+        assert_eq!(air.name(), "Unknown");
+        assert_eq!(air.places(), None);
+        assert_eq!(air.tool(), None);
+    }
+
+    #[test]
+    fn every_listed_item_has_its_own_definition() {
+        for &item in ItemRegistry::get().items() {
+            assert_ne!(item.name(), "Unknown", "{:?}", item);
+        }
+    }
+}
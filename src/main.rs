@@ -11,15 +11,30 @@ mod chunk;
 mod player;
 mod utils;
 mod ui;
+mod item;
+mod block_entity;
+mod block_model;
+mod crafting;
+mod mining;
 mod inventory;
 mod world;
+mod worldgen;
 
 use voxel::VoxelType;
 use chunk::Chunk;
-use player::Player;
-use world::World;
+use player::{GameMode, Player};
+use world::{SpawnSearch, World};
+use crate::crafting::RecipeBook;
 use crate::inventory::Inventory;
-use crate::ui::ui_quad::UiQuad;
+use crate::ui::{UiFrame, creative_screen::CreativeScreen, hotbar::Hotbar, inventory_screen::InventoryScreen, screen::Screen, item_renderer::ItemRenderer, ui_quad::UiQuad};
+use crate::worldgen::presets::WorldPreset;
+
+/// How far around the estimated spawn to look for a safe block in the loaded terrain
+const SPAWN_SEARCH_RADIUS: i32 = 16;
+
+/// Chunks further than this from the player are unloaded. Kept above the 5 chunks
+/// generated around the player so chunks at the edge don't load and unload repeatedly.
+const UNLOAD_DISTANCE: i32 = 8;
 
 fn main() {
     // Show a message box with panic info if an error occurs
@@ -80,19 +95,45 @@ fn main() {
     atlas_normal.set_mipmap_and_filtering(ferrousgl::MipmapType::Nearest, ferrousgl::FilterMode::Nearest);
     atlas_normal.unbind();
 
+    let item_atlas = Texture::new_from_file(Path::new("assets/textures/items.png"))
+        .expect("Failed to load item atlas");
+    item_atlas.bind(0);
+    item_atlas.set_mipmap_and_filtering(ferrousgl::MipmapType::Nearest, ferrousgl::FilterMode::Nearest);
+    item_atlas.unbind();
+
     let ui_tex = Texture::new_from_file(Path::new("assets/textures/ui.png"))
         .expect("Failed to load texture ui atlas");
     ui_tex.bind(0);
     ui_tex.set_mipmap_and_filtering(ferrousgl::MipmapType::Nearest, ferrousgl::FilterMode::Nearest);
     ui_tex.unbind();
 
-    // Create player
-    let mut player = Player::new(Vec3::new(0.0, 300.0, 0.0));
-
     // Create world
+    // World preset from the command line: a generator name such as `flat:1×Stone,3×Dirt,1×Grass`
+    // or a preset file like `assets/presets/default.json`
+    let preset_arg = std::env::args().nth(1).unwrap_or_else(|| "default".to_string());
+    let preset = if preset_arg.ends_with(".json") {
+        WorldPreset::load(Path::new(&preset_arg)).expect("Failed to load world preset")
+    } else {
+        WorldPreset::named(&preset_arg)
+    };
     let mut rng = rand::rng();
-    let mut world = World::new(64, rng.random_range(0..100000));
-    println!("Seed: {}", world.seed);
+    let seed = preset.seed.unwrap_or_else(|| rng.random_range(0..100000));
+    let mut world = World::new(64, seed, preset).expect("Failed to create world");
+    println!("Seed: {} ({})", world.seed, world.preset.generator);
+    if let Err(e) = world.preset.save(Path::new("world.json")) {
+        eprintln!("Failed to save world preset: {}", e);
+    }
+
+    // Create player on the saved spawn, or on a rough guess until the terrain there has loaded
+    let spawn_position = |(x, y, z): (i32, i32, i32)| Vec3::new(x as f32 + 0.5, y as f32 + 1.0, z as f32 + 0.5);
+    let (spawn, mut spawn_search) = match world.preset.spawn {
+        Some([x, y, z]) => ((x, y, z), None),
+        None => match world.estimate_spawn() {
+            Some(estimate) => (estimate, Some(estimate)),
+            None => ((0, 0, 0), None),
+        },
+    };
+    let mut player = Player::new(spawn_position(spawn));
 
     // Generate initial world
     //world.generate_chunks_around_center(0, 0, 0, 6);
@@ -121,7 +162,14 @@ fn main() {
         Vec2::new(0.0, 1.0/28.4),
     ]);
     
+    let recipes = RecipeBook::load(Path::new("assets/recipes/crafting.json"))
+        .expect("Failed to load crafting recipes");
     let mut inventory = Inventory::new();
+    let hotbar = Hotbar::new();
+    let item_renderer = ItemRenderer::new();
+    let mut screen: Option<Screen> = None;
+    // The survival inventory screen between opens, with whatever didn't fit back into the inventory
+    let mut inventory_screen: Option<InventoryScreen> = None;
 
     // Main game loop
     while !window.should_window_close() {
@@ -135,9 +183,57 @@ fn main() {
         window.clear_depth(); // Clear depth buffer
         window.set_depth_testing(DepthType::LessOrEqual);
 
+        // Hold the player at the estimate until a safe spawn is found in the loaded terrain
+        if let Some((sx, sy, sz)) = spawn_search {
+            let cs = world.chunk_size as i32;
+            world.generate_nearest_missing_chunk_simple(sx.div_euclid(cs), sy.div_euclid(cs), sz.div_euclid(cs));
+            let found = match world.find_spawn(sx, sz, SPAWN_SEARCH_RADIUS) {
+                SpawnSearch::Found(spawn) => Some(spawn),
+                SpawnSearch::Loading => None,
+                SpawnSearch::NoneSuitable => Some((sx, world.surface_height(sx, sz).unwrap_or(sy), sz)),
+            };
+            if let Some(spawn) = found {
+                world.set_spawn(spawn);
+                if let Err(e) = world.preset.save(Path::new("world.json")) {
+                    eprintln!("Failed to save world preset: {}", e);
+                }
+                player.position = spawn_position(spawn);
+                spawn_search = None;
+            } else {
+                player.position = spawn_position((sx, sy, sz));
+            }
+            player.velocity = Vec3::ZERO;
+        }
+
         // Handle input
-        player.handle_input(&mut window, delta_time, &mut world, true, inventory.get_selected_voxel().unwrap());
-        inventory.update(&window);
+        // Open screens take over the mouse and keyboard, leaving the cursor free
+        if let Some(open) = screen.as_mut() {
+            open.update(&window, &mut inventory, &hotbar, &recipes, &world);
+            if open.wants_close(&window) {
+                let left = open.close(&mut inventory);
+                if let Some(Screen::Inventory(closed)) = screen.take() {
+                    inventory_screen = Some(closed);
+                }
+                if let Some(stack) = left {
+                    inventory_screen.get_or_insert_with(InventoryScreen::new).keep(stack);
+                }
+                let (w, h) = window.get_window_size();
+                window.set_mouse_position(w as f64 / 2.0, h as f64 / 2.0);
+            }
+        } else if window.is_key_pressed(WindowKey::E) {
+            screen = Some(match player.game_mode {
+                GameMode::Survival => Screen::Inventory(inventory_screen.take().unwrap_or_else(InventoryScreen::new)),
+                GameMode::Creative => Screen::Creative(CreativeScreen::new()),
+            });
+        }
+
+        if let Some(opened) = player.handle_input(&mut window, delta_time, &mut world, screen.is_none(), &mut inventory) {
+            screen = Screen::for_block_entity(&world, opened);
+        }
+        world.tick_block_entities(delta_time);
+        if screen.is_none() {
+            hotbar.update(&window, &mut inventory);
+        }
 
         // Set up view/projection matrices
         let projection = Mat4::perspective_rh_gl(
@@ -159,19 +255,19 @@ fn main() {
         shader.set_uniform_3f("viewPos", player.position.x, player.position.y, player.position.z);
 
         world.render(&window, &shader);
+        player.render_break_overlay(&window, &shader);
         shader.unbind_program();
 
         world.process_chunk_updates(&window);
         // Convert player position to chunk coordinates
-        
+        let chunk_x = (player.position.x / world.chunk_size as f32).floor() as i32;
+        let chunk_y = (player.position.y / world.chunk_size as f32).floor() as i32;
+        let chunk_z = (player.position.z / world.chunk_size as f32).floor() as i32;
 
         //world.update_chunks_around_player(chunk_x, chunk_y, chunk_z);
+        world.remove_distant_chunks(chunk_x, chunk_y, chunk_z, UNLOAD_DISTANCE);
 
         if window.is_key_held(WindowKey::F3) {
-            let chunk_x = (player.position.x / world.chunk_size as f32).floor() as i32;
-            let chunk_y = (player.position.y / world.chunk_size as f32).floor() as i32;
-            let chunk_z = (player.position.z / world.chunk_size as f32).floor() as i32;
-
             world.generate_nearest_missing_chunk_simple(chunk_x, chunk_y, chunk_z);
         }
 
@@ -183,7 +279,17 @@ fn main() {
         crosshair.render_with_texture(&mut window, &ui_shader, Mat4::from_translation(Vec3::new(w as f32/2.0, h as f32/2.0, 0.0))
             * model, ortho, Some(&ui_tex));
 
-        inventory.render(&mut window, &ui_shader, model, ortho, &ui_tex, &atlas);
+        let ui_frame = UiFrame {
+            shader: &ui_shader,
+            projection: ortho,
+            ui_atlas: &ui_tex,
+            block_atlas: &atlas,
+            item_atlas: &item_atlas,
+        };
+        hotbar.render(&mut window, &ui_frame, &item_renderer, &inventory);
+        if let Some(open) = screen.as_mut() {
+            open.render(&mut window, &ui_frame, &item_renderer, &mut inventory, &recipes, &world);
+        }
         
         // Update window
         window.update();
@@ -195,10 +301,15 @@ fn main() {
             frame_count = 0;
             last_fps_update = Instant::now();
             // Update window title with FPS
+            let biome = world.biome_at(
+                player.position.x.floor() as i32,
+                player.position.z.floor() as i32,
+            );
             window.set_window_title(
                 &format!(
-                    "Craftmine (fps: {})", 
-                    fps
+                    "Craftmine (fps: {}, biome: {})", 
+                    fps,
+                    biome.name()
                 )
             );
         }
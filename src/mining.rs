@@ -0,0 +1,161 @@
+// mining.rs
+// How blocks react to being broken in survival. Kept out of voxel.rs so the block list
+// doesn't depend on items.
+use crate::{
+    inventory::ItemStack,
+    item::{Item, Material, Tool, ToolClass},
+    voxel::{Shape, VoxelType},
+};
+
+/// Breaking a block without the tool it requires is this much slower, and drops nothing
+const WRONG_TOOL_PENALTY: f32 = 10.0 / 3.0;
+
+impl VoxelType {
+    /// Seconds to break the block by hand, None for blocks that can't be broken
+    pub fn hardness(&self) -> Option<f32> {
+        let seconds = match self {
+            VoxelType::Air | VoxelType::Water => return None,
+            VoxelType::TallGrass | VoxelType::Dandelion | VoxelType::Poppy => 0.0,
+            VoxelType::Snow => 0.3,
+            VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves | VoxelType::BirchLeaves => 0.3,
+            VoxelType::Glass => 0.45,
+            VoxelType::Cactus => 0.6,
+            VoxelType::Dirt | VoxelType::Sand | VoxelType::Ice => 0.75,
+            VoxelType::Gravel | VoxelType::Grass => 0.9,
+            VoxelType::Sandstone => 1.2,
+            VoxelType::Stone | VoxelType::Stonebrick => 2.25,
+            VoxelType::WalnutWood | VoxelType::SpruceWood | VoxelType::MahoganyWood | VoxelType::BirchWood => 3.0,
+            VoxelType::WalnutPlanks | VoxelType::SprucePlanks | VoxelType::MahoganyPlanks | VoxelType::BirchPlanks => 3.0,
+            VoxelType::Cobblestone => 3.0,
+            VoxelType::StoneSlab | VoxelType::StoneStairs => 3.0,
+            VoxelType::WalnutSlab | VoxelType::SpruceSlab | VoxelType::MahoganySlab | VoxelType::BirchSlab => 3.0,
+            VoxelType::WalnutStairs | VoxelType::SpruceStairs | VoxelType::MahoganyStairs | VoxelType::BirchStairs => 3.0,
+            VoxelType::WalnutFence | VoxelType::SpruceFence | VoxelType::MahoganyFence | VoxelType::BirchFence => 3.0,
+            VoxelType::CopperOre | VoxelType::AmethystOre => 4.5,
+            VoxelType::Furnace => 5.25,
+            VoxelType::Chest => 3.75,
+        };
+        Some(seconds)
+    }
+
+    /// The tool class that speeds up breaking the block
+    pub fn preferred_tool(&self) -> Option<ToolClass> {
+        match self {
+            VoxelType::Stone
+            | VoxelType::Cobblestone
+            | VoxelType::Sandstone
+            | VoxelType::Stonebrick
+            | VoxelType::CopperOre
+            | VoxelType::AmethystOre
+            | VoxelType::Furnace
+            | VoxelType::StoneSlab
+            | VoxelType::StoneStairs
+            | VoxelType::Ice => Some(ToolClass::Pickaxe),
+            VoxelType::WalnutWood
+            | VoxelType::WalnutPlanks
+            | VoxelType::SpruceWood
+            | VoxelType::SprucePlanks
+            | VoxelType::MahoganyWood
+            | VoxelType::MahoganyPlanks
+            | VoxelType::BirchWood
+            | VoxelType::BirchPlanks
+            | VoxelType::Chest => Some(ToolClass::Axe),
+            block if block.is_wooden_shape() => Some(ToolClass::Axe),
+            VoxelType::Dirt | VoxelType::Grass | VoxelType::Gravel | VoxelType::Sand | VoxelType::Snow => {
+                Some(ToolClass::Shovel)
+            }
+            _ => None,
+        }
+    }
+
+    /// Stone-like blocks drop nothing and break slowly without a pickaxe
+    pub fn requires_tool(&self) -> bool {
+        matches!(
+            self,
+            VoxelType::Stone
+                | VoxelType::Cobblestone
+                | VoxelType::Sandstone
+                | VoxelType::Stonebrick
+                | VoxelType::CopperOre
+                | VoxelType::AmethystOre
+                | VoxelType::Furnace
+                | VoxelType::StoneSlab
+                | VoxelType::StoneStairs
+        )
+    }
+
+    /// Slabs, stairs and fences made of planks
+    fn is_wooden_shape(&self) -> bool {
+        self.shape() != Shape::Cube && self.shape() != Shape::Cross && !matches!(self, VoxelType::StoneSlab | VoxelType::StoneStairs)
+    }
+
+    fn harvestable_with(&self, tool: Option<Tool>) -> bool {
+        !self.requires_tool() || tool.map(|tool| tool.class()) == self.preferred_tool()
+    }
+
+    /// Seconds to break the block while holding `tool`, None if it can't be broken
+    pub fn break_time(&self, tool: Option<Tool>) -> Option<f32> {
+        let mut seconds = self.hardness()?;
+        if let Some(tool) = tool.filter(|tool| Some(tool.class()) == self.preferred_tool()) {
+            seconds /= tool.tier().speed();
+        }
+        if !self.harvestable_with(tool) {
+            seconds *= WRONG_TOOL_PENALTY;
+        }
+        Some(seconds)
+    }
+
+    /// What the block leaves behind when broken with `tool`
+    pub fn drops(&self, tool: Option<Tool>) -> Option<ItemStack> {
+        if !self.harvestable_with(tool) {
+            return None;
+        }
+        match self {
+            VoxelType::Air | VoxelType::Water | VoxelType::Ice | VoxelType::Glass => None,
+            VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves | VoxelType::BirchLeaves => None,
+            VoxelType::TallGrass => None,
+            VoxelType::Stone => Some(ItemStack::new(Item::Block(VoxelType::Cobblestone), 1)),
+            VoxelType::Grass => Some(ItemStack::new(Item::Block(VoxelType::Dirt), 1)),
+            VoxelType::AmethystOre => Some(ItemStack::new(Item::Material(Material::AmethystShard), 2)),
+            block => Some(ItemStack::new(Item::Block(*block), 1)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stone_drops_cobblestone() {
+        let drop = VoxelType::Stone.drops(Some(Tool::WoodenPickaxe));
+        assert_eq!(drop, Some(ItemStack::new(Item::Block(VoxelType::Cobblestone), 1)));
+    }
+
+    #[test]
+    fn stone_without_a_pickaxe_drops_nothing() {
+        assert_eq!(VoxelType::Stone.drops(None), None);
+        assert_eq!(VoxelType::Stone.drops(Some(Tool::CopperShovel)), None);
+        // It still breaks, just slower than with a pickaxe
+        assert!(VoxelType::Stone.break_time(None) > VoxelType::Stone.break_time(Some(Tool::WoodenPickaxe)));
+    }
+
+    #[test]
+    fn a_better_tier_is_faster() {
+        let time = |tool| VoxelType::Stone.break_time(Some(tool)).unwrap();
+        assert!(time(Tool::StonePickaxe) < time(Tool::WoodenPickaxe));
+        assert!(time(Tool::CopperPickaxe) < time(Tool::StonePickaxe));
+        // A better tool of the wrong class doesn't help
+        assert_eq!(
+            VoxelType::Dirt.break_time(Some(Tool::CopperPickaxe)),
+            VoxelType::Dirt.break_time(None)
+        );
+    }
+
+    #[test]
+    fn blocks_that_need_no_tool_drop_by_hand() {
+        assert_eq!(VoxelType::Dirt.drops(None), Some(ItemStack::new(Item::Block(VoxelType::Dirt), 1)));
+        assert_eq!(VoxelType::Grass.drops(None), Some(ItemStack::new(Item::Block(VoxelType::Dirt), 1)));
+        assert_eq!(VoxelType::Water.break_time(None), None);
+    }
+}
@@ -1,8 +1,28 @@
 // player.rs
 use ferrousgl::{GlWindow, Mesh, Shader, WindowKey};
 use glam::{Mat4, Vec3, Vec4};
+use serde::{Deserialize, Serialize};
 
-use crate::{utils::{collides_aabb::collides_aabb, tree_gen::generate_mahogany_tree}, voxel::VoxelType};
+use crate::{inventory::{Inventory, ItemStack}, item::Item, utils::{collides_aabb::collides_aabb, cube_face::cube_face, tree_gen::TreeSpecies}, voxel::{Placement, VoxelType}};
+
+/// Survival players collect the blocks they break and use them up when placing,
+/// creative players place from an unlimited supply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    Survival,
+    Creative,
+}
+
+/// How far along breaking the targeted block is, from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BreakProgress {
+    target: (i32, i32, i32),
+    progress: f32,
+}
+
+/// Crack overlay stages, drawn from the bottom row of the block atlas
+const CRACK_STAGES: usize = 10;
+const CRACK_ATLAS_ROW: usize = 15;
 
 pub struct Player {
     pub position: Vec3,
@@ -10,14 +30,18 @@ pub struct Player {
     pub rotation: (f32, f32), // pitch, yaw
     pub on_ground: bool,
     pub flight_mode: bool,    // New field for flight mode
+    pub game_mode: GameMode,
     pub input_wait_time: f32, // Used to manage input timing
     hand_mesh: Mesh, // Add this line
+    breaking: Option<BreakProgress>,
+    crack_meshes: Vec<Mesh>,
 }
 
 // Player hitbox constants (width, height, depth)
 const PLAYER_WIDTH: f32 = 0.6;
 const PLAYER_HEIGHT: f32 = 1.8;
 const PLAYER_DEPTH: f32 = 0.6;
+const STEP_HEIGHT: f32 = 0.5; // Slabs and stairs can be walked up
 
 impl Player {
     pub fn new(position: Vec3) -> Self {
@@ -30,21 +54,28 @@ impl Player {
             rotation: (0.0, 0.0),
             on_ground: false,
             flight_mode: false, // Default to no flight\
+            game_mode: GameMode::Survival,
             input_wait_time: 0.0,
             hand_mesh, // Add this line
+            breaking: None,
+            crack_meshes: (0..CRACK_STAGES).map(create_crack_mesh).collect(),
         }
     }
 
+    /// Moves the player and uses the mouse on blocks. Returns the position of a block
+    /// entity the player right-clicked to open.
     pub fn handle_input(
         &mut self,
         window: &mut GlWindow,
         delta_time: f32,
         world: &mut crate::world::World,
-        move_mouse: bool,
-        selected_voxel: VoxelType,
-    ) {
+        capture_input: bool,
+        inventory: &mut Inventory,
+    ) -> Option<(i32, i32, i32)> {
+        let mut opened = None;
+
         // Toggle flight mode when F1 is pressed (unchanged)
-        if window.is_key_pressed(WindowKey::F1) {
+        if capture_input && window.is_key_pressed(WindowKey::F1) {
             self.flight_mode = !self.flight_mode;
             if self.flight_mode {
                 println!("Flight mode enabled");
@@ -54,8 +85,16 @@ impl Player {
             }
         }
 
+        // Switch between survival and creative when F2 is pressed
+        if capture_input && window.is_key_pressed(WindowKey::F2) {
+            self.game_mode = match self.game_mode {
+                GameMode::Survival => GameMode::Creative,
+                GameMode::Creative => GameMode::Survival,
+            };
+        }
+
         // --- Mouse look --- (unchanged)
-        if move_mouse {
+        if capture_input {
             let mouse_sensitivity = 0.002;
             let mouse_delta = window.get_mouse_delta();
             self.rotation.1 += mouse_delta.0 as f32 * mouse_sensitivity;
@@ -94,26 +133,26 @@ impl Player {
         let forward = Vec3::new(right.z, 0.0, -right.x).normalize(); // Perpendicular to forward
 
         // Handle input
-        if window.is_key_held(WindowKey::W) {
+        if capture_input && window.is_key_held(WindowKey::W) {
             move_dir += forward;
         }
-        if window.is_key_held(WindowKey::S) {
+        if capture_input && window.is_key_held(WindowKey::S) {
             move_dir -= forward;
         }
-        if window.is_key_held(WindowKey::D) {
+        if capture_input && window.is_key_held(WindowKey::D) {
             move_dir += right;
         }
-        if window.is_key_held(WindowKey::A) {
+        if capture_input && window.is_key_held(WindowKey::A) {
             move_dir -= right;
         }
 
         // Flight mode specific controls
         if self.flight_mode {
             let up = Vec3::Y;
-            if window.is_key_held(WindowKey::Space) {
+            if capture_input && window.is_key_held(WindowKey::Space) {
                 move_dir += up;
             }
-            if window.is_key_held(WindowKey::LeftControl) {
+            if capture_input && window.is_key_held(WindowKey::LeftControl) {
                 move_dir -= up;
             }
         }
@@ -123,37 +162,73 @@ impl Player {
             move_dir = move_dir.normalize();
         }
 
-        if window.is_key_pressed(WindowKey::Y) {
+        if capture_input && window.is_key_pressed(WindowKey::Y) {
             // Example voxel data - you'd replace this with your actual data
-            let voxels_to_load = generate_mahogany_tree((0, 0, 0));
+            let voxels_to_load = TreeSpecies::Mahogany
+                .default_definition()
+                .generate((0, 0, 0), &mut rand::rng())
+                .into_vec();
             
             world.load_voxels_world_async(voxels_to_load, (self.position.x as i32, self.position.y as i32, self.position.z as i32));
         }
 
-        if window.is_mouse_button_pressed(glfw::MouseButtonLeft) {
+        if capture_input && window.is_mouse_button_held(glfw::MouseButtonLeft) {
+            match self.game_mode {
+                GameMode::Survival => self.mine(world, inventory, delta_time),
+                GameMode::Creative => {
+                    if self.input_wait_time == 0.0 {
+                        if let Some((x, y, z, _, _, _)) = self.raycast(world, 5.0, 0.1) {
+                            // Creative breaking drops nothing, but never loses stored items
+                            Self::break_into_inventory(world, inventory, (x, y, z), None);
+                            self.input_wait_time = 0.2;
+                        }
+                    }
+                }
+            }
+        } else if capture_input && window.is_mouse_button_pressed(glfw::MouseButtonRight) {
             if self.input_wait_time == 0.0 {
-                if let Some((x, y, z, _, _, _)) = self.raycast(world, 5.0, 0.1) {
-                    world.set_voxel_main_thread(x, y, z, crate::voxel::VoxelType::Air);
+                if let Some((hx, hy, hz, x, y, z)) = self.raycast(world, 5.0, 0.1) {
+                    // Open the targeted block entity, or place the block of the selected
+                    // item adjacent to the hit face
+                    if world.with_block_entity(hx, hy, hz, |_| ()).is_some() {
+                        opened = Some((hx, hy, hz));
+                    } else if let Some(block) = inventory.selected_stack().and_then(|stack| stack.item.places()) {
+                        let normal = (x - hx, y - hy, z - hz);
+                        let placement = Placement {
+                            normal,
+                            look: (yaw.cos(), yaw.sin()),
+                            hit_height: self.hit_height((x, y, z), normal),
+                            replacing: world.get_voxel(x, y, z),
+                        };
+                        let state = block.placement_state(&placement);
+                        if world.set_block_main_thread(x, y, z, block, state) && self.game_mode == GameMode::Survival {
+                            inventory.take_selected();
+                        }
+                    }
                     self.input_wait_time = 0.2;
                 }
             }
-        } else if window.is_mouse_button_pressed(glfw::MouseButtonRight) {
-            if self.input_wait_time == 0.0 {
-                if let Some((_, _, _, x, y, z)) = self.raycast(world, 5.0, 0.1) {
-                    // Place block adjacent to the hit face
-                    world.set_voxel_main_thread(x, y, z, selected_voxel); // Or whatever block type you want
+        } else if capture_input && window.is_mouse_button_pressed(glfw::MouseButtonMiddle) {
+            // Pick the targeted block into the selected hotbar slot
+            if self.input_wait_time == 0.0 && self.game_mode == GameMode::Creative {
+                if let Some((x, y, z, _, _, _)) = self.raycast(world, 5.0, 0.1) {
+                    let picked = world.get_voxel(x, y, z);
+                    inventory.hotbar[inventory.selected] = Some(ItemStack::new(Item::Block(picked), 1));
                     self.input_wait_time = 0.2;
                 }
             }
         } else {
             self.input_wait_time = 0.0;
         }
+        if !(capture_input && window.is_mouse_button_held(glfw::MouseButtonLeft)) {
+            self.breaking = None;
+        }
         self.input_wait_time = (self.input_wait_time - delta_time).max(0.0);
 
         // --- Movement physics ---
         if self.flight_mode {
             // Flight physics
-            let fly_speed = if window.is_key_held(WindowKey::LeftShift) {
+            let fly_speed = if capture_input && window.is_key_held(WindowKey::LeftShift) {
                 20.0
             } else {
                 10.0
@@ -161,12 +236,12 @@ impl Player {
             self.velocity = move_dir * fly_speed;
         } else {
             // Ground physics
-            let accel = if window.is_key_held(WindowKey::LeftShift) {
+            let accel = if capture_input && window.is_key_held(WindowKey::LeftShift) {
                 30.0
             } else {
                 15.0
             };
-            let max_speed = if window.is_key_held(WindowKey::LeftShift) {
+            let max_speed = if capture_input && window.is_key_held(WindowKey::LeftShift) {
                 10.0
             } else {
                 5.0
@@ -193,7 +268,7 @@ impl Player {
             self.velocity.z = horizontal_velocity.z;
 
             // Jumping
-            if window.is_key_held(WindowKey::Space) && self.on_ground {
+            if capture_input && window.is_key_held(WindowKey::Space) && self.on_ground {
                 self.velocity.y = 8.0;
                 self.on_ground = false;
             }
@@ -214,6 +289,8 @@ impl Player {
             let try_x = new_position + glam::vec3(self.velocity.x * delta_time, 0.0, 0.0);
             if !collides_aabb(try_x, PLAYER_WIDTH, PLAYER_HEIGHT, PLAYER_DEPTH, world) {
                 new_position.x = try_x.x;
+            } else if let Some(stepped) = self.step_up(try_x, world) {
+                new_position = stepped;
             } else {
                 self.velocity.x = 0.0;
             }
@@ -234,12 +311,94 @@ impl Player {
             let try_z = new_position + glam::vec3(0.0, 0.0, self.velocity.z * delta_time);
             if !collides_aabb(try_z, PLAYER_WIDTH, PLAYER_HEIGHT, PLAYER_DEPTH, world) {
                 new_position.z = try_z.z;
+            } else if let Some(stepped) = self.step_up(try_z, world) {
+                new_position = stepped;
             } else {
                 self.velocity.z = 0.0;
             }
         }
 
         self.position = new_position;
+        opened
+    }
+
+    /// Walking into a slab or stairs on the ground climbs onto it instead of stopping
+    fn step_up(&self, blocked: glam::Vec3, world: &crate::world::World) -> Option<glam::Vec3> {
+        let stepped = blocked + glam::vec3(0.0, STEP_HEIGHT, 0.0);
+        (self.on_ground && !collides_aabb(stepped, PLAYER_WIDTH, PLAYER_HEIGHT, PLAYER_DEPTH, world)).then_some(stepped)
+    }
+
+    /// Advances breaking the targeted block while the left button is held. Progress starts
+    /// over whenever the target changes, and the block's drops and anything stored in it
+    /// go to the inventory.
+    fn mine(&mut self, world: &mut crate::world::World, inventory: &mut Inventory, delta_time: f32) {
+        let Some((x, y, z, _, _, _)) = self.raycast(world, 5.0, 0.1) else {
+            self.breaking = None;
+            return;
+        };
+        let block = world.get_voxel(x, y, z);
+        let tool = inventory.selected_stack().and_then(|stack| stack.item.tool());
+        let Some(break_time) = block.break_time(tool) else {
+            self.breaking = None;
+            return;
+        };
+
+        let progress = match self.breaking {
+            Some(breaking) if breaking.target == (x, y, z) => breaking.progress,
+            _ => 0.0,
+        } + delta_time / break_time.max(f32::EPSILON);
+
+        if progress < 1.0 {
+            self.breaking = Some(BreakProgress { target: (x, y, z), progress });
+        } else if Self::break_into_inventory(world, inventory, (x, y, z), block.drops(tool)) {
+            self.breaking = None;
+        } else {
+            // Held at the last crack stage until the inventory has room for what is stored in it
+            self.breaking = Some(BreakProgress { target: (x, y, z), progress: 1.0 });
+        }
+    }
+
+    /// Breaks the block, putting anything stored in it and then `drops` into the inventory.
+    /// There are no dropped items yet, so a chest or furnace whose contents don't fit stays,
+    /// while drops that don't fit are lost.
+    fn break_into_inventory(
+        world: &crate::world::World,
+        inventory: &mut Inventory,
+        (x, y, z): (i32, i32, i32),
+        drops: Option<ItemStack>,
+    ) -> bool {
+        let contents = world.with_block_entity(x, y, z, |entity| entity.contents()).unwrap_or_default();
+        let mut after = inventory.clone();
+        if contents.into_iter().any(|stack| after.add(stack).is_some()) {
+            return false;
+        }
+        let broken = world.break_block(x, y, z).is_some();
+        if broken {
+            if let Some(drops) = drops {
+                after.add(drops);
+            }
+            *inventory = after;
+        }
+        broken
+    }
+
+    /// Draws cracks over the block being broken with the voxel shader, after the world
+    pub fn render_break_overlay(&self, window: &GlWindow, shader: &Shader) {
+        let Some(breaking) = self.breaking else {
+            return;
+        };
+        let stage = ((breaking.progress * CRACK_STAGES as f32) as usize).min(CRACK_STAGES - 1);
+        let (x, y, z) = breaking.target;
+
+        // Slightly larger than the block so the cracks don't z-fight with its faces
+        let inflate = 0.002;
+        let model = Mat4::from_translation(Vec3::new(x as f32, y as f32, z as f32) - Vec3::splat(inflate / 2.0))
+            * Mat4::from_scale(Vec3::splat(1.0 + inflate));
+        shader.set_uniform_matrix_4fv("model", model.as_ref());
+        shader.set_uniform_1f("usingAlpha", 1.0);
+        window.set_blend_mode(ferrousgl::BlendMode::Alpha);
+        window.render_mesh(&self.crack_meshes[stage]);
+        window.set_blend_mode(ferrousgl::BlendMode::None);
     }
 
     pub fn get_view_matrix(&self) -> Mat4 {
@@ -266,6 +425,27 @@ impl Player {
         Mat4::look_at_rh(eye_position, target, up)
     }
 
+    /// Height within cell `(x, y, z)` where the view ray enters it through the face with
+    /// `normal`, from 0 to 1
+    fn hit_height(&self, (x, y, z): (i32, i32, i32), normal: (i32, i32, i32)) -> f32 {
+        let (pitch, yaw) = self.rotation;
+        let direction = Vec3::new(yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+        let eye = self.position + Vec3::new(0.0, PLAYER_HEIGHT * 0.9, 0.0);
+
+        // The face between the cell and the clicked block, as an axis and a coordinate on it
+        let (axis, plane) = match normal {
+            (0, 1, 0) => return 0.0,
+            (0, -1, 0) => return 1.0,
+            (1, 0, 0) => (0, x as f32),
+            (-1, 0, 0) => (0, x as f32 + 1.0),
+            (0, 0, 1) => (2, z as f32),
+            (0, 0, -1) => (2, z as f32 + 1.0),
+            _ => return 0.0,
+        };
+        let t = (plane - eye[axis]) / direction[axis];
+        (eye.y + direction.y * t - y as f32).clamp(0.0, 1.0)
+    }
+
     pub fn raycast(
         &self,
         world: &crate::world::World,
@@ -298,7 +478,8 @@ impl Player {
 
         while distance < max_distance {
             // Check current block
-            if world.get_voxel(block_x, block_y, block_z) != crate::voxel::VoxelType::Air {
+            // Water doesn't stop the ray, so blocks can be broken and placed underwater
+            if !matches!(world.get_voxel(block_x, block_y, block_z), VoxelType::Air | VoxelType::Water) {
                 return Some((
                     block_x,
                     block_y,
@@ -328,6 +509,48 @@ impl Player {
     }
 }
 
+/// A unit cube with every face showing crack `stage`, laid out like chunk meshes
+fn create_crack_mesh(stage: usize) -> Mesh {
+    const FACE_NORMALS: [[f32; 3]; 6] = [
+        [0.0, 1.0, 0.0],
+        [0.0, -1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, -1.0],
+        [1.0, 0.0, 0.0],
+        [-1.0, 0.0, 0.0],
+    ];
+    // Same tile coordinates as `VoxelType::get_face_texture`
+    let tile = 1.0 / 16.0;
+    let margin = tile * 0.01;
+    let uvs = [
+        (stage + 1) as f32 * tile - margin,
+        (CRACK_ATLAS_ROW + 1) as f32 * tile - margin,
+        -tile + 2.0 * margin,
+        -tile + 2.0 * margin,
+    ];
+
+    let mut vertices = Vec::with_capacity(6 * 4 * 8);
+    let mut indices = Vec::with_capacity(6 * 6);
+    for (face, normal) in FACE_NORMALS.iter().enumerate() {
+        let (positions, face_uvs) = cube_face(0, 0, 0, uvs, face);
+        for (pos, uv) in positions.iter().zip(face_uvs) {
+            vertices.extend_from_slice(&[pos[0], pos[1], pos[2], normal[0], normal[1], normal[2], uv[0], uv[1]]);
+        }
+        let base = face as u32 * 4;
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    let mut mesh = Mesh::new();
+    mesh.update_vertices(&vertices);
+    mesh.add_vertex_attributes(&[
+        (0, 3, gl::FLOAT, false),  // position
+        (1, 3, gl::FLOAT, false),  // normal
+        (2, 2, gl::FLOAT, false),  // uv
+    ]);
+    mesh.update_indices(&indices);
+    mesh
+}
+
 fn create_cube_mesh() -> Mesh {
     // 8 unique vertices (position, normal, uv)
     let vertices: [f32; 8 * (3 + 3 + 2)] = [
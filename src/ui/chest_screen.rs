@@ -0,0 +1,119 @@
+use ferrousgl::GlWindow;
+use glam::Vec2;
+
+use crate::{
+    block_entity::{
+        BlockEntity,
+        chest::{CHEST_COLUMNS, CHEST_SLOTS, Chest},
+    },
+    inventory::{HOTBAR_SLOTS, Inventory, ItemStack},
+    ui::{
+        UiFrame,
+        item_renderer::ItemRenderer,
+        slot_grid::SlotGrid,
+        slot_interaction::{SlotInteraction, SlotView},
+    },
+    world::World,
+};
+
+// Indices into `ChestScreen::views`
+const MAIN_VIEW: usize = 0;
+const HOTBAR_VIEW: usize = 1;
+const CHEST_VIEW: usize = 2;
+
+/// The contents of the chest at `position` above the player's main grid and hotbar.
+/// Shift-clicking moves stacks between the chest and the inventory.
+pub struct ChestScreen {
+    position: (i32, i32, i32),
+    interaction: SlotInteraction,
+    ui_scale: f32,
+}
+
+// Runs `f` on the chest at `position`. Its chunk may briefly be out on a worker, in which
+// case the screen skips the frame.
+fn with_chest<R>(world: &World, (x, y, z): (i32, i32, i32), f: impl FnOnce(&mut Chest) -> R) -> Option<R> {
+    world
+        .with_block_entity(x, y, z, |entity| match entity {
+            BlockEntity::Chest(chest) => Some(f(chest)),
+            _ => None,
+        })
+        .flatten()
+}
+
+/// Where everything on the screen goes for a window of `window_size`
+struct Layout {
+    chest: SlotGrid,
+    main: SlotGrid,
+    hotbar: SlotGrid,
+}
+
+impl Layout {
+    fn new(window_size: (u32, u32), ui_scale: f32, main_rows: usize) -> Self {
+        let size = 30.0 * ui_scale;
+        let centre_x = window_size.0 as f32 / 2.0;
+        let chest_rows = CHEST_SLOTS / CHEST_COLUMNS;
+        // Chest rows, a gap, the main rows, a smaller gap and the hotbar
+        let height = size * (chest_rows as f32 + 0.5 + main_rows as f32 + 0.25 + 1.0);
+        let top = window_size.1 as f32 / 2.0 - height / 2.0;
+        let main_top = top + size * (chest_rows as f32 + 0.5);
+
+        Self {
+            chest: SlotGrid::centred(centre_x, top, CHEST_COLUMNS, chest_rows, size),
+            main: SlotGrid::centred(centre_x, main_top, HOTBAR_SLOTS, main_rows, size),
+            hotbar: SlotGrid::centred(centre_x, main_top + size * (main_rows as f32 + 0.25), HOTBAR_SLOTS, 1, size),
+        }
+    }
+}
+
+impl ChestScreen {
+    pub fn new(position: (i32, i32, i32)) -> Self {
+        Self {
+            position,
+            interaction: SlotInteraction::new(),
+            ui_scale: 1.5,
+        }
+    }
+
+    fn layout(&self, window_size: (u32, u32), inventory: &Inventory) -> Layout {
+        Layout::new(window_size, self.ui_scale, inventory.main.len() / HOTBAR_SLOTS)
+    }
+
+    fn views<'a>(layout: &Layout, inventory: &'a mut Inventory, chest: &'a mut Chest) -> [SlotView<'a>; 3] {
+        let view = |grid, slots, quick_move_to| SlotView { grid, slots, quick_move_to, take_only: false };
+        [
+            view(layout.main, &mut inventory.main[..], &[CHEST_VIEW][..]),
+            view(layout.hotbar, &mut inventory.hotbar[..], &[CHEST_VIEW]),
+            view(layout.chest, &mut chest.slots[..], &[MAIN_VIEW, HOTBAR_VIEW]),
+        ]
+    }
+
+    pub fn update(&mut self, window: &GlWindow, inventory: &mut Inventory, world: &World) {
+        let layout = self.layout(window.get_window_size(), inventory);
+        let interaction = &mut self.interaction;
+        with_chest(world, self.position, |chest| {
+            let mut views = Self::views(&layout, inventory, chest);
+            interaction.update(window, &mut views);
+        });
+    }
+
+    pub fn render(&mut self, window: &mut GlWindow, frame: &UiFrame, items: &ItemRenderer, inventory: &mut Inventory, world: &World) {
+        let layout = self.layout(window.get_window_size(), inventory);
+
+        let text_scale = 2.0 * self.ui_scale.floor();
+        let label_offset = Vec2::new(0.0, -items.text.line_height(text_scale) - 2.0 * self.ui_scale);
+        items.text.render(window, frame, "Chest", layout.chest.origin + label_offset, text_scale);
+
+        let interaction = &self.interaction;
+        with_chest(world, self.position, |chest| {
+            let views = Self::views(&layout, inventory, chest);
+            interaction.render(window, frame, items, &views);
+        });
+    }
+
+    /// Puts the stack held by the cursor back into the inventory. Returns whatever
+    /// doesn't fit.
+    pub fn close(&mut self, inventory: &mut Inventory) -> Option<ItemStack> {
+        self.interaction.return_held(inventory);
+        self.interaction.take_held()
+    }
+}
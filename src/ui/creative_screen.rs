@@ -0,0 +1,146 @@
+use ferrousgl::{GlWindow, WindowKey};
+use glam::Vec2;
+
+use crate::{
+    inventory::{HOTBAR_SLOTS, Inventory, ItemStack},
+    item::{Item, ItemRegistry},
+    ui::{UiFrame, hotbar::{Hotbar, NUMBER_KEYS}, item_renderer::ItemRenderer, slot_grid::SlotGrid},
+};
+
+const COLUMNS: usize = HOTBAR_SLOTS;
+const ROWS_VISIBLE: usize = 5;
+
+const LETTER_KEYS: [(WindowKey, char); 27] = [
+    (WindowKey::A, 'a'), (WindowKey::B, 'b'), (WindowKey::C, 'c'), (WindowKey::D, 'd'),
+    (WindowKey::E, 'e'), (WindowKey::F, 'f'), (WindowKey::G, 'g'), (WindowKey::H, 'h'),
+    (WindowKey::I, 'i'), (WindowKey::J, 'j'), (WindowKey::K, 'k'), (WindowKey::L, 'l'),
+    (WindowKey::M, 'm'), (WindowKey::N, 'n'), (WindowKey::O, 'o'), (WindowKey::P, 'p'),
+    (WindowKey::Q, 'q'), (WindowKey::R, 'r'), (WindowKey::S, 's'), (WindowKey::T, 't'),
+    (WindowKey::U, 'u'), (WindowKey::V, 'v'), (WindowKey::W, 'w'), (WindowKey::X, 'x'),
+    (WindowKey::Y, 'y'), (WindowKey::Z, 'z'), (WindowKey::Space, ' '),
+];
+
+/// Every item in a scrollable grid, filtered by typed text. Clicking an item puts a full
+/// stack of it into the selected hotbar slot, pressing 1-9 over it into that slot instead.
+pub struct CreativeScreen {
+    filter: String,
+    scroll_row: usize,
+    ui_scale: f32,
+}
+
+impl CreativeScreen {
+    pub fn new() -> Self {
+        Self {
+            filter: String::new(),
+            scroll_row: 0,
+            ui_scale: 1.5,
+        }
+    }
+
+    /// The items whose name contains the filter text, ignoring case
+    fn matching_items(&self) -> Vec<Item> {
+        ItemRegistry::get()
+            .items()
+            .iter()
+            .copied()
+            .filter(|item| item.name().to_lowercase().contains(&self.filter))
+            .collect()
+    }
+
+    fn grid(&self, window_size: (u32, u32)) -> SlotGrid {
+        let size = 30.0 * self.ui_scale;
+        let top = window_size.1 as f32 / 2.0 - size * ROWS_VISIBLE as f32 / 2.0;
+        SlotGrid::centred(window_size.0 as f32 / 2.0, top, COLUMNS, ROWS_VISIBLE, size)
+    }
+
+    /// Index into `matching_items` of the item in visible slot `slot`
+    fn item_index(&self, slot: usize) -> usize {
+        self.scroll_row * COLUMNS + slot
+    }
+
+    pub fn update(&mut self, window: &GlWindow, inventory: &mut Inventory, hotbar: &Hotbar) {
+        // Typing edits the filter, which starts over from the first row
+        let mut filter_changed = false;
+        for (key, c) in LETTER_KEYS {
+            if window.is_key_pressed(key) {
+                self.filter.push(c);
+                filter_changed = true;
+            }
+        }
+        if window.is_key_pressed(WindowKey::Backspace) {
+            filter_changed |= self.filter.pop().is_some();
+        }
+        if filter_changed {
+            self.scroll_row = 0;
+        }
+
+        let matching = self.matching_items();
+        let max_row = matching.len().div_ceil(COLUMNS).saturating_sub(ROWS_VISIBLE);
+        let scroll = window.get_mouse_wheel_delta().1;
+        if scroll > 0.0 {
+            self.scroll_row = self.scroll_row.saturating_sub(1);
+        } else if scroll < 0.0 {
+            self.scroll_row += 1;
+        }
+        self.scroll_row = self.scroll_row.min(max_row);
+
+        let window_size = window.get_window_size();
+        let (mouse_x, mouse_y) = window.get_mouse_position();
+        let mouse = Vec2::new(mouse_x as f32, mouse_y as f32);
+
+        // Clicking the hotbar picks the slot that clicked items go into
+        if window.is_mouse_button_pressed(glfw::MouseButtonLeft) {
+            if let Some(slot) = hotbar.grid(window_size).slot_at(mouse) {
+                inventory.select(slot);
+            }
+        }
+
+        let hovered = self
+            .grid(window_size)
+            .slot_at(mouse)
+            .and_then(|slot| matching.get(self.item_index(slot)).copied());
+        if let Some(item) = hovered {
+            let full_stack = Some(ItemStack::new(item, item.max_stack_size()));
+            if window.is_mouse_button_pressed(glfw::MouseButtonLeft) {
+                inventory.hotbar[inventory.selected] = full_stack;
+            }
+            for (i, key) in NUMBER_KEYS.into_iter().enumerate() {
+                if window.is_key_pressed(key) {
+                    inventory.hotbar[i] = full_stack;
+                }
+            }
+        }
+    }
+
+    pub fn render(&self, window: &mut GlWindow, frame: &UiFrame, items: &ItemRenderer) {
+        let window_size = window.get_window_size();
+        let grid = self.grid(window_size);
+        let matching = self.matching_items();
+
+        // Search field above the grid, with the current scroll position on the right
+        let text_scale = 2.0 * self.ui_scale.floor();
+        let text_y = grid.origin.y - items.text.line_height(text_scale) - 4.0 * self.ui_scale;
+        let search = format!("Search: {}_", self.filter);
+        items.text.render(window, frame, &search, Vec2::new(grid.origin.x, text_y), text_scale);
+        let total_rows = matching.len().div_ceil(COLUMNS).max(1);
+        let rows = format!("{}/{}", (self.scroll_row + ROWS_VISIBLE).min(total_rows), total_rows);
+        let rows_x = grid.origin.x + grid.width() - items.text.text_width(&rows, text_scale);
+        items.text.render(window, frame, &rows, Vec2::new(rows_x, text_y), text_scale);
+
+        let (mouse_x, mouse_y) = window.get_mouse_position();
+        let hovered = grid.slot_at(Vec2::new(mouse_x as f32, mouse_y as f32));
+        for slot in 0..grid.len {
+            let centre = grid.slot_centre(slot);
+            items.render_slot(window, frame, centre, grid.slot_size);
+            if let Some(&item) = matching.get(self.item_index(slot)) {
+                items.render_stack(window, frame, &ItemStack::new(item, 1), centre, grid.slot_size);
+            }
+        }
+        if let Some(slot) = hovered {
+            items.render_selector(window, frame, grid.slot_centre(slot), grid.slot_size);
+            if let Some(item) = matching.get(self.item_index(slot)) {
+                items.render_tooltip(window, frame, item.name(), Vec2::new(mouse_x as f32, mouse_y as f32));
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use ferrousgl::GlWindow;
+use glam::Vec2;
+
+use crate::{
+    block_entity::{
+        BlockEntity,
+        furnace::Furnace,
+    },
+    inventory::{HOTBAR_SLOTS, Inventory, ItemStack},
+    ui::{
+        UiFrame,
+        item_renderer::ItemRenderer,
+        progress_icon::{FillDirection, ProgressIcon},
+        slot_grid::SlotGrid,
+        slot_interaction::{SlotInteraction, SlotView},
+    },
+    world::World,
+};
+
+// Indices into `FurnaceScreen::views`
+const MAIN_VIEW: usize = 0;
+const HOTBAR_VIEW: usize = 1;
+
+/// Furnace rows above the player's inventory: input, flame and fuel in one column
+const FURNACE_ROWS: usize = 3;
+
+/// The slots of the furnace at `position` above the player's main grid and hotbar, with
+/// an arrow for smelting progress and a flame for the fuel left
+pub struct FurnaceScreen {
+    position: (i32, i32, i32),
+    interaction: SlotInteraction,
+    arrow: ProgressIcon,
+    flame: ProgressIcon,
+    ui_scale: f32,
+}
+
+// Runs `f` on the furnace at `position`. Its chunk may briefly be out on a worker, in
+// which case the screen skips the frame.
+fn with_furnace<R>(world: &World, (x, y, z): (i32, i32, i32), f: impl FnOnce(&mut Furnace) -> R) -> Option<R> {
+    world
+        .with_block_entity(x, y, z, |entity| match entity {
+            BlockEntity::Furnace(furnace) => Some(f(furnace)),
+            _ => None,
+        })
+        .flatten()
+}
+
+/// Where everything on the screen goes for a window of `window_size`
+struct Layout {
+    main: SlotGrid,
+    hotbar: SlotGrid,
+    input: SlotGrid,
+    fuel: SlotGrid,
+    output: SlotGrid,
+    flame: Vec2,
+    arrow: Vec2,
+}
+
+impl Layout {
+    fn new(window_size: (u32, u32), ui_scale: f32, main_rows: usize) -> Self {
+        let size = 30.0 * ui_scale;
+        let centre_x = window_size.0 as f32 / 2.0;
+        // Furnace rows, a gap, the main rows, a smaller gap and the hotbar
+        let height = size * (FURNACE_ROWS as f32 + 0.5 + main_rows as f32 + 0.25 + 1.0);
+        let top = window_size.1 as f32 / 2.0 - height / 2.0;
+        let main_top = top + size * (FURNACE_ROWS as f32 + 0.5);
+        let left = centre_x - size * HOTBAR_SLOTS as f32 / 2.0;
+        let slot = |column: f32, row: f32| SlotGrid::new(Vec2::new(left + size * column, top + size * row), 1, 1, size);
+
+        Self {
+            main: SlotGrid::centred(centre_x, main_top, HOTBAR_SLOTS, main_rows, size),
+            hotbar: SlotGrid::centred(centre_x, main_top + size * (main_rows as f32 + 0.25), HOTBAR_SLOTS, 1, size),
+            input: slot(2.0, 0.0),
+            fuel: slot(2.0, 2.0),
+            output: slot(6.0, 1.0),
+            flame: Vec2::new(left + size * 2.5, top + size * 1.5),
+            arrow: Vec2::new(left + size * 4.5, top + size * 1.5),
+        }
+    }
+}
+
+impl FurnaceScreen {
+    pub fn new(position: (i32, i32, i32)) -> Self {
+        Self {
+            position,
+            interaction: SlotInteraction::new(),
+            arrow: ProgressIcon::new(Vec2::new(0.0, 176.0), Vec2::new(24.0, 176.0), Vec2::new(22.0, 11.0), FillDirection::Right),
+            flame: ProgressIcon::new(Vec2::new(48.0, 176.0), Vec2::new(64.0, 176.0), Vec2::new(14.0, 14.0), FillDirection::Up),
+            ui_scale: 1.5,
+        }
+    }
+
+    fn layout(&self, window_size: (u32, u32), inventory: &Inventory) -> Layout {
+        Layout::new(window_size, self.ui_scale, inventory.main.len() / HOTBAR_SLOTS)
+    }
+
+    fn views<'a>(layout: &Layout, inventory: &'a mut Inventory, furnace: &'a mut Furnace) -> [SlotView<'a>; 5] {
+        let view = |grid, slots, quick_move_to, take_only| SlotView { grid, slots, quick_move_to, take_only };
+        let [input, fuel, output] = &mut furnace.slots;
+        [
+            view(layout.main, &mut inventory.main[..], &[HOTBAR_VIEW][..], false),
+            view(layout.hotbar, &mut inventory.hotbar[..], &[MAIN_VIEW], false),
+            view(layout.input, std::slice::from_mut(input), &[MAIN_VIEW, HOTBAR_VIEW], false),
+            view(layout.fuel, std::slice::from_mut(fuel), &[MAIN_VIEW, HOTBAR_VIEW], false),
+            view(layout.output, std::slice::from_mut(output), &[MAIN_VIEW, HOTBAR_VIEW], true),
+        ]
+    }
+
+    pub fn update(&mut self, window: &GlWindow, inventory: &mut Inventory, world: &World) {
+        let layout = self.layout(window.get_window_size(), inventory);
+        let interaction = &mut self.interaction;
+        with_furnace(world, self.position, |furnace| {
+            let mut views = Self::views(&layout, inventory, furnace);
+            interaction.update(window, &mut views);
+        });
+    }
+
+    pub fn render(&mut self, window: &mut GlWindow, frame: &UiFrame, items: &ItemRenderer, inventory: &mut Inventory, world: &World) {
+        let layout = self.layout(window.get_window_size(), inventory);
+
+        let text_scale = 2.0 * self.ui_scale.floor();
+        let label_offset = Vec2::new(0.0, -items.text.line_height(text_scale) - 2.0 * self.ui_scale);
+        items.text.render(window, frame, "Furnace", layout.input.origin + label_offset, text_scale);
+
+        let icon_scale = 2.0 * self.ui_scale;
+        let (arrow, flame, interaction) = (&mut self.arrow, &mut self.flame, &self.interaction);
+        with_furnace(world, self.position, |furnace| {
+            flame.render(window, frame, layout.flame, icon_scale, furnace.burn_fraction());
+            arrow.render(window, frame, layout.arrow, icon_scale, furnace.smelt_fraction());
+            let views = Self::views(&layout, inventory, furnace);
+            interaction.render(window, frame, items, &views);
+        });
+    }
+
+    /// Puts the stack held by the cursor back into the inventory. Returns whatever
+    /// doesn't fit.
+    pub fn close(&mut self, inventory: &mut Inventory) -> Option<ItemStack> {
+        self.interaction.return_held(inventory);
+        self.interaction.take_held()
+    }
+}
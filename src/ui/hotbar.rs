@@ -0,0 +1,69 @@
+use ferrousgl::{GlWindow, WindowKey};
+
+use crate::{
+    inventory::{HOTBAR_SLOTS, Inventory},
+    ui::{UiFrame, item_renderer::ItemRenderer, slot_grid::SlotGrid},
+};
+
+pub const NUMBER_KEYS: [WindowKey; HOTBAR_SLOTS] = [
+    WindowKey::Num1,
+    WindowKey::Num2,
+    WindowKey::Num3,
+    WindowKey::Num4,
+    WindowKey::Num5,
+    WindowKey::Num6,
+    WindowKey::Num7,
+    WindowKey::Num8,
+    WindowKey::Num9,
+];
+
+/// The row of hotbar slots along the bottom of the screen
+pub struct Hotbar {
+    ui_scale: f32,
+}
+
+impl Hotbar {
+    pub fn new() -> Self {
+        Self { ui_scale: 1.5 }
+    }
+
+    pub fn slot_size(&self) -> f32 {
+        30.0 * self.ui_scale
+    }
+
+    /// Where the hotbar slots are for a window of `window_size`
+    pub fn grid(&self, window_size: (u32, u32)) -> SlotGrid {
+        let size = self.slot_size();
+        let top = window_size.1 as f32 - size - 5.0 * self.ui_scale;
+        SlotGrid::centred(window_size.0 as f32 / 2.0, top, HOTBAR_SLOTS, 1, size)
+    }
+
+    /// Changes the selected slot with the scroll wheel or the number keys 1-9
+    pub fn update(&self, window: &GlWindow, inventory: &mut Inventory) {
+        let scroll = window.get_mouse_wheel_delta().1;
+        if scroll > 0.0 {
+            inventory.scroll_selection(-1);
+        } else if scroll < 0.0 {
+            inventory.scroll_selection(1);
+        }
+
+        for (i, key) in NUMBER_KEYS.into_iter().enumerate() {
+            if window.is_key_held(key) {
+                inventory.select(i);
+            }
+        }
+    }
+
+    pub fn render(&self, window: &mut GlWindow, frame: &UiFrame, items: &ItemRenderer, inventory: &Inventory) {
+        let grid = self.grid(window.get_window_size());
+        let size = grid.slot_size;
+        for (i, slot) in inventory.hotbar.iter().enumerate() {
+            let centre = grid.slot_centre(i);
+            items.render_slot(window, frame, centre, size);
+            if let Some(stack) = slot {
+                items.render_stack(window, frame, stack, centre, size);
+            }
+        }
+        items.render_selector(window, frame, grid.slot_centre(inventory.selected), size);
+    }
+}
@@ -0,0 +1,203 @@
+use ferrousgl::{GlWindow, WindowKey};
+use glam::Vec2;
+
+use crate::{
+    crafting::{GRID_SIZE, GRID_SLOTS, Recipe, RecipeBook, consume_ingredients},
+    inventory::{HOTBAR_SLOTS, Inventory, ItemStack, fill_into, merge_into},
+    ui::{
+        UiFrame,
+        item_renderer::ItemRenderer,
+        slot_grid::SlotGrid,
+        slot_interaction::{SlotInteraction, SlotView},
+    },
+};
+
+// Indices into `InventoryScreen::views`, the crafting grid sits between the hotbar and result
+const MAIN_VIEW: usize = 0;
+const HOTBAR_VIEW: usize = 1;
+const RESULT_VIEW: usize = 3;
+
+const RECIPE_BOOK_COLUMNS: usize = 3;
+const RECIPE_BOOK_ROWS: usize = 7;
+
+/// The player's main grid and a copy of the hotbar for rearranging items with the mouse,
+/// a crafting grid with its result slot, and a recipe book of everything craftable from
+/// what the player carries. Clicking a recipe lays its ingredients out in the grid.
+pub struct InventoryScreen {
+    interaction: SlotInteraction,
+    crafting: [Option<ItemStack>; GRID_SLOTS],
+    result: [Option<ItemStack>; 1],
+    ui_scale: f32,
+}
+
+/// Where everything on the screen goes for a window of `window_size`
+struct Layout {
+    main: SlotGrid,
+    hotbar: SlotGrid,
+    crafting: SlotGrid,
+    result: SlotGrid,
+    recipe_book: SlotGrid,
+}
+
+impl Layout {
+    fn new(window_size: (u32, u32), ui_scale: f32, main_rows: usize) -> Self {
+        let size = 30.0 * ui_scale;
+        let centre_x = window_size.0 as f32 / 2.0;
+        // Crafting rows, a gap, the main rows, a smaller gap and the hotbar
+        let height = size * (GRID_SIZE as f32 + 0.5 + main_rows as f32 + 0.25 + 1.0);
+        let top = window_size.1 as f32 / 2.0 - height / 2.0;
+        let main_top = top + size * (GRID_SIZE as f32 + 0.5);
+        let left = centre_x - size * HOTBAR_SLOTS as f32 / 2.0;
+
+        Self {
+            main: SlotGrid::centred(centre_x, main_top, HOTBAR_SLOTS, main_rows, size),
+            hotbar: SlotGrid::centred(centre_x, main_top + size * (main_rows as f32 + 0.25), HOTBAR_SLOTS, 1, size),
+            crafting: SlotGrid::new(Vec2::new(left + size, top), GRID_SIZE, GRID_SLOTS, size),
+            result: SlotGrid::new(Vec2::new(left + size * (GRID_SIZE as f32 + 2.0), top + size), 1, 1, size),
+            recipe_book: SlotGrid::new(
+                Vec2::new(left + size * (HOTBAR_SLOTS as f32 + 0.5), top),
+                RECIPE_BOOK_COLUMNS,
+                RECIPE_BOOK_COLUMNS * RECIPE_BOOK_ROWS,
+                size,
+            ),
+        }
+    }
+}
+
+impl InventoryScreen {
+    pub fn new() -> Self {
+        Self {
+            interaction: SlotInteraction::new(),
+            crafting: [None; GRID_SLOTS],
+            result: [None],
+            ui_scale: 1.5,
+        }
+    }
+
+    fn layout(&self, window_size: (u32, u32), inventory: &Inventory) -> Layout {
+        Layout::new(window_size, self.ui_scale, inventory.main.len() / HOTBAR_SLOTS)
+    }
+
+    fn views<'a>(
+        layout: &Layout,
+        inventory: &'a mut Inventory,
+        crafting: &'a mut [Option<ItemStack>; GRID_SLOTS],
+        result: &'a mut [Option<ItemStack>; 1],
+    ) -> [SlotView<'a>; 4] {
+        let view = |grid, slots, quick_move_to, take_only| SlotView { grid, slots, quick_move_to, take_only };
+        [
+            view(layout.main, &mut inventory.main[..], &[HOTBAR_VIEW][..], false),
+            view(layout.hotbar, &mut inventory.hotbar[..], &[MAIN_VIEW], false),
+            view(layout.crafting, &mut crafting[..], &[MAIN_VIEW, HOTBAR_VIEW], false),
+            view(layout.result, &mut result[..], &[MAIN_VIEW, HOTBAR_VIEW], true),
+        ]
+    }
+
+    /// What the player could craft with the items they carry, including those in the grid
+    fn craftable<'a>(&self, inventory: &Inventory, recipes: &'a RecipeBook) -> Vec<&'a Recipe> {
+        let available: Vec<ItemStack> = inventory.stacks().chain(self.crafting.iter().flatten()).copied().collect();
+        let mut craftable = recipes.craftable(&available);
+        craftable.truncate(RECIPE_BOOK_COLUMNS * RECIPE_BOOK_ROWS);
+        craftable
+    }
+
+    pub fn update(&mut self, window: &GlWindow, inventory: &mut Inventory, recipes: &RecipeBook) {
+        let window_size = window.get_window_size();
+        let layout = self.layout(window_size, inventory);
+        let (mouse_x, mouse_y) = window.get_mouse_position();
+        let mouse = Vec2::new(mouse_x as f32, mouse_y as f32);
+
+        if window.is_mouse_button_pressed(glfw::MouseButtonLeft) {
+            if let Some(slot) = layout.recipe_book.slot_at(mouse) {
+                if let Some(recipe) = self.craftable(inventory, recipes).get(slot) {
+                    self.fill_grid(recipe, inventory);
+                }
+            }
+        }
+
+        self.result[0] = recipes.find(&self.crafting).map(Recipe::result);
+        let mut views = Self::views(&layout, inventory, &mut self.crafting, &mut self.result);
+        if self.interaction.update(window, &mut views) == Some(RESULT_VIEW) {
+            consume_ingredients(&mut self.crafting);
+
+            // Shift-clicking crafts as many times as the ingredients and inventory allow
+            let shift = window.is_key_held(WindowKey::LeftShift) || window.is_key_held(WindowKey::RightShift);
+            while let Some(recipe) = recipes.find(&self.crafting).filter(|_| shift) {
+                let mut after = inventory.clone();
+                if after.add(recipe.result()).is_some() {
+                    break;
+                }
+                *inventory = after;
+                consume_ingredients(&mut self.crafting);
+            }
+        }
+        self.result[0] = recipes.find(&self.crafting).map(Recipe::result);
+    }
+
+    /// Clears the crafting grid back into the inventory and lays `recipe` out from the top
+    /// left with ingredients taken from the inventory
+    fn fill_grid(&mut self, recipe: &Recipe, inventory: &mut Inventory) {
+        let mut after = inventory.clone();
+        if self.crafting.iter().flatten().any(|stack| after.add(*stack).is_some()) {
+            return;
+        }
+        let available: Vec<ItemStack> = after.stacks().copied().collect();
+        let Some(picked) = recipe.pick_ingredients(&available) else {
+            return;
+        };
+
+        let mut picked = picked.into_iter();
+        for (slot, ingredient) in self.crafting.iter_mut().zip(recipe.layout()) {
+            *slot = ingredient.and_then(|_| picked.next()).map(|item| {
+                after.remove(item, 1);
+                ItemStack::new(item, 1)
+            });
+        }
+        *inventory = after;
+    }
+
+    pub fn render(&mut self, window: &mut GlWindow, frame: &UiFrame, items: &ItemRenderer, inventory: &mut Inventory, recipes: &RecipeBook) {
+        let layout = self.layout(window.get_window_size(), inventory);
+
+        let text_scale = 2.0 * self.ui_scale.floor();
+        let label_offset = Vec2::new(0.0, -items.text.line_height(text_scale) - 2.0 * self.ui_scale);
+        items.text.render(window, frame, "Crafting", layout.crafting.origin + label_offset, text_scale);
+        items.text.render(window, frame, "Recipes", layout.recipe_book.origin + label_offset, text_scale);
+
+        let (mouse_x, mouse_y) = window.get_mouse_position();
+        let mouse = Vec2::new(mouse_x as f32, mouse_y as f32);
+        let craftable = self.craftable(inventory, recipes);
+        for (slot, recipe) in craftable.iter().enumerate() {
+            let centre = layout.recipe_book.slot_centre(slot);
+            items.render_slot(window, frame, centre, layout.recipe_book.slot_size);
+            items.render_stack(window, frame, &recipe.result(), centre, layout.recipe_book.slot_size);
+        }
+
+        let views = Self::views(&layout, inventory, &mut self.crafting, &mut self.result);
+        self.interaction.render(window, frame, items, &views);
+
+        let hovered_recipe = layout.recipe_book.slot_at(mouse).and_then(|slot| craftable.get(slot));
+        if let (Some(recipe), false) = (hovered_recipe, self.interaction.is_holding()) {
+            items.render_tooltip(window, frame, recipe.result().item.name(), mouse);
+        }
+    }
+
+    /// Puts the stack held by the cursor and the crafting grid back into the inventory.
+    /// Whatever doesn't fit stays where it was for the next time the screen opens.
+    pub fn close(&mut self, inventory: &mut Inventory) {
+        self.interaction.return_held(inventory);
+        for slot in self.crafting.iter_mut() {
+            if let Some(stack) = slot.take() {
+                *slot = inventory.add(stack);
+            }
+        }
+    }
+
+    /// Keeps a stack another screen couldn't return to the full inventory in the crafting
+    /// grid. There are no dropped items yet, so it is lost if the grid is full as well.
+    pub fn keep(&mut self, stack: ItemStack) {
+        if let Some(stack) = merge_into(&mut self.crafting, stack) {
+            fill_into(&mut self.crafting, stack);
+        }
+    }
+}
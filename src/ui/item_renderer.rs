@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+
+use ferrousgl::GlWindow;
+use glam::{Mat4, Vec2, Vec3};
+
+use crate::{
+    inventory::ItemStack,
+    item::{Item, ItemIcon, ItemRegistry},
+    voxel::BlockState,
+    ui::{UiFrame, text::TextRenderer, ui_quad::UiQuad},
+};
+
+// UI atlas regions, in the atlas' 1/28.4 units
+const UI_UNIT: f32 = 1.0 / 28.4;
+
+/// Draws inventory slots and the item stacks inside them. The quads are unit sized and
+/// shared, every draw places them with its own model matrix.
+pub struct ItemRenderer {
+    slot: UiQuad,
+    selector: UiQuad,
+    panel: UiQuad,
+    icons: HashMap<Item, (UiQuad, ItemIcon)>,
+    pub text: TextRenderer,
+}
+
+impl ItemRenderer {
+    pub fn new() -> Self {
+        let atlas_quad = |min: Vec2, max: Vec2| {
+            let mut quad = UiQuad::new();
+            quad.set_uvs([
+                Vec2::new(min.x, min.y),
+                Vec2::new(max.x, min.y),
+                Vec2::new(max.x, max.y),
+                Vec2::new(min.x, max.y),
+            ]);
+            quad
+        };
+
+        let slot = atlas_quad(Vec2::new(0.0, UI_UNIT), Vec2::new(UI_UNIT * 2.0, UI_UNIT * 3.0));
+        let selector = atlas_quad(Vec2::new(UI_UNIT * 2.0, UI_UNIT), Vec2::new(UI_UNIT * 4.0, UI_UNIT * 3.0));
+        let panel = atlas_quad(Vec2::new(0.0, UI_UNIT * 3.0), Vec2::new(UI_UNIT * 4.0, UI_UNIT * 4.0));
+
+        let icons = ItemRegistry::get()
+            .items()
+            .iter()
+            .map(|&item| {
+                let icon = item.definition().icon;
+                let quad = match icon {
+                    ItemIcon::Block(block) => {
+                        let [u, v, w, h] = block.get_face_texture(0, BlockState::default());
+                        atlas_quad(Vec2::new(u, v), Vec2::new(u + w, v + h))
+                    }
+                    ItemIcon::Atlas(column, row) => {
+                        let min = Vec2::new(column as f32, row as f32) / 16.0;
+                        atlas_quad(min, min + 1.0 / 16.0)
+                    }
+                };
+                (item, (quad, icon))
+            })
+            .collect();
+
+        Self {
+            slot,
+            selector,
+            panel,
+            icons,
+            text: TextRenderer::new(),
+        }
+    }
+
+    fn place(centre: Vec2, size: f32) -> Mat4 {
+        Mat4::from_translation(Vec3::new(centre.x, centre.y, 0.0)) * Mat4::from_scale(Vec3::new(size, size, 1.0))
+    }
+
+    pub fn render_slot(&self, window: &mut GlWindow, frame: &UiFrame, centre: Vec2, size: f32) {
+        self.slot.render_with_texture(window, frame.shader, Self::place(centre, size), frame.projection, Some(frame.ui_atlas));
+    }
+
+    pub fn render_selector(&self, window: &mut GlWindow, frame: &UiFrame, centre: Vec2, size: f32) {
+        self.selector.render_with_texture(window, frame.shader, Self::place(centre, size), frame.projection, Some(frame.ui_atlas));
+    }
+
+    /// Draws the icon of `stack` in a slot of `size` pixels, with its count in the bottom
+    /// right corner unless it is a single item
+    pub fn render_stack(&self, window: &mut GlWindow, frame: &UiFrame, stack: &ItemStack, centre: Vec2, size: f32) {
+        let (quad, icon) = &self.icons[&stack.item];
+        let atlas = match icon {
+            ItemIcon::Block(_) => frame.block_atlas,
+            ItemIcon::Atlas(..) => frame.item_atlas,
+        };
+        quad.render_with_texture(window, frame.shader, Self::place(centre, size * 25.0 / 30.0), frame.projection, Some(atlas));
+
+        if stack.count > 1 {
+            let scale = (size / 20.0).floor().max(1.0);
+            let count = stack.count.to_string();
+            let corner = centre + Vec2::splat(size / 2.0 - scale * 2.0);
+            let position = corner - Vec2::new(self.text.text_width(&count, scale), self.text.line_height(scale));
+            self.text.render(window, frame, &count, position, scale);
+        }
+    }
+
+    /// Draws `text` on a panel just above and to the right of `cursor`
+    pub fn render_tooltip(&self, window: &mut GlWindow, frame: &UiFrame, text: &str, cursor: Vec2) {
+        let scale = 2.0;
+        let padding = Vec2::new(6.0, 4.0);
+        let size = Vec2::new(self.text.text_width(text, scale), self.text.line_height(scale)) + padding * 2.0;
+        let top_left = cursor + Vec2::new(12.0, -size.y - 4.0);
+
+        let centre = top_left + size / 2.0;
+        let model = Mat4::from_translation(Vec3::new(centre.x, centre.y, 0.0)) * Mat4::from_scale(Vec3::new(size.x, size.y, 1.0));
+        self.panel.render_with_texture(window, frame.shader, model, frame.projection, Some(frame.ui_atlas));
+        self.text.render(window, frame, text, top_left + padding, scale);
+    }
+}
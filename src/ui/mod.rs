@@ -1 +1,24 @@
-pub mod ui_quad;
\ No newline at end of file
+use ferrousgl::{Shader, Texture};
+use glam::Mat4;
+
+pub mod ui_quad;
+pub mod text;
+pub mod item_renderer;
+pub mod slot_grid;
+pub mod hotbar;
+pub mod slot_interaction;
+pub mod inventory_screen;
+pub mod creative_screen;
+pub mod progress_icon;
+pub mod furnace_screen;
+pub mod chest_screen;
+pub mod screen;
+
+/// The shader, projection and atlases shared by everything drawn on the UI layer in a frame
+pub struct UiFrame<'a> {
+    pub shader: &'a Shader,
+    pub projection: Mat4,
+    pub ui_atlas: &'a Texture,
+    pub block_atlas: &'a Texture,
+    pub item_atlas: &'a Texture,
+}
@@ -0,0 +1,84 @@
+use ferrousgl::GlWindow;
+use glam::{Mat4, Vec2, Vec3};
+
+use crate::ui::{UiFrame, ui_quad::UiQuad};
+
+const UI_ATLAS_SIZE: f32 = 256.0;
+
+/// Which way an icon fills up as its progress grows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillDirection {
+    Right,
+    Up,
+}
+
+/// An icon from the UI atlas drawn filled in part over its empty version, like the arrow
+/// and flame showing a furnace's smelting and fuel
+pub struct ProgressIcon {
+    empty: UiQuad,
+    full: UiQuad,
+    full_origin: Vec2,
+    size: Vec2,
+    direction: FillDirection,
+}
+
+// Sets `quad` to show the atlas region starting at `origin` pixels, `size` pixels large
+fn set_region(quad: &mut UiQuad, origin: Vec2, size: Vec2) {
+    let min = origin / UI_ATLAS_SIZE;
+    let max = (origin + size) / UI_ATLAS_SIZE;
+    quad.set_uvs([
+        Vec2::new(min.x, min.y),
+        Vec2::new(max.x, min.y),
+        Vec2::new(max.x, max.y),
+        Vec2::new(min.x, max.y),
+    ]);
+}
+
+impl ProgressIcon {
+    /// `empty_origin` and `full_origin` are the top left pixels of the two versions of the
+    /// icon in the UI atlas, both `size` pixels large
+    pub fn new(empty_origin: Vec2, full_origin: Vec2, size: Vec2, direction: FillDirection) -> Self {
+        let mut empty = UiQuad::new();
+        set_region(&mut empty, empty_origin, size);
+        Self {
+            empty,
+            full: UiQuad::new(),
+            full_origin,
+            size,
+            direction,
+        }
+    }
+
+    /// Draws the icon centred on `centre` with every atlas pixel `scale` pixels large,
+    /// filled in by `fraction` from 0 to 1
+    pub fn render(&mut self, window: &mut GlWindow, frame: &UiFrame, centre: Vec2, scale: f32, fraction: f32) {
+        let place = |centre: Vec2, size: Vec2| {
+            Mat4::from_translation(Vec3::new(centre.x, centre.y, 0.0)) * Mat4::from_scale(Vec3::new(size.x, size.y, 1.0))
+        };
+        let size = self.size * scale;
+        self.empty.render_with_texture(window, frame.shader, place(centre, size), frame.projection, Some(frame.ui_atlas));
+
+        let fraction = fraction.clamp(0.0, 1.0);
+        if fraction <= 0.0 {
+            return;
+        }
+        let (origin, filled) = match self.direction {
+            FillDirection::Right => (self.full_origin, Vec2::new(self.size.x * fraction, self.size.y)),
+            FillDirection::Up => {
+                let height = self.size.y * fraction;
+                (self.full_origin + Vec2::new(0.0, self.size.y - height), Vec2::new(self.size.x, height))
+            }
+        };
+        set_region(&mut self.full, origin, filled);
+
+        // The filled part shares the icon's left edge, or its bottom edge when filling up
+        let top_left = centre - size / 2.0 + (origin - self.full_origin) * scale;
+        self.full.render_with_texture(
+            window,
+            frame.shader,
+            place(top_left + filled * scale / 2.0, filled * scale),
+            frame.projection,
+            Some(frame.ui_atlas),
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use ferrousgl::{GlWindow, WindowKey};
+
+use crate::{
+    block_entity::BlockEntity,
+    crafting::RecipeBook,
+    inventory::{Inventory, ItemStack},
+    ui::{
+        UiFrame, chest_screen::ChestScreen, creative_screen::CreativeScreen, furnace_screen::FurnaceScreen, hotbar::Hotbar, inventory_screen::InventoryScreen,
+        item_renderer::ItemRenderer,
+    },
+    world::World,
+};
+
+/// A menu drawn over the world. While one is open the cursor is free and the player
+/// ignores keyboard and mouse input.
+pub enum Screen {
+    Inventory(InventoryScreen),
+    Creative(CreativeScreen),
+    Furnace(FurnaceScreen),
+    Chest(ChestScreen),
+}
+
+impl Screen {
+    /// The screen for the block entity at the world position, if there is one
+    pub fn for_block_entity(world: &World, (x, y, z): (i32, i32, i32)) -> Option<Screen> {
+        world.with_block_entity(x, y, z, |entity| match entity {
+            BlockEntity::Furnace(_) => Screen::Furnace(FurnaceScreen::new((x, y, z))),
+            BlockEntity::Chest(_) => Screen::Chest(ChestScreen::new((x, y, z))),
+        })
+    }
+
+    pub fn update(&mut self, window: &GlWindow, inventory: &mut Inventory, hotbar: &Hotbar, recipes: &RecipeBook, world: &World) {
+        match self {
+            Screen::Inventory(screen) => screen.update(window, inventory, recipes),
+            Screen::Creative(screen) => screen.update(window, inventory, hotbar),
+            Screen::Furnace(screen) => screen.update(window, inventory, world),
+            Screen::Chest(screen) => screen.update(window, inventory, world),
+        }
+    }
+
+    pub fn render(
+        &mut self,
+        window: &mut GlWindow,
+        frame: &UiFrame,
+        items: &ItemRenderer,
+        inventory: &mut Inventory,
+        recipes: &RecipeBook,
+        world: &World,
+    ) {
+        match self {
+            Screen::Inventory(screen) => screen.render(window, frame, items, inventory, recipes),
+            Screen::Creative(screen) => screen.render(window, frame, items),
+            Screen::Furnace(screen) => screen.render(window, frame, items, inventory, world),
+            Screen::Chest(screen) => screen.render(window, frame, items, inventory, world),
+        }
+    }
+
+    /// Escape closes any screen, E also closes screens that don't take text input
+    pub fn wants_close(&self, window: &GlWindow) -> bool {
+        window.is_key_pressed(WindowKey::Escape)
+            || (!matches!(self, Screen::Creative(_)) && window.is_key_pressed(WindowKey::E))
+    }
+
+    /// Returns the screen's stacks to the inventory. The inventory screen keeps what
+    /// doesn't fit itself, other screens hand back a held stack there was no room for.
+    pub fn close(&mut self, inventory: &mut Inventory) -> Option<ItemStack> {
+        match self {
+            Screen::Inventory(screen) => {
+                screen.close(inventory);
+                None
+            }
+            Screen::Creative(_) => None,
+            Screen::Furnace(screen) => screen.close(inventory),
+            Screen::Chest(screen) => screen.close(inventory),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use glam::Vec2;
+
+/// Layout of a rectangular grid of equally sized slots, filled row by row
+#[derive(Debug, Clone, Copy)]
+pub struct SlotGrid {
+    pub origin: Vec2, // Top left corner of the first slot
+    pub columns: usize,
+    pub len: usize,
+    pub slot_size: f32,
+}
+
+impl SlotGrid {
+    pub fn new(origin: Vec2, columns: usize, len: usize, slot_size: f32) -> Self {
+        Self { origin, columns, len, slot_size }
+    }
+
+    /// A grid of `columns` by `rows` slots centred horizontally on `centre_x`
+    pub fn centred(centre_x: f32, top: f32, columns: usize, rows: usize, slot_size: f32) -> Self {
+        let origin = Vec2::new(centre_x - columns as f32 * slot_size / 2.0, top);
+        Self::new(origin, columns, columns * rows, slot_size)
+    }
+
+    pub fn width(&self) -> f32 {
+        self.columns as f32 * self.slot_size
+    }
+
+    /// Centre of slot `index`
+    pub fn slot_centre(&self, index: usize) -> Vec2 {
+        let cell = Vec2::new((index % self.columns) as f32, (index / self.columns) as f32);
+        self.origin + (cell + 0.5) * self.slot_size
+    }
+
+    /// The slot under `point`, if any
+    pub fn slot_at(&self, point: Vec2) -> Option<usize> {
+        let cell = (point - self.origin) / self.slot_size;
+        if cell.x < 0.0 || cell.y < 0.0 || cell.x >= self.columns as f32 {
+            return None;
+        }
+        let index = cell.y as usize * self.columns + cell.x as usize;
+        (index < self.len).then_some(index)
+    }
+}
@@ -0,0 +1,225 @@
+use ferrousgl::{GlWindow, WindowKey};
+use glam::Vec2;
+
+use crate::{
+    inventory::{Inventory, ItemStack, fill_into, merge_into},
+    ui::{UiFrame, item_renderer::ItemRenderer, slot_grid::SlotGrid},
+};
+
+/// A grid on screen together with the slots it shows
+pub struct SlotView<'a> {
+    pub grid: SlotGrid,
+    pub slots: &'a mut [Option<ItemStack>],
+    /// Views that shift-clicked stacks move to, tried in order
+    pub quick_move_to: &'a [usize],
+    /// Output slots like a crafting result: stacks can only be taken out, and only whole
+    pub take_only: bool,
+}
+
+/// Moving stacks around between slot views with the mouse. Left click picks up or puts
+/// down a whole stack, right click picks up half or puts down one, shift-click sends a
+/// stack to another view. Dragging a picked up stack and releasing it over another slot
+/// puts it down there.
+pub struct SlotInteraction {
+    held: Option<ItemStack>,
+    drag_from: Option<(usize, usize)>,
+}
+
+fn mouse_position(window: &GlWindow) -> Vec2 {
+    let (x, y) = window.get_mouse_position();
+    Vec2::new(x as f32, y as f32)
+}
+
+/// The view and slot index under `point`
+fn slot_under(views: &[SlotView], point: Vec2) -> Option<(usize, usize)> {
+    views
+        .iter()
+        .enumerate()
+        .find_map(|(v, view)| view.grid.slot_at(point).filter(|&i| i < view.slots.len()).map(|i| (v, i)))
+}
+
+/// Picks up the whole slot, puts the whole held stack down, merges or swaps the two
+fn left_click(slot: &mut Option<ItemStack>, held: &mut Option<ItemStack>) {
+    match (slot.as_mut(), held.as_mut()) {
+        (Some(target), Some(stack)) if target.can_stack_with(stack) => {
+            let moved = stack.count.min(target.max_size() - target.count);
+            target.count += moved;
+            stack.count -= moved;
+            if stack.count == 0 {
+                *held = None;
+            }
+        }
+        _ => std::mem::swap(slot, held),
+    }
+}
+
+/// Picks up half of the slot rounded up, or puts a single held item down
+fn right_click(slot: &mut Option<ItemStack>, held: &mut Option<ItemStack>) {
+    match (slot.as_mut(), held.as_mut()) {
+        (Some(target), None) => {
+            let taken = target.count.div_ceil(2);
+            *held = Some(ItemStack::new(target.item, taken));
+            target.count -= taken;
+            if target.count == 0 {
+                *slot = None;
+            }
+        }
+        (None, Some(stack)) => {
+            *slot = Some(ItemStack::new(stack.item, 1));
+            stack.count -= 1;
+            if stack.count == 0 {
+                *held = None;
+            }
+        }
+        (Some(target), Some(stack)) if target.can_stack_with(stack) => {
+            if !target.is_full() {
+                target.count += 1;
+                stack.count -= 1;
+                if stack.count == 0 {
+                    *held = None;
+                }
+            }
+        }
+        _ => std::mem::swap(slot, held),
+    }
+}
+
+/// Takes the whole output stack into the held one, if it all fits
+fn take_output(slot: &mut Option<ItemStack>, held: &mut Option<ItemStack>) -> bool {
+    match (slot.as_ref(), held.as_mut()) {
+        (Some(_), None) => {
+            *held = slot.take();
+            true
+        }
+        (Some(output), Some(stack)) if stack.can_stack_with(output) && stack.count + output.count <= stack.max_size() => {
+            stack.count += output.count;
+            *slot = None;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Moves the stack in slot `index` of `view` to the views it quick-moves to, topping up
+/// stacks before filling empty slots. Stacks from take-only views move entirely or not at
+/// all. Returns whether the whole stack moved.
+fn quick_move(views: &mut [SlotView], (view, index): (usize, usize)) -> bool {
+    let Some(stack) = views[view].slots[index].take() else {
+        return false;
+    };
+    let targets = views[view].quick_move_to;
+    let backup: Vec<Vec<Option<ItemStack>>> = targets.iter().map(|&target| views[target].slots.to_vec()).collect();
+    let mut left = Some(stack);
+    for &target in targets {
+        left = left.and_then(|stack| merge_into(views[target].slots, stack));
+    }
+    for &target in targets {
+        left = left.and_then(|stack| fill_into(views[target].slots, stack));
+    }
+    if left.is_some() && views[view].take_only {
+        for (&target, slots) in targets.iter().zip(backup) {
+            views[target].slots.copy_from_slice(&slots);
+        }
+        left = Some(stack);
+    }
+    let moved = left.is_none();
+    views[view].slots[index] = left;
+    moved
+}
+
+impl SlotInteraction {
+    pub fn new() -> Self {
+        Self {
+            held: None,
+            drag_from: None,
+        }
+    }
+
+    pub fn is_holding(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// Moves the stack held by the cursor into `inventory`, e.g. on closing, and keeps
+    /// holding whatever doesn't fit
+    pub fn return_held(&mut self, inventory: &mut Inventory) {
+        self.drag_from = None;
+        if let Some(held) = self.held.take() {
+            self.held = inventory.add(held);
+        }
+    }
+
+    /// Takes the stack held by the cursor, e.g. when the screen goes away with it
+    pub fn take_held(&mut self) -> Option<ItemStack> {
+        self.drag_from = None;
+        self.held.take()
+    }
+
+    /// Handles this frame's clicks. Returns the take-only view a stack was taken out of,
+    /// so the screen can e.g. use up the crafting ingredients.
+    pub fn update(&mut self, window: &GlWindow, views: &mut [SlotView]) -> Option<usize> {
+        let hovered = slot_under(views, mouse_position(window));
+        let shift = window.is_key_held(WindowKey::LeftShift) || window.is_key_held(WindowKey::RightShift);
+
+        if let Some((view, index)) = hovered.filter(|&(view, _)| views[view].take_only) {
+            let clicked = window.is_mouse_button_pressed(glfw::MouseButtonLeft) || window.is_mouse_button_pressed(glfw::MouseButtonRight);
+            let taken = clicked
+                && if shift {
+                    quick_move(views, (view, index))
+                } else {
+                    take_output(&mut views[view].slots[index], &mut self.held)
+                };
+            return taken.then_some(view);
+        }
+
+        if window.is_mouse_button_pressed(glfw::MouseButtonLeft) {
+            if let Some((view, index)) = hovered {
+                if shift && self.held.is_none() {
+                    quick_move(views, (view, index));
+                } else {
+                    let was_empty = self.held.is_none();
+                    left_click(&mut views[view].slots[index], &mut self.held);
+                    self.drag_from = (was_empty && self.held.is_some()).then_some((view, index));
+                }
+            }
+        } else if window.is_mouse_button_released(glfw::MouseButtonLeft) {
+            // Releasing a dragged stack over a different slot puts it down there
+            if let (Some(from), Some((view, index))) = (self.drag_from.take(), hovered) {
+                if from != (view, index) {
+                    left_click(&mut views[view].slots[index], &mut self.held);
+                }
+            }
+        } else if window.is_mouse_button_pressed(glfw::MouseButtonRight) {
+            if let Some((view, index)) = hovered {
+                right_click(&mut views[view].slots[index], &mut self.held);
+            }
+        }
+        None
+    }
+
+    /// Draws the views, the stack held by the cursor and a tooltip for the hovered stack
+    pub fn render(&self, window: &mut GlWindow, frame: &UiFrame, items: &ItemRenderer, views: &[SlotView]) {
+        for view in views {
+            for (i, slot) in view.slots.iter().enumerate() {
+                let centre = view.grid.slot_centre(i);
+                items.render_slot(window, frame, centre, view.grid.slot_size);
+                if let Some(stack) = slot {
+                    items.render_stack(window, frame, stack, centre, view.grid.slot_size);
+                }
+            }
+        }
+
+        let mouse = mouse_position(window);
+        let hovered = slot_under(views, mouse);
+        if let Some((view, index)) = hovered {
+            let grid = &views[view].grid;
+            items.render_selector(window, frame, grid.slot_centre(index), grid.slot_size);
+        }
+
+        if let Some(stack) = &self.held {
+            let size = views.first().map_or(45.0, |view| view.grid.slot_size);
+            items.render_stack(window, frame, stack, mouse, size);
+        } else if let Some(stack) = hovered.and_then(|(view, index)| views[view].slots[index].as_ref()) {
+            items.render_tooltip(window, frame, stack.item.name(), mouse);
+        }
+    }
+}
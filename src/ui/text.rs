@@ -0,0 +1,100 @@
+use ferrousgl::GlWindow;
+use glam::{Mat4, Vec2, Vec3};
+
+use crate::ui::{UiFrame, ui_quad::UiQuad};
+
+// The pixel font is baked into the UI atlas as 16 glyphs per row in 8px cells,
+// starting at ASCII 32, with a dark copy of the same layout right below for shadows
+const ATLAS_SIZE: f32 = 256.0;
+const FONT_ORIGIN: Vec2 = Vec2::new(0.0, 64.0);
+const SHADOW_OFFSET: f32 = 48.0;
+const CELL: f32 = 8.0;
+const FIRST_GLYPH: u8 = 32;
+
+/// Horizontal advance of every glyph in font pixels, starting at ASCII 32
+const ADVANCES: [u8; 95] = [
+    2, 3, 4, 6, 5, 6, 5, 2, 3, 4, 5, 5, 5, 5, 4, 6,
+    5, 4, 5, 5, 5, 4, 4, 4, 4, 4, 3, 3, 4, 5, 5, 4,
+    6, 4, 4, 4, 4, 3, 3, 4, 4, 2, 4, 4, 3, 6, 5, 5,
+    4, 6, 4, 5, 4, 5, 4, 6, 4, 4, 4, 3, 6, 3, 4, 4,
+    4, 4, 4, 4, 4, 3, 3, 4, 4, 2, 4, 4, 3, 6, 5, 5,
+    4, 6, 4, 5, 4, 5, 4, 6, 4, 4, 4, 4, 3, 4, 5,
+];
+
+/// Draws single-line text with the pixel font from the UI atlas
+pub struct TextRenderer {
+    glyphs: Vec<UiQuad>,
+    shadows: Vec<UiQuad>,
+}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        let glyph_quad = |index: usize, shadow: bool| {
+            let min = FONT_ORIGIN
+                + Vec2::new((index % 16) as f32, (index / 16) as f32) * CELL
+                + Vec2::new(0.0, if shadow { SHADOW_OFFSET } else { 0.0 });
+            let max = min + Vec2::splat(CELL);
+            let (min, max) = (min / ATLAS_SIZE, max / ATLAS_SIZE);
+
+            // Anchor the quad at its top left corner so glyphs can be placed by translation
+            let mut quad = UiQuad::new();
+            quad.set_size(Vec2::splat(CELL));
+            quad.set_position(Vec2::splat(CELL / 2.0));
+            quad.set_uvs([
+                Vec2::new(min.x, min.y),
+                Vec2::new(max.x, min.y),
+                Vec2::new(max.x, max.y),
+                Vec2::new(min.x, max.y),
+            ]);
+            quad
+        };
+
+        Self {
+            glyphs: (0..ADVANCES.len()).map(|i| glyph_quad(i, false)).collect(),
+            shadows: (0..ADVANCES.len()).map(|i| glyph_quad(i, true)).collect(),
+        }
+    }
+
+    /// The font only has capitals, anything else outside printable ASCII draws as '?'
+    fn glyph_index(c: char) -> usize {
+        let c = c.to_ascii_uppercase();
+        if (' '..='~').contains(&c) {
+            (c as u8 - FIRST_GLYPH) as usize
+        } else {
+            (b'?' - FIRST_GLYPH) as usize
+        }
+    }
+
+    /// Width of `text` in screen pixels when drawn at `scale`
+    pub fn text_width(&self, text: &str, scale: f32) -> f32 {
+        text.chars().map(|c| ADVANCES[Self::glyph_index(c)] as f32).sum::<f32>() * scale
+    }
+
+    /// Height of a line of text in screen pixels when drawn at `scale`
+    pub fn line_height(&self, scale: f32) -> f32 {
+        CELL * scale
+    }
+
+    /// Draws `text` with its top left corner at `position`, with a drop shadow
+    pub fn render(
+        &self,
+        window: &mut GlWindow,
+        frame: &UiFrame,
+        text: &str,
+        position: Vec2,
+        scale: f32,
+    ) {
+        for (quads, offset) in [(&self.shadows, Vec2::splat(scale)), (&self.glyphs, Vec2::ZERO)] {
+            let mut x = position.x + offset.x;
+            for c in text.chars() {
+                let index = Self::glyph_index(c);
+                if c != ' ' {
+                    let model = Mat4::from_translation(Vec3::new(x, position.y + offset.y, 0.0))
+                        * Mat4::from_scale(Vec3::new(scale, scale, 1.0));
+                    quads[index].render_with_texture(window, frame.shader, model, frame.projection, Some(frame.ui_atlas));
+                }
+                x += ADVANCES[index] as f32 * scale;
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::voxel::VoxelType;
+use crate::block_model::{BlockModel, ModelBox};
 
 // Robust AABB collision with voxels for a given hitbox size
 pub fn collides_aabb(
@@ -18,14 +18,24 @@ pub fn collides_aabb(
         pos.y + height,
         pos.z + depth / 2.0,
     );
-    for x in min.x.floor() as i32..max.x.ceil() as i32 {
-        for y in min.y.floor() as i32..max.y.ceil() as i32 {
-            for z in min.z.floor() as i32..max.z.ceil() as i32 {
-                if !world.get_voxel(x, y, z).is_not_solid() {
-                    return true;
+    let hitbox = ModelBox { min, max };
+    world.with_blocks(|block| {
+        // Start a block lower since fences reach up into the cell above them
+        for x in min.x.floor() as i32..max.x.ceil() as i32 {
+            for y in min.y.floor() as i32 - 1..max.y.ceil() as i32 {
+                for z in min.z.floor() as i32..max.z.ceil() as i32 {
+                    let (voxel, state) = block(x, y, z);
+                    if voxel.is_not_solid() {
+                        continue;
+                    }
+                    let cell = glam::vec3(x as f32, y as f32, z as f32);
+                    let boxes = BlockModel::collision_boxes(voxel, state, |dx, dz| block(x + dx, y, z + dz).0);
+                    if boxes.iter().any(|model_box| model_box.translated(cell).intersects(&hitbox)) {
+                        return true;
+                    }
                 }
             }
         }
-    }
-    false
-}
\ No newline at end of file
+        false
+    })
+}
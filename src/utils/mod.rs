@@ -2,4 +2,5 @@ pub mod collides_aabb;
 pub mod interpolate;
 pub mod cube_face;
 pub mod tree_gen;
+pub mod voxel_set;
 pub mod string_to_texture;
\ No newline at end of file
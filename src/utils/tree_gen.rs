@@ -1,38 +1,366 @@
 use rand::Rng;
-use crate::{utils::interpolate::lerp, voxel::VoxelType};
-use std::f64::consts::PI;
+use crate::{
+    utils::{
+        interpolate::lerp,
+        voxel_set::{CompactVoxels, VoxelSet},
+    },
+    voxel::VoxelType,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
-/// Generic tree generator for different wood/leaf types
-fn generate_tree_generic(
+/// Tree species that biomes can refer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
+pub enum TreeSpecies {
+    Walnut,
+    Spruce,
+    Mahogany,
+    Birch,
+    Palm,
+    Cactus,
+    Shrub,
+    FallenLog,
+    DeadTree,
+}
+
+impl TreeSpecies {
+    /// Built-in look of the species, used when a preset doesn't define it
+    pub fn default_definition(&self) -> TreeDefinition {
+        let (wood, leaves, shape) = match self {
+            TreeSpecies::Walnut => (
+                VoxelType::WalnutWood,
+                Some(VoxelType::WalnutLeaves),
+                TreeShape::Round {
+                    trunk_height: [3, 6],
+                    trunk_thickness: [2, 2],
+                    branch_length: [2, 4],
+                    leaf_radius: [1, 2],
+                    crown_radius: [2, 3],
+                },
+            ),
+            TreeSpecies::Spruce => (
+                VoxelType::SpruceWood,
+                Some(VoxelType::SpruceLeaves),
+                TreeShape::Conical {
+                    trunk_height: [14, 21],
+                    crown_base: [3, 5],
+                    base_radius: [3, 5],
+                },
+            ),
+            TreeSpecies::Mahogany => (
+                VoxelType::MahoganyWood,
+                Some(VoxelType::MahoganyLeaves),
+                TreeShape::Round {
+                    trunk_height: [18, 31],
+                    trunk_thickness: [2, 3],
+                    branch_length: [7, 11],
+                    leaf_radius: [2, 3],
+                    crown_radius: [3, 4],
+                },
+            ),
+            TreeSpecies::Birch => (
+                VoxelType::BirchWood,
+                Some(VoxelType::BirchLeaves),
+                TreeShape::Round {
+                    trunk_height: [6, 9],
+                    trunk_thickness: [1, 1],
+                    branch_length: [1, 2],
+                    leaf_radius: [1, 2],
+                    crown_radius: [2, 3],
+                },
+            ),
+            TreeSpecies::Palm => (
+                VoxelType::WalnutWood,
+                Some(VoxelType::MahoganyLeaves),
+                TreeShape::Palm {
+                    trunk_height: [6, 10],
+                    lean: [1, 4],
+                    fronds: [5, 7],
+                    frond_length: [3, 5],
+                },
+            ),
+            TreeSpecies::Cactus => (
+                VoxelType::Cactus,
+                None,
+                TreeShape::Column {
+                    height: [2, 4],
+                    arms: [0, 2],
+                },
+            ),
+            TreeSpecies::Shrub => (
+                VoxelType::WalnutWood,
+                Some(VoxelType::WalnutLeaves),
+                TreeShape::Bush {
+                    radius: [1, 2],
+                    height: [1, 2],
+                },
+            ),
+            TreeSpecies::FallenLog => (
+                VoxelType::WalnutWood,
+                None,
+                TreeShape::FallenLog { length: [3, 6] },
+            ),
+            TreeSpecies::DeadTree => (
+                VoxelType::SpruceWood,
+                None,
+                TreeShape::LSystem {
+                    axiom: "FFA".to_string(),
+                    rules: vec![
+                        LSystemRule::new('A', "F[&FB]++[&FB]++[&FB]"),
+                        LSystemRule::new('A', "F[&FB]+++[&FB]"),
+                        LSystemRule::new('B', "F[-FB][+F]"),
+                        LSystemRule::new('B', "F[^F]"),
+                    ],
+                    iterations: 3,
+                    angle: 40.0,
+                    segment_length: [1, 3],
+                    leaf_radius: [0, 0],
+                },
+            ),
+        };
+        TreeDefinition {
+            species: *self,
+            wood,
+            leaves,
+            shape,
+        }
+    }
+}
+
+/// Rewrites every `symbol` in an L-system string with `replacement`.
+/// With several rules for the same symbol one of them is picked at random.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LSystemRule {
+    pub symbol: char,
+    pub replacement: String,
+}
+
+impl LSystemRule {
+    pub fn new(symbol: char, replacement: &str) -> Self {
+        Self {
+            symbol,
+            replacement: replacement.to_string(),
+        }
+    }
+}
+
+/// How a tree is built. All ranges are inclusive [min, max] and rolled per tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape")]
+pub enum TreeShape {
+    /// Trunk with random side branches ending in leaf clusters, topped by a round crown
+    Round {
+        trunk_height: [i32; 2],
+        trunk_thickness: [i32; 2],
+        branch_length: [i32; 2],
+        leaf_radius: [i32; 2],
+        crown_radius: [i32; 2],
+    },
+    /// Straight trunk with rings of leaves that shrink towards the top
+    Conical {
+        trunk_height: [i32; 2],
+        /// Height of the lowest ring above the ground
+        crown_base: [i32; 2],
+        base_radius: [i32; 2],
+    },
+    /// Curved, leaning trunk with fronds drooping from the top
+    Palm {
+        trunk_height: [i32; 2],
+        /// Horizontal distance between the base and the top of the trunk
+        lean: [i32; 2],
+        fronds: [i32; 2],
+        frond_length: [i32; 2],
+    },
+    /// Low clump of leaves around a single wood block
+    Bush { radius: [i32; 2], height: [i32; 2] },
+    /// Trunk lying on the ground along x or z
+    FallenLog { length: [i32; 2] },
+    /// Upright column with short arms bending upwards, like a cactus
+    Column { height: [i32; 2], arms: [i32; 2] },
+    /// Branches drawn by a turtle that follows an L-system string.
+    /// `F` draws wood, `L` places leaves, `+`/`-` turn, `&`/`^` tilt down and up,
+    /// and `[`/`]` save and restore the turtle. Other symbols are only rewritten.
+    LSystem {
+        axiom: String,
+        rules: Vec<LSystemRule>,
+        iterations: u32,
+        /// Turn angle in degrees, jittered by up to a quarter per turn
+        angle: f64,
+        segment_length: [i32; 2],
+        leaf_radius: [i32; 2],
+    },
+}
+
+/// Blocks and shape of one species, loaded from the world gen settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeDefinition {
+    pub species: TreeSpecies,
+    pub wood: VoxelType,
+    /// None for trees without foliage
+    pub leaves: Option<VoxelType>,
+    #[serde(flatten)]
+    pub shape: TreeShape,
+}
+
+impl TreeDefinition {
+    /// The built-in definition of every species
+    pub fn defaults() -> Vec<TreeDefinition> {
+        TreeSpecies::iter().map(|species| species.default_definition()).collect()
+    }
+
+    /// Voxels of one tree growing from `base_pos`, the air block above the ground
+    pub fn generate(&self, base_pos: (i32, i32, i32), rng: &mut impl Rng) -> CompactVoxels {
+        let mut voxels = VoxelSet::new();
+        match &self.shape {
+            TreeShape::Round {
+                trunk_height,
+                trunk_thickness,
+                branch_length,
+                leaf_radius,
+                crown_radius,
+            } => {
+                let height = roll(rng, *trunk_height);
+                let thickness = roll(rng, *trunk_thickness);
+                generate_round(
+                    &mut voxels,
+                    base_pos,
+                    rng,
+                    self.wood,
+                    self.leaves,
+                    height,
+                    thickness,
+                    *branch_length,
+                    *leaf_radius,
+                    *crown_radius,
+                );
+            }
+            TreeShape::Conical {
+                trunk_height,
+                crown_base,
+                base_radius,
+            } => {
+                let height = roll(rng, *trunk_height);
+                let crown_base = roll(rng, *crown_base);
+                let radius = roll(rng, *base_radius);
+                generate_conical(&mut voxels, base_pos, self.wood, self.leaves, height, crown_base, radius);
+            }
+            TreeShape::Palm {
+                trunk_height,
+                lean,
+                fronds,
+                frond_length,
+            } => {
+                let height = roll(rng, *trunk_height);
+                let lean = roll(rng, *lean);
+                let fronds = roll(rng, *fronds);
+                generate_palm(
+                    &mut voxels,
+                    base_pos,
+                    rng,
+                    self.wood,
+                    self.leaves,
+                    height,
+                    lean,
+                    fronds,
+                    *frond_length,
+                );
+            }
+            TreeShape::Bush { radius, height } => {
+                let radius = roll(rng, *radius);
+                let height = roll(rng, *height);
+                generate_bush(&mut voxels, base_pos, rng, self.wood, self.leaves, radius, height);
+            }
+            TreeShape::FallenLog { length } => {
+                let length = roll(rng, *length);
+                generate_fallen_log(&mut voxels, base_pos, rng, self.wood, length);
+            }
+            TreeShape::Column { height, arms } => {
+                let height = roll(rng, *height);
+                let arms = roll(rng, *arms);
+                generate_column(&mut voxels, base_pos, rng, self.wood, height, arms);
+            }
+            TreeShape::LSystem {
+                axiom,
+                rules,
+                iterations,
+                angle,
+                segment_length,
+                leaf_radius,
+            } => {
+                let commands = expand_lsystem(axiom, rules, *iterations, rng);
+                let turtle = Turtle {
+                    wood: self.wood,
+                    leaves: self.leaves,
+                    angle: angle.to_radians(),
+                    segment_length: *segment_length,
+                    leaf_radius: *leaf_radius,
+                };
+                turtle.draw(&mut voxels, base_pos, &commands, rng);
+            }
+        }
+        voxels.into_compact()
+    }
+}
+
+/// Tree definitions by species, built-in ones overridden by those of the preset
+pub struct TreeRegistry {
+    definitions: HashMap<TreeSpecies, TreeDefinition>,
+}
+
+impl TreeRegistry {
+    pub fn new(definitions: &[TreeDefinition]) -> Self {
+        let mut registry: HashMap<_, _> = TreeDefinition::defaults()
+            .into_iter()
+            .map(|definition| (definition.species, definition))
+            .collect();
+        for definition in definitions {
+            registry.insert(definition.species, definition.clone());
+        }
+        Self {
+            definitions: registry,
+        }
+    }
+
+    pub fn get(&self, species: TreeSpecies) -> &TreeDefinition {
+        &self.definitions[&species]
+    }
+}
+
+fn roll(rng: &mut impl Rng, [min, max]: [i32; 2]) -> i32 {
+    rng.random_range(min..=max.max(min))
+}
+
+/// Trunk with random branches and a round crown
+fn generate_round(
+    voxels: &mut VoxelSet,
     base_pos: (i32, i32, i32),
+    rng: &mut impl Rng,
     wood: VoxelType,
-    leaves: VoxelType,
-    trunk_height_range: std::ops::Range<i32>,
-    branch_length_range: std::ops::Range<i32>,
-    leaf_radius_range: std::ops::Range<i32>,
-    crown_radius_range: std::ops::Range<i32>,
-    trunk_thickness: i32, // <-- new parameter
-) -> Vec<(i32, i32, i32, VoxelType)> {
+    leaves: Option<VoxelType>,
+    trunk_height: i32,
+    trunk_thickness: i32,
+    branch_length_range: [i32; 2],
+    leaf_radius_range: [i32; 2],
+    crown_radius_range: [i32; 2],
+) {
     let (x, y, z) = base_pos;
-    let mut voxels = Vec::new();
-    let mut rng = rand::rng();
-
-    // Generate trunk
-    let trunk_height = rng.random_range(trunk_height_range.clone());
     let trunk_top = y + trunk_height;
 
     // Create trunk (thickness x thickness blocks)
     for dx in 0..trunk_thickness {
         for dz in 0..trunk_thickness {
             for dy in 0..trunk_height {
-                voxels.push((x + dx, y + dy, z + dz, wood));
+                voxels.insert((x + dx, y + dy, z + dz), wood);
 
                 // Random chance to spawn a branch
                 if dy > 5 && dy < trunk_height - 5 && rng.random_bool(0.2) {
                     let start_point = (x + dx, y + dy, z + dz);
 
                     // Branch parameters
-                    let max_length = rng.random_range(branch_length_range.clone());
+                    let max_length = roll(rng, branch_length_range).max(1);
                     let y_offset = rng.random_range(1..4);
                     let x_offset = rng.random_range(-max_length..max_length);
                     let z_offset = rng.random_range(-max_length..max_length);
@@ -43,96 +371,335 @@ fn generate_tree_generic(
                         start_point.2 + z_offset,
                     );
 
-                    // Draw branch
-                    let steps = max_length * 2;
-                    for i in 0..=steps {
-                        let t = i as f32 / steps as f32;
-                        let bx = lerp(start_point.0 as f32, end_point.0 as f32, t).round() as i32;
-                        let by = lerp(start_point.1 as f32, end_point.1 as f32, t).round() as i32;
-                        let bz = lerp(start_point.2 as f32, end_point.2 as f32, t).round() as i32;
-                        voxels.push((bx, by, bz, wood));
-                    }
+                    draw_line(voxels, start_point, end_point, wood);
 
                     // Add leaves at branch end
-                    generate_leaves_custom(&mut voxels, end_point, leaves, leaf_radius_range.clone());
+                    if let Some(leaves) = leaves {
+                        let radius = roll(rng, leaf_radius_range);
+                        generate_leaves_custom(voxels, end_point, leaves, radius);
+                    }
                 }
             }
         }
     }
 
     // Add leaves at the top of the tree
-    generate_leaves_crown_custom(&mut voxels, (x, trunk_top, z), leaves, crown_radius_range);
-
-    voxels
-}
-
-/// Spruce tree generator
-pub fn generate_spruce_tree(base_pos: (i32, i32, i32)) -> Vec<(i32, i32, i32, VoxelType)> {
-    generate_tree_generic(
-        base_pos,
-        VoxelType::SpruceWood,
-        VoxelType::SpruceLeaves,
-        14..22,      // Spruce trunk height
-        3..6,        // Spruce branch length
-        1..3,        // Spruce leaf cluster radius
-        2..4,        // Spruce crown radius
-        1,           // Spruce trunk thickness
-    )
-}
-
-/// Mahogany tree generator
-pub fn generate_mahogany_tree(base_pos: (i32, i32, i32)) -> Vec<(i32, i32, i32, VoxelType)> {
-    let mut rng = rand::rng();
-    let thickness = rng.random_range(2..3); // 2 or 3
-    generate_tree_generic(
-        base_pos,
-        VoxelType::MahoganyWood,
-        VoxelType::MahoganyLeaves,
-        18..32,      // Mahogany trunk height
-        7..12,        // Mahogany branch length
-        2..4,        // Mahogany leaf cluster radius
-        3..5,        // Mahogany crown radius
-        thickness,   // Mahogany trunk thickness (2 or 3)
-    )
-}
-
-/// Walnut tree generator
-pub fn generate_walnut_tree(base_pos: (i32, i32, i32)) -> Vec<(i32, i32, i32, VoxelType)> {
-    generate_tree_generic(
-        base_pos,
-        VoxelType::WalnutWood,
-        VoxelType::WalnutLeaves,
-        3..7,     // Walnut trunk height
-        2..5,       // Walnut branch length
-        1..3,       // Walnut leaf cluster radius
-        2..4,       // Walnut crown radius
-        2,          // Walnut trunk thickness
-    )
+    if let Some(leaves) = leaves {
+        let radius = roll(rng, crown_radius_range);
+        generate_leaves_crown_custom(voxels, (x, trunk_top, z), leaves, radius);
+    }
+}
+
+/// Straight trunk with leaf rings, alternating wide and narrow so the cone looks layered
+fn generate_conical(
+    voxels: &mut VoxelSet,
+    base_pos: (i32, i32, i32),
+    wood: VoxelType,
+    leaves: Option<VoxelType>,
+    trunk_height: i32,
+    crown_base: i32,
+    base_radius: i32,
+) {
+    let (x, y, z) = base_pos;
+    for dy in 0..trunk_height {
+        voxels.insert((x, y + dy, z), wood);
+    }
+    let Some(leaves) = leaves else {
+        return;
+    };
+
+    let crown_base = crown_base.min(trunk_height - 1);
+    let crown_height = trunk_height - crown_base;
+    for dy in crown_base..trunk_height {
+        let t = (dy - crown_base) as f32 / crown_height as f32;
+        let mut radius = lerp(base_radius as f32, 0.0, t).round() as i32;
+        if (trunk_height - dy) % 2 == 0 {
+            radius -= 1;
+        }
+        if radius <= 0 {
+            continue;
+        }
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                if (dx != 0 || dz != 0) && dx * dx + dz * dz <= radius * radius + radius / 2 {
+                    voxels.insert((x + dx, y + dy, z + dz), leaves);
+                }
+            }
+        }
+    }
+
+    // Pointed tip above the trunk
+    voxels.insert((x, y + trunk_height, z), leaves);
+    voxels.insert((x, y + trunk_height + 1, z), leaves);
+}
+
+/// Trunk bending away from its base, with drooping fronds fanned around the top
+fn generate_palm(
+    voxels: &mut VoxelSet,
+    base_pos: (i32, i32, i32),
+    rng: &mut impl Rng,
+    wood: VoxelType,
+    leaves: Option<VoxelType>,
+    trunk_height: i32,
+    lean: i32,
+    fronds: i32,
+    frond_length_range: [i32; 2],
+) {
+    let (x, y, z) = base_pos;
+    let direction = rng.random_range(0.0..TAU);
+    let (lean_x, lean_z) = (direction.cos() * lean as f64, direction.sin() * lean as f64);
+
+    // The lean grows quadratically, so the trunk starts upright and curves over
+    let mut top = base_pos;
+    for dy in 0..trunk_height {
+        let t = dy as f64 / (trunk_height - 1).max(1) as f64;
+        let pos = (
+            x + (lean_x * t * t).round() as i32,
+            y + dy,
+            z + (lean_z * t * t).round() as i32,
+        );
+        // Keep diagonal steps connected
+        if dy > 0 && (pos.0 != top.0 || pos.2 != top.2) {
+            voxels.insert((pos.0, top.1, pos.2), wood);
+        }
+        voxels.insert((pos.0, pos.1, pos.2), wood);
+        top = pos;
+    }
+
+    let Some(leaves) = leaves else {
+        return;
+    };
+    voxels.insert((top.0, top.1 + 1, top.2), leaves);
+    for i in 0..fronds {
+        let angle = i as f64 / fronds as f64 * TAU + rng.random_range(-0.3..0.3);
+        let length = roll(rng, frond_length_range);
+        for step in 1..=length {
+            let t = step as f64 / length as f64;
+            // Rises slightly, then droops towards the tip
+            let dy = 1 - (t * t * 3.0).round() as i32;
+            let fx = top.0 + (angle.cos() * step as f64).round() as i32;
+            let fz = top.2 + (angle.sin() * step as f64).round() as i32;
+            voxels.insert((fx, top.1 + dy, fz), leaves);
+        }
+    }
+}
+
+/// A single wood block wrapped in a flattened blob of leaves with ragged edges
+fn generate_bush(
+    voxels: &mut VoxelSet,
+    base_pos: (i32, i32, i32),
+    rng: &mut impl Rng,
+    wood: VoxelType,
+    leaves: Option<VoxelType>,
+    radius: i32,
+    height: i32,
+) {
+    let (x, y, z) = base_pos;
+    voxels.insert((x, y, z), wood);
+    let Some(leaves) = leaves else {
+        return;
+    };
+
+    for dy in 0..=height {
+        for dx in -radius..=radius {
+            for dz in -radius..=radius {
+                let horizontal = (dx * dx + dz * dz) as f32 / (radius as f32 + 0.5).powi(2);
+                let vertical = dy as f32 / (height + 1) as f32;
+                let distance = horizontal + vertical * vertical;
+                if distance > 1.0 || (distance > 0.6 && rng.random_bool(0.4)) {
+                    continue;
+                }
+                if dx != 0 || dy != 0 || dz != 0 {
+                    voxels.insert((x + dx, y + dy, z + dz), leaves);
+                }
+            }
+        }
+    }
+}
+
+/// Trunk lying along x or z, starting at the base
+fn generate_fallen_log(
+    voxels: &mut VoxelSet,
+    base_pos: (i32, i32, i32),
+    rng: &mut impl Rng,
+    wood: VoxelType,
+    length: i32,
+) {
+    let (x, y, z) = base_pos;
+    let (dx, dz) = match rng.random_range(0..4) {
+        0 => (1, 0),
+        1 => (-1, 0),
+        2 => (0, 1),
+        _ => (0, -1),
+    };
+    for i in 0..length {
+        voxels.insert((x + dx * i, y, z + dz * i), wood);
+    }
+}
+
+/// Upright column with arms that stick out one block and then grow upwards
+fn generate_column(
+    voxels: &mut VoxelSet,
+    base_pos: (i32, i32, i32),
+    rng: &mut impl Rng,
+    wood: VoxelType,
+    height: i32,
+    arms: i32,
+) {
+    let (x, y, z) = base_pos;
+    for dy in 0..height {
+        voxels.insert((x, y + dy, z), wood);
+    }
+    if height < 3 {
+        return;
+    }
+
+    for _ in 0..arms {
+        let (dx, dz) = match rng.random_range(0..4) {
+            0 => (1, 0),
+            1 => (-1, 0),
+            2 => (0, 1),
+            _ => (0, -1),
+        };
+        let arm_y = y + rng.random_range(1..height - 1);
+        let arm_height = rng.random_range(1..=2);
+        for dy in 0..=arm_height {
+            voxels.insert((x + dx, arm_y + dy, z + dz), wood);
+        }
+    }
+}
+
+/// Upper bound on the expanded L-system string, so runaway rules stay cheap
+const MAX_LSYSTEM_LENGTH: usize = 4096;
+
+/// Applies the rules to the axiom `iterations` times
+fn expand_lsystem(
+    axiom: &str,
+    rules: &[LSystemRule],
+    iterations: u32,
+    rng: &mut impl Rng,
+) -> Vec<char> {
+    let mut current: Vec<char> = axiom.chars().collect();
+    for _ in 0..iterations {
+        let mut next = Vec::with_capacity(current.len() * 2);
+        for &symbol in &current {
+            let candidates: Vec<&LSystemRule> =
+                rules.iter().filter(|rule| rule.symbol == symbol).collect();
+            if candidates.is_empty() {
+                next.push(symbol);
+            } else {
+                let rule = candidates[rng.random_range(0..candidates.len())];
+                next.extend(rule.replacement.chars());
+            }
+        }
+        if next.len() > MAX_LSYSTEM_LENGTH {
+            break;
+        }
+        current = next;
+    }
+    current
+}
+
+/// Draws an expanded L-system string in voxels
+struct Turtle {
+    wood: VoxelType,
+    leaves: Option<VoxelType>,
+    /// Turn angle in radians
+    angle: f64,
+    segment_length: [i32; 2],
+    leaf_radius: [i32; 2],
+}
+
+impl Turtle {
+    fn draw(
+        &self,
+        voxels: &mut VoxelSet,
+        base_pos: (i32, i32, i32),
+        commands: &[char],
+        rng: &mut impl Rng,
+    ) {
+        // Position, yaw and pitch, starting upright with a random heading
+        let mut position = (base_pos.0 as f64, base_pos.1 as f64, base_pos.2 as f64);
+        let mut yaw = rng.random_range(0.0..TAU);
+        let mut pitch = std::f64::consts::FRAC_PI_2;
+        let mut stack = Vec::new();
+
+        for &command in commands {
+            let jitter = rng.random_range(-0.25..=0.25) * self.angle;
+            match command {
+                'F' => {
+                    let length = roll(rng, self.segment_length) as f64;
+                    let end = (
+                        position.0 + yaw.cos() * pitch.cos() * length,
+                        position.1 + pitch.sin() * length,
+                        position.2 + yaw.sin() * pitch.cos() * length,
+                    );
+                    draw_line(voxels, round(position), round(end), self.wood);
+                    position = end;
+                }
+                'L' => {
+                    if let Some(leaves) = self.leaves {
+                        let radius = roll(rng, self.leaf_radius);
+                        generate_leaves_custom(voxels, round(position), leaves, radius);
+                    }
+                }
+                '+' => yaw += self.angle + jitter,
+                '-' => yaw -= self.angle + jitter,
+                '&' => pitch -= self.angle + jitter,
+                '^' => pitch += self.angle + jitter,
+                '[' => stack.push((position, yaw, pitch)),
+                ']' => {
+                    if let Some(state) = stack.pop() {
+                        (position, yaw, pitch) = state;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn round((x, y, z): (f64, f64, f64)) -> (i32, i32, i32) {
+    (x.round() as i32, y.round() as i32, z.round() as i32)
+}
+
+// Straight line of `block` between two points
+fn draw_line(
+    voxels: &mut VoxelSet,
+    start: (i32, i32, i32),
+    end: (i32, i32, i32),
+    block: VoxelType,
+) {
+    let steps = (end.0 - start.0)
+        .abs()
+        .max((end.1 - start.1).abs())
+        .max((end.2 - start.2).abs())
+        .max(1)
+        * 2;
+    for i in 0..=steps {
+        let t = i as f32 / steps as f32;
+        let bx = lerp(start.0 as f32, end.0 as f32, t).round() as i32;
+        let by = lerp(start.1 as f32, end.1 as f32, t).round() as i32;
+        let bz = lerp(start.2 as f32, end.2 as f32, t).round() as i32;
+        voxels.insert((bx, by, bz), block);
+    }
 }
 
 // Helper for leaves with custom type and radius
 fn generate_leaves_custom(
-    voxels: &mut Vec<(i32, i32, i32, VoxelType)>,
+    voxels: &mut VoxelSet,
     center: (i32, i32, i32),
     leaf_type: VoxelType,
-    radius_range: std::ops::Range<i32>,
+    radius: i32,
 ) {
     let (cx, cy, cz) = center;
-    let mut rng = rand::rng();
-    let radius = rng.random_range(radius_range);
 
     for dx in -radius..=radius {
         for dy in -1..=1 {
             for dz in -radius..=radius {
                 let distance_sq = dx * dx + dy * dy + dz * dz;
                 if distance_sq <= radius * radius {
-                    let x = cx + dx;
-                    let y = cy + dy;
-                    let z = cz + dz;
-
-                    if !voxels.iter().any(|&(vx, vy, vz, _)| vx == x && vy == y && vz == z) {
-                        voxels.push((x, y, z, leaf_type));
-                    }
+                    voxels.insert((cx + dx, cy + dy, cz + dz), leaf_type);
                 }
             }
         }
@@ -141,14 +708,12 @@ fn generate_leaves_custom(
 
 // Helper for crown with custom type and radius
 fn generate_leaves_crown_custom(
-    voxels: &mut Vec<(i32, i32, i32, VoxelType)>,
+    voxels: &mut VoxelSet,
     center: (i32, i32, i32),
     leaf_type: VoxelType,
-    radius_range: std::ops::Range<i32>,
+    radius: i32,
 ) {
     let (cx, cy, cz) = center;
-    let mut rng = rand::rng();
-    let radius = rng.random_range(radius_range);
 
     for dx in -radius..=radius {
         for dy in 0..radius {
@@ -159,15 +724,9 @@ fn generate_leaves_crown_custom(
                 let distance_sq = x_norm * x_norm + y_norm * y_norm + z_norm * z_norm;
 
                 if distance_sq <= 1.0 {
-                    let x = cx + dx;
-                    let y = cy + dy;
-                    let z = cz + dz;
-
-                    if !voxels.iter().any(|&(vx, vy, vz, _)| vx == x && vy == y && vz == z) {
-                        voxels.push((x, y, z, leaf_type));
-                    }
+                    voxels.insert((cx + dx, cy + dy, cz + dz), leaf_type);
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
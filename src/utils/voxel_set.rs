@@ -0,0 +1,81 @@
+use crate::voxel::VoxelType;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+/// Sparse set of voxels keyed by position. Placing a block where another one already
+/// is keeps whichever has the higher precedence, and the first one on a tie, so wood
+/// always wins over leaves no matter which part of a tree was generated first.
+#[derive(Debug, Default)]
+pub struct VoxelSet {
+    voxels: HashMap<(i32, i32, i32), VoxelType>,
+}
+
+impl VoxelSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, position: (i32, i32, i32), block: VoxelType) {
+        match self.voxels.entry(position) {
+            Entry::Vacant(entry) => {
+                entry.insert(block);
+            }
+            Entry::Occupied(mut entry) => {
+                if precedence(block) > precedence(*entry.get()) {
+                    entry.insert(block);
+                }
+            }
+        }
+    }
+
+    /// Freezes the set into a sorted list with its bounding box
+    pub fn into_compact(self) -> CompactVoxels {
+        let mut voxels: Vec<(i32, i32, i32, VoxelType)> = self
+            .voxels
+            .into_iter()
+            .map(|((x, y, z), block)| (x, y, z, block))
+            .collect();
+        voxels.sort_unstable_by_key(|&(x, y, z, _)| (x, y, z));
+
+        let mut min = (i32::MAX, i32::MAX, i32::MAX);
+        let mut max = (i32::MIN, i32::MIN, i32::MIN);
+        for &(x, y, z, _) in &voxels {
+            min = (min.0.min(x), min.1.min(y), min.2.min(z));
+            max = (max.0.max(x), max.1.max(y), max.2.max(z));
+        }
+        CompactVoxels { min, max, voxels }
+    }
+}
+
+// Leaves give way to every other block
+fn precedence(block: VoxelType) -> u8 {
+    if block.is_leaves() { 0 } else { 1 }
+}
+
+/// Voxels without duplicates, sorted by position, with their inclusive bounding box
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactVoxels {
+    pub min: (i32, i32, i32),
+    pub max: (i32, i32, i32),
+    voxels: Vec<(i32, i32, i32, VoxelType)>,
+}
+
+impl CompactVoxels {
+    /// Whether the bounding box overlaps the inclusive box from `min` to `max`
+    pub fn intersects(&self, min: (i32, i32, i32), max: (i32, i32, i32)) -> bool {
+        self.min.0 <= max.0
+            && self.max.0 >= min.0
+            && self.min.1 <= max.1
+            && self.max.1 >= min.1
+            && self.min.2 <= max.2
+            && self.max.2 >= min.2
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, i32, VoxelType)> + '_ {
+        self.voxels.iter().copied()
+    }
+
+    pub fn into_vec(self) -> Vec<(i32, i32, i32, VoxelType)> {
+        self.voxels
+    }
+}
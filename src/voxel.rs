@@ -1,8 +1,9 @@
 // voxel.rs
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, EnumString};
 
-#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, Serialize, Deserialize)]
 pub enum VoxelType {
     Air,
     Dirt,
@@ -23,17 +24,158 @@ pub enum VoxelType {
     MahoganyWood,
     MahoganyPlanks,
     MahoganyLeaves,
+    BirchWood,
+    BirchPlanks,
+    BirchLeaves,
+    Cactus,
     Cobblestone,
     CopperOre,
     AmethystOre,
     Stonebrick,
     Glass,
+    Furnace,
+    Chest,
+    WalnutSlab,
+    SpruceSlab,
+    MahoganySlab,
+    BirchSlab,
+    StoneSlab,
+    WalnutStairs,
+    SpruceStairs,
+    MahoganyStairs,
+    BirchStairs,
+    StoneStairs,
+    WalnutFence,
+    SpruceFence,
+    MahoganyFence,
+    BirchFence,
+    TallGrass,
+    Dandelion,
+    Poppy,
 }
 
+/// The geometry a block is meshed and collided with, built by `block_model`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    Cube,
+    Slab,
+    Stairs,
+    Fence,
+    /// Two textured quads crossing diagonally, for plants
+    Cross,
+}
+
+/// Axis a block like a log lies along
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Y,
+    X,
+    Z,
+}
+
+/// Horizontal direction the front of a block faces, in quarter turns from `South` (+Z)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facing {
+    South,
+    West,
+    North,
+    East,
+}
+
+/// Which half of its cell a block like a slab fills
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Half {
+    Bottom,
+    Top,
+}
+
+/// Compact per-voxel state bits stored next to the `VoxelType`: the axis in bits 0-1,
+/// facing in bits 2-3, half in bit 4 and waterlogged in bit 5. What a block makes of
+/// them is decided by its `state_properties`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct BlockState(u8);
+
+const AXIS_SHIFT: u8 = 0;
+const FACING_SHIFT: u8 = 2;
+const HALF_BIT: u8 = 1 << 4;
+const WATERLOGGED_BIT: u8 = 1 << 5;
+
+impl BlockState {
+    pub fn axis(&self) -> Axis {
+        match (self.0 >> AXIS_SHIFT) & 0b11 {
+            1 => Axis::X,
+            2 => Axis::Z,
+            _ => Axis::Y,
+        }
+    }
+
+    pub fn with_axis(self, axis: Axis) -> Self {
+        Self(self.0 & !(0b11 << AXIS_SHIFT) | (axis as u8) << AXIS_SHIFT)
+    }
+
+    pub fn facing(&self) -> Facing {
+        match (self.0 >> FACING_SHIFT) & 0b11 {
+            1 => Facing::West,
+            2 => Facing::North,
+            3 => Facing::East,
+            _ => Facing::South,
+        }
+    }
+
+    pub fn with_facing(self, facing: Facing) -> Self {
+        Self(self.0 & !(0b11 << FACING_SHIFT) | (facing as u8) << FACING_SHIFT)
+    }
+
+    pub fn half(&self) -> Half {
+        if self.0 & HALF_BIT != 0 { Half::Top } else { Half::Bottom }
+    }
+
+    pub fn with_half(self, half: Half) -> Self {
+        match half {
+            Half::Top => Self(self.0 | HALF_BIT),
+            Half::Bottom => Self(self.0 & !HALF_BIT),
+        }
+    }
+
+    pub fn waterlogged(&self) -> bool {
+        self.0 & WATERLOGGED_BIT != 0
+    }
+
+    pub fn with_waterlogged(self, waterlogged: bool) -> Self {
+        if waterlogged { Self(self.0 | WATERLOGGED_BIT) } else { Self(self.0 & !WATERLOGGED_BIT) }
+    }
+}
+
+/// Which of the state bits a block uses
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateProperties {
+    pub axis: bool,
+    pub facing: bool,
+    pub half: bool,
+    pub waterloggable: bool,
+}
+
+/// What a block is being placed against, for picking its state
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    /// Normal of the clicked face, pointing into the cell the block is placed in
+    pub normal: (i32, i32, i32),
+    /// Horizontal direction the player looks in, as (x, z)
+    pub look: (f32, f32),
+    /// Height of the clicked point within the placed block's cell, from 0 to 1
+    pub hit_height: f32,
+    /// The voxel the block replaces
+    pub replacing: VoxelType,
+}
+
+// World faces around the Y axis in `Facing` order: Front (+Z), Left (-X), Back (-Z), Right (+X)
+const HORIZONTAL_FACES: [usize; 4] = [2, 5, 3, 4];
+
 impl VoxelType {
     pub fn is_transparent(&self) -> bool {
         matches!(self, VoxelType::Air | VoxelType::Water | VoxelType::Ice | VoxelType::Glass | 
-                  VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves)
+                  VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves |
+                  VoxelType::BirchLeaves | VoxelType::TallGrass | VoxelType::Dandelion | VoxelType::Poppy)
     }
 
     pub fn transparent_optimize_outer_only(&self) -> bool {
@@ -42,20 +184,120 @@ impl VoxelType {
 
     pub fn transparent_optimize_outer_one_inner(&self) -> bool {
         matches!(self, VoxelType::Glass | 
-                  VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves)
+                  VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves |
+                  VoxelType::BirchLeaves)
     }
 
     pub fn is_not_solid(&self) -> bool {
         matches!(self, VoxelType::Air)
     }
 
+    pub fn is_leaves(&self) -> bool {
+        matches!(self, VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves |
+                  VoxelType::BirchLeaves)
+    }
+
+    pub fn shape(&self) -> Shape {
+        match self {
+            VoxelType::WalnutSlab | VoxelType::SpruceSlab | VoxelType::MahoganySlab | VoxelType::BirchSlab |
+            VoxelType::StoneSlab => Shape::Slab,
+            VoxelType::WalnutStairs | VoxelType::SpruceStairs | VoxelType::MahoganyStairs | VoxelType::BirchStairs |
+            VoxelType::StoneStairs => Shape::Stairs,
+            VoxelType::WalnutFence | VoxelType::SpruceFence | VoxelType::MahoganyFence | VoxelType::BirchFence => Shape::Fence,
+            VoxelType::TallGrass | VoxelType::Dandelion | VoxelType::Poppy => Shape::Cross,
+            _ => Shape::Cube,
+        }
+    }
+
+    /// Whether the block fills its whole cell and hides the faces of neighbours against it
+    pub fn is_opaque_cube(&self) -> bool {
+        self.shape() == Shape::Cube && !self.is_transparent()
+    }
+
+    pub fn state_properties(&self) -> StateProperties {
+        match self {
+            VoxelType::WalnutWood | VoxelType::SpruceWood | VoxelType::MahoganyWood | VoxelType::BirchWood => {
+                StateProperties { axis: true, ..Default::default() }
+            }
+            VoxelType::Furnace | VoxelType::Chest => StateProperties { facing: true, ..Default::default() },
+            VoxelType::WalnutLeaves | VoxelType::SpruceLeaves | VoxelType::MahoganyLeaves | VoxelType::BirchLeaves => {
+                StateProperties { waterloggable: true, ..Default::default() }
+            }
+            _ => match self.shape() {
+                Shape::Slab => StateProperties { half: true, waterloggable: true, ..Default::default() },
+                Shape::Stairs => StateProperties { facing: true, half: true, waterloggable: true, ..Default::default() },
+                Shape::Fence => StateProperties { waterloggable: true, ..Default::default() },
+                _ => StateProperties::default(),
+            },
+        }
+    }
+
+    /// The state of the block when placed: logs lie along the axis of the clicked face,
+    /// blocks with a front face the player, halves follow the clicked height and
+    /// waterloggable blocks placed into water keep it
+    pub fn placement_state(&self, placement: &Placement) -> BlockState {
+        let properties = self.state_properties();
+        let mut state = BlockState::default();
+        if properties.axis {
+            state = state.with_axis(match placement.normal {
+                (0, _, 0) => Axis::Y,
+                (_, 0, 0) => Axis::X,
+                _ => Axis::Z,
+            });
+        }
+        if properties.facing {
+            let (x, z) = placement.look;
+            state = state.with_facing(if x.abs() > z.abs() {
+                if x > 0.0 { Facing::West } else { Facing::East }
+            } else if z > 0.0 {
+                Facing::North
+            } else {
+                Facing::South
+            });
+        }
+        if properties.half {
+            let top = match placement.normal.1 {
+                1 => false,
+                -1 => true,
+                _ => placement.hit_height > 0.5,
+            };
+            state = state.with_half(if top { Half::Top } else { Half::Bottom });
+        }
+        if properties.waterloggable {
+            state = state.with_waterlogged(placement.replacing == VoxelType::Water);
+        }
+        state
+    }
+
+    /// Which of the block's own faces shows on world `face` in `state`, and by how many
+    /// quarter turns its texture is rotated so e.g. bark runs along a sideways log
+    pub fn oriented_face(&self, face: usize, state: BlockState) -> (usize, usize) {
+        let properties = self.state_properties();
+        if properties.axis {
+            return match (state.axis(), face) {
+                (Axis::Y, _) => (face, 0),
+                (Axis::X, 4 | 5) | (Axis::Z, 2 | 3) => (0, 0),
+                (Axis::X, _) | (Axis::Z, 4 | 5) => (2, 1),
+                (Axis::Z, _) => (2, 0),
+            };
+        }
+        if properties.facing {
+            if let Some(index) = HORIZONTAL_FACES.iter().position(|&f| f == face) {
+                let turns = state.facing() as usize;
+                return (HORIZONTAL_FACES[(index + 4 - turns) % 4], 0);
+            }
+        }
+        (face, 0)
+    }
+
     pub fn get_all_voxel_types() -> Vec<VoxelType> {
         VoxelType::iter().filter(|&v| v != VoxelType::Air).collect()
     }
 
-    pub fn get_face_texture(&self, face: usize) -> [f32; 4] {
+    pub fn get_face_texture(&self, face: usize, state: BlockState) -> [f32; 4] {
         // [u, v, tile_width, tile_height]
         // Face order: Top, Bottom, Front, Back, Right, Left
+        let (face, _) = self.oriented_face(face, state);
         let tile_w = 1.0 / 16.0;
         let tile_h = 1.0 / 16.0;
         let margin_u = tile_w * 0.01;
@@ -94,11 +336,40 @@ impl VoxelType {
             },
             VoxelType::MahoganyPlanks => (5, 2),
             VoxelType::MahoganyLeaves => (5, 3),
+            VoxelType::BirchWood => match face {
+                0 | 1 => (6, 1),  // Top/Bottom
+                _ => (6, 0),      // Sides
+            },
+            VoxelType::BirchPlanks => (6, 2),
+            VoxelType::BirchLeaves => (6, 3),
+            VoxelType::Cactus => match face {
+                0 | 1 => (7, 1),  // Top/Bottom
+                _ => (7, 0),      // Sides
+            },
             VoxelType::Cobblestone => (2, 1),
             VoxelType::CopperOre => (2, 2),
             VoxelType::AmethystOre => (2, 3),
             VoxelType::Stonebrick => (2, 4),
             VoxelType::Glass => (2, 5),
+            VoxelType::Furnace => match face {
+                0 | 1 => (8, 2),  // Top/Bottom
+                2 => (8, 1),      // Front
+                _ => (8, 0),      // Sides
+            },
+            VoxelType::Chest => match face {
+                0 | 1 => (9, 2),  // Top/Bottom
+                2 => (9, 1),      // Front
+                _ => (9, 0),      // Sides
+            },
+            // Shaped blocks reuse the texture of what they're made of
+            VoxelType::WalnutSlab | VoxelType::WalnutStairs | VoxelType::WalnutFence => (3, 2),
+            VoxelType::SpruceSlab | VoxelType::SpruceStairs | VoxelType::SpruceFence => (4, 2),
+            VoxelType::MahoganySlab | VoxelType::MahoganyStairs | VoxelType::MahoganyFence => (5, 2),
+            VoxelType::BirchSlab | VoxelType::BirchStairs | VoxelType::BirchFence => (6, 2),
+            VoxelType::StoneSlab | VoxelType::StoneStairs => (2, 0),
+            VoxelType::TallGrass => (10, 0),
+            VoxelType::Dandelion => (10, 1),
+            VoxelType::Poppy => (10, 2),
         };
 
         if u == -1 && v == -1 {
@@ -134,11 +405,131 @@ impl VoxelType {
             VoxelType::MahoganyWood => "Mahogany Wood",
             VoxelType::MahoganyPlanks => "Mahogany Planks",
             VoxelType::MahoganyLeaves => "Mahogany Leaves",
+            VoxelType::BirchWood => "Birch Wood",
+            VoxelType::BirchPlanks => "Birch Planks",
+            VoxelType::BirchLeaves => "Birch Leaves",
+            VoxelType::Cactus => "Cactus",
             VoxelType::Cobblestone => "Cobblestone",
             VoxelType::CopperOre => "Copper Ore",
             VoxelType::AmethystOre => "Amethyst Ore",
             VoxelType::Stonebrick => "Stone Brick",
             VoxelType::Glass => "Glass",
+            VoxelType::Furnace => "Furnace",
+            VoxelType::Chest => "Chest",
+            VoxelType::WalnutSlab => "Walnut Slab",
+            VoxelType::SpruceSlab => "Spruce Slab",
+            VoxelType::MahoganySlab => "Mahogany Slab",
+            VoxelType::BirchSlab => "Birch Slab",
+            VoxelType::StoneSlab => "Stone Slab",
+            VoxelType::WalnutStairs => "Walnut Stairs",
+            VoxelType::SpruceStairs => "Spruce Stairs",
+            VoxelType::MahoganyStairs => "Mahogany Stairs",
+            VoxelType::BirchStairs => "Birch Stairs",
+            VoxelType::StoneStairs => "Stone Stairs",
+            VoxelType::WalnutFence => "Walnut Fence",
+            VoxelType::SpruceFence => "Spruce Fence",
+            VoxelType::MahoganyFence => "Mahogany Fence",
+            VoxelType::BirchFence => "Birch Fence",
+            VoxelType::TallGrass => "Tall Grass",
+            VoxelType::Dandelion => "Dandelion",
+            VoxelType::Poppy => "Poppy",
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AXES: [Axis; 3] = [Axis::Y, Axis::X, Axis::Z];
+    const FACINGS: [Facing; 4] = [Facing::South, Facing::West, Facing::North, Facing::East];
+    const HALVES: [Half; 2] = [Half::Bottom, Half::Top];
+
+    fn placement(normal: (i32, i32, i32), look: (f32, f32), hit_height: f32) -> Placement {
+        Placement { normal, look, hit_height, replacing: VoxelType::Air }
+    }
+
+    #[test]
+    fn state_bits_round_trip_for_every_combination() {
+        for axis in AXES {
+            for facing in FACINGS {
+                for half in HALVES {
+                    for waterlogged in [false, true] {
+                        let state = BlockState::default()
+                            .with_axis(axis)
+                            .with_facing(facing)
+                            .with_half(half)
+                            .with_waterlogged(waterlogged);
+                        assert_eq!(state.axis(), axis);
+                        assert_eq!(state.facing(), facing);
+                        assert_eq!(state.half(), half);
+                        assert_eq!(state.waterlogged(), waterlogged);
+
+                        // Overwriting a field leaves the others alone
+                        let other = state.with_axis(Axis::Z).with_facing(Facing::East).with_half(Half::Top);
+                        let back = other.with_axis(axis).with_facing(facing).with_half(half);
+                        assert_eq!(back, state);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn logs_lie_along_the_clicked_face() {
+        let cases = [
+            ((0, 1, 0), Axis::Y),
+            ((0, -1, 0), Axis::Y),
+            ((1, 0, 0), Axis::X),
+            ((-1, 0, 0), Axis::X),
+            ((0, 0, 1), Axis::Z),
+            ((0, 0, -1), Axis::Z),
+        ];
+        for (normal, axis) in cases {
+            let state = VoxelType::WalnutWood.placement_state(&placement(normal, (0.0, 1.0), 0.5));
+            assert_eq!(state.axis(), axis, "normal {:?}", normal);
+        }
+    }
+
+    #[test]
+    fn fronts_face_the_player_in_every_quadrant() {
+        let cases = [
+            ((1.0, 0.2), Facing::West),
+            ((-1.0, -0.2), Facing::East),
+            ((0.2, 1.0), Facing::North),
+            ((-0.2, -1.0), Facing::South),
+        ];
+        for (look, facing) in cases {
+            for normal in [(0, 1, 0), (1, 0, 0), (0, 0, -1)] {
+                let state = VoxelType::Furnace.placement_state(&placement(normal, look, 0.5));
+                assert_eq!(state.facing(), facing, "look {:?}, normal {:?}", look, normal);
+            }
+        }
+    }
+
+    #[test]
+    fn halves_follow_the_clicked_face_and_height() {
+        let cases = [
+            ((0, 1, 0), 0.9, Half::Bottom),
+            ((0, -1, 0), 0.1, Half::Top),
+            ((1, 0, 0), 0.75, Half::Top),
+            ((0, 0, 1), 0.25, Half::Bottom),
+        ];
+        for (normal, hit_height, half) in cases {
+            for block in [VoxelType::StoneSlab, VoxelType::StoneStairs] {
+                let state = block.placement_state(&placement(normal, (0.0, 1.0), hit_height));
+                assert_eq!(state.half(), half, "{:?} on {:?} at {}", block, normal, hit_height);
+            }
+        }
+    }
+
+    #[test]
+    fn only_waterloggable_blocks_keep_the_water() {
+        let into_water = Placement { replacing: VoxelType::Water, ..placement((0, 1, 0), (0.0, 1.0), 0.5) };
+        assert!(VoxelType::StoneSlab.placement_state(&into_water).waterlogged());
+        assert!(!VoxelType::StoneSlab.placement_state(&placement((0, 1, 0), (0.0, 1.0), 0.5)).waterlogged());
+        assert!(!VoxelType::Furnace.placement_state(&into_water).waterlogged());
+        // Blocks without state bits always place in the default state
+        assert_eq!(VoxelType::Stone.placement_state(&into_water), BlockState::default());
+    }
+}
@@ -1,14 +1,36 @@
+use crate::block_entity::BlockEntity;
 use crate::chunk::{self, Chunk};
-use crate::utils::tree_gen::{generate_mahogany_tree, generate_spruce_tree, generate_walnut_tree};
-use crate::voxel::VoxelType;
+use crate::inventory::ItemStack;
+use crate::voxel::{BlockState, VoxelType};
+use crate::worldgen::biome::Biome;
+use crate::worldgen::columns::ColumnCache;
+use crate::worldgen::features::FeatureStart;
+use crate::worldgen::generator::TerrainGenerator;
+use crate::worldgen::pipeline::{self, ChunkStatus};
+use crate::worldgen::presets::WorldPreset;
+use crate::worldgen::spawn;
 use ferrousgl::{GlWindow, Shader};
 use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::{Receiver, Sender, channel};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+/// Outcome of looking for a spawn point in the loaded terrain
+pub enum SpawnSearch {
+    /// Surface block to stand on
+    Found((i32, i32, i32)),
+    /// A nearer column hasn't loaded yet
+    Loading,
+    /// Every column in range is water, leaves or the wrong biome
+    NoneSuitable,
+}
+
 enum ChunkWorkerAction {
-    Generate((i32, i32, i32), Option<Vec<(i32, i32, i32, VoxelType)>>), // <-- changed
+    /// Runs the terrain stages of a new chunk, up to `ChunkStatus::Carved`
+    Generate((i32, i32, i32)),
+    /// Runs the remaining stages once all neighbours are carved, then puts back the
+    /// edits and block entities saved when the chunk was last unloaded
+    Decorate(Chunk, Vec<FeatureStart>, Option<String>),
     Remesh(Chunk),
     ModifyVoxel {
         chunk: Chunk,
@@ -33,28 +55,41 @@ pub struct World {
     worker_handles: Vec<thread::JoinHandle<()>>,
     pub seed: u32,
     next_chunk_pos: (i32, i32, i32),
-    // --- Add this field ---
-    pending_voxels: Arc<Mutex<HashMap<(i32, i32, i32), Vec<(i32, i32, i32, VoxelType)>>>>,
     pending_chunks: Arc<Mutex<HashSet<(i32, i32, i32)>>>, // <-- Add this
+    /// Feature starts of every carved chunk, kept while the chunk itself is out on a worker
+    feature_starts: Mutex<HashMap<(i32, i32, i32), Vec<FeatureStart>>>,
+    /// 2D noise shared by all chunks stacked in the same (cx, cz) column
+    column_cache: Arc<ColumnCache>,
+    generator: Arc<dyn TerrainGenerator>,
+    /// Generator name, seed and settings this world was created with
+    pub preset: WorldPreset,
+    /// Terrain height per chunk column, used to skip chunks far from the surface
+    surface_estimates: Mutex<HashMap<(i32, i32), Option<i32>>>,
+    /// Serialised edits and block entities of unloaded chunks, restored when they generate again
+    saved_chunks: Mutex<HashMap<(i32, i32, i32), String>>,
 }
 
 impl World {
-    pub fn new(chunk_size: usize, seed: u32) -> Self {
+    pub fn new(chunk_size: usize, seed: u32, mut preset: WorldPreset) -> Result<Self, String> {
+        let generator = preset.build_generator(seed)?;
+        // Record the seed so saving the preset reproduces this exact world
+        preset.seed = Some(seed);
+
         let (work_sender, work_receiver) = channel();
         let (chunk_sender, chunk_receiver) = channel();
 
         let chunks = Arc::new(Mutex::new(HashMap::new()));
         let work_receiver = Arc::new(Mutex::new(work_receiver));
-        let pending_voxels = Arc::new(Mutex::new(HashMap::new()));
         let pending_chunks = Arc::new(Mutex::new(HashSet::new()));
+        let column_cache = Arc::new(ColumnCache::new());
 
         let mut worker_handles = Vec::new();
 
         for _ in 0..10 {
             let work_receiver = Arc::clone(&work_receiver);
             let chunk_sender = chunk_sender.clone();
-            let pending_voxels = Arc::clone(&pending_voxels);
-            let pending_chunks = Arc::clone(&pending_chunks);
+            let column_cache = Arc::clone(&column_cache);
+            let generator = Arc::clone(&generator);
 
             let handle = thread::spawn(move || {
                 loop {
@@ -63,15 +98,19 @@ impl World {
                         lock.recv()
                     };
                     match work {
-                        Ok(ChunkWorkerAction::Generate((cx, cy, cz), pending)) => {
-                            let chunk = if let Some(pending_voxels) = pending {
-                                Chunk::new(cx, cy, cz, chunk_size, seed, Some(&pending_voxels))
-                            } else {
-                                Chunk::new(cx, cy, cz, chunk_size, seed, None)
-                            };
-                            let mut chunk = chunk;
-                            chunk.generate_data(cx, cy, cz);
-                            chunk.prepare_mesh();
+                        Ok(ChunkWorkerAction::Generate((cx, cy, cz))) => {
+                            let mut chunk = Chunk::new(cx, cy, cz, chunk_size);
+                            pipeline::generate_terrain(generator.as_ref(), &mut chunk, &column_cache);
+                            chunk_sender.send(chunk).unwrap();
+                        }
+                        Ok(ChunkWorkerAction::Decorate(mut chunk, starts, saved)) => {
+                            pipeline::finish_chunk(generator.as_ref(), &mut chunk, &starts);
+                            if let Some(saved) = saved {
+                                match chunk.restore(&saved) {
+                                    Ok(()) => chunk.prepare_mesh(),
+                                    Err(e) => eprintln!("Failed to restore the edits of {:?}: {}", chunk.position, e),
+                                }
+                            }
                             chunk_sender.send(chunk).unwrap();
                         }
                         Ok(ChunkWorkerAction::Remesh(mut chunk)) => {
@@ -85,11 +124,11 @@ impl World {
                             wz,
                             voxel_type,
                         }) => {
-                            let cs = 32 as i32;
+                            let cs = chunk.size as i32;
                             let lx = wx.rem_euclid(cs) as usize;
                             let ly = wy.rem_euclid(cs) as usize;
                             let lz = wz.rem_euclid(cs) as usize;
-                            chunk.set_voxel(lx, ly, lz, voxel_type);
+                            chunk.edit_voxel(lx, ly, lz, voxel_type, BlockState::default());
                             chunk.prepare_mesh();
                             chunk_sender.send(chunk).unwrap();
                         }
@@ -127,7 +166,7 @@ impl World {
                             for (chunk_coords, updates) in chunk_updates {
                                 if let Some(mut chunk) = chunks.remove(&chunk_coords) {
                                     for (lx, ly, lz, voxel_type) in updates {
-                                        chunk.set_voxel(lx, ly, lz, voxel_type);
+                                        chunk.edit_voxel(lx, ly, lz, voxel_type, BlockState::default());
                                     }
                                     chunk.prepare_mesh();
                                     chunk_sender.send(chunk).unwrap();
@@ -141,7 +180,7 @@ impl World {
             worker_handles.push(handle);
         }
 
-        Self {
+        Ok(Self {
             chunks,
             chunk_size,
             work_sender,
@@ -149,9 +188,14 @@ impl World {
             worker_handles,
             seed,
             next_chunk_pos: (0, 0, 0),
-            pending_voxels,
             pending_chunks,
-        }
+            feature_starts: Mutex::new(HashMap::new()),
+            column_cache,
+            generator,
+            preset,
+            surface_estimates: Mutex::new(HashMap::new()),
+            saved_chunks: Mutex::new(HashMap::new()),
+        })
     }
 
     fn drop(&mut self) {
@@ -164,16 +208,42 @@ impl World {
     }
 
     pub fn create_chunk(&self, cx: i32, cy: i32, cz: i32) {
-        // --- Get pending voxels for this chunk, if any ---
-        let pending = {
-            let mut pending = self.pending_voxels.lock().unwrap();
-            pending.remove(&(cx, cy, cz))
-        };
         self.work_sender
-            .send(ChunkWorkerAction::Generate((cx, cy, cz), pending))
+            .send(ChunkWorkerAction::Generate((cx, cy, cz)))
             .unwrap();
     }
 
+    /// Sends every carved chunk whose neighbours are all carved on to the features stage
+    fn decorate_ready_chunks(&self) {
+        let mut chunks = self.chunks.lock().unwrap();
+        let mut pending = self.pending_chunks.lock().unwrap();
+        let feature_starts = self.feature_starts.lock().unwrap();
+        let mut saved_chunks = self.saved_chunks.lock().unwrap();
+
+        // Neighbours far from the surface may never be generated and count as empty
+        let carved = |n: &(i32, i32, i32)| {
+            feature_starts.contains_key(n) || !self.within_terrain_range(*n)
+        };
+        let ready: Vec<(i32, i32, i32)> = chunks
+            .iter()
+            .filter(|(_, chunk)| chunk.status == ChunkStatus::Carved)
+            .map(|(&pos, _)| pos)
+            .filter(|&pos| pipeline::neighbourhood(pos).all(|n| carved(&n)))
+            .collect();
+
+        for pos in ready {
+            let chunk = chunks.remove(&pos).unwrap();
+            let starts = pipeline::neighbourhood(pos)
+                .filter_map(|n| feature_starts.get(&n))
+                .flat_map(|starts| starts.iter().copied())
+                .collect();
+            pending.insert(pos);
+            self.work_sender
+                .send(ChunkWorkerAction::Decorate(chunk, starts, saved_chunks.remove(&pos)))
+                .unwrap();
+        }
+    }
+
     pub fn request_remesh(&self, cx: i32, cy: i32, cz: i32) {
         let mut chunks = self.chunks.lock().unwrap();
         if let Some(chunk) = chunks.remove(&(cx, cy, cz)) {
@@ -197,6 +267,7 @@ impl World {
     pub fn process_chunk_updates(&self, window: &GlWindow) {
         // Step 1: Collect all available chunk updates
         let mut received_chunks = Vec::new();
+        let mut carved_any = false;
         while let Ok(mut chunk) = self.chunk_receiver.try_recv() {
             let key = (chunk.position.0, chunk.position.1, chunk.position.2);
 
@@ -206,21 +277,14 @@ impl World {
                 pending.remove(&key);
             }
 
-            // --- Save out-of-bounds voxels for other chunks ---
-            let out_voxels = std::mem::take(&mut chunk.out_of_bounds_voxels);
-            if !out_voxels.is_empty() {
-                let cs = self.chunk_size as i32;
-                let mut pending = self.pending_voxels.lock().unwrap();
-                for (wx, wy, wz, vtype) in out_voxels {
-                    let target_chunk = (wx.div_euclid(cs), wy.div_euclid(cs), wz.div_euclid(cs));
-                    pending
-                        .entry(target_chunk)
-                        .or_default()
-                        .push((wx, wy, wz, vtype));
-                }
+            if chunk.status == ChunkStatus::Carved {
+                // Not meshed yet, neighbours need its feature starts before it can be decorated
+                let starts = std::mem::take(&mut chunk.feature_starts);
+                self.feature_starts.lock().unwrap().insert(key, starts);
+                carved_any = true;
+            } else {
+                chunk.upload_to_gpu();
             }
-
-            chunk.upload_to_gpu();
             received_chunks.push((key, chunk));
         }
 
@@ -232,29 +296,9 @@ impl World {
             }
         }
 
-        // Step 2.1: Apply pending voxels to loaded chunks
-        {
-            let mut chunks = self.chunks.lock().unwrap();
-            let mut pending = self.pending_voxels.lock().unwrap();
-            let mut to_remesh = Vec::new();
-
-            for (&key, chunk) in chunks.iter_mut() {
-                if let Some(voxels) = pending.remove(&key) {
-                    let cs = self.chunk_size as i32;
-                    for (wx, wy, wz, vtype) in voxels {
-                        let lx = wx.rem_euclid(cs) as usize;
-                        let ly = wy.rem_euclid(cs) as usize;
-                        let lz = wz.rem_euclid(cs) as usize;
-                        chunk.set_voxel(lx, ly, lz, vtype);
-                    }
-                    to_remesh.push(key);
-                }
-            }
-            drop(chunks); // Release lock before sending work
-
-            for (cx, cy, cz) in to_remesh {
-                self.request_remesh(cx, cy, cz);
-            }
+        // Step 2.1: Move chunks with a fully carved neighbourhood to the features stage
+        if carved_any {
+            self.decorate_ready_chunks();
         }
 
         // Step 2.5: Rebuild chunks that need it
@@ -262,7 +306,8 @@ impl World {
             let mut chunks = self.chunks.lock().unwrap();
             let mut to_remesh = Vec::new();
             for (&(cx, cy, cz), chunk) in chunks.iter_mut() {
-                if chunk.needs_rebuild {
+                // Chunks still in the pipeline are meshed when they finish it
+                if chunk.needs_rebuild && chunk.status == ChunkStatus::Meshed {
                     chunk.needs_rebuild = false; // Reset the flag
                     to_remesh.push((cx, cy, cz));
                 }
@@ -273,12 +318,17 @@ impl World {
                 self.request_remesh(cx, cy, cz);
             }
         }
-
-        // Decoration and detail placement removed.
-        // No further steps needed.
     }
 
     pub fn set_voxel_main_thread(&self, wx: i32, wy: i32, wz: i32, voxel_type: VoxelType) -> bool {
+        self.set_block_main_thread(wx, wy, wz, voxel_type, BlockState::default())
+    }
+
+    /// Like `set_voxel_main_thread`, with the state bits of the new block
+    pub fn set_block_main_thread(&self, wx: i32, wy: i32, wz: i32, voxel_type: VoxelType, state: BlockState) -> bool {
+        if !self.within_build_limits(wy) {
+            return false;
+        }
         let cs = self.chunk_size as i32;
         let cx = wx.div_euclid(cs);
         let cy = wy.div_euclid(cs);
@@ -290,8 +340,12 @@ impl World {
         let mut chunks = self.chunks.lock().unwrap();
         let mut updated = false;
 
-        if let Some(chunk) = chunks.get_mut(&(cx, cy, cz)) {
-            chunk.set_voxel(lx, ly, lz, voxel_type);
+        // Chunks still in the pipeline would overwrite the edit with features
+        if let Some(chunk) = chunks
+            .get_mut(&(cx, cy, cz))
+            .filter(|chunk| chunk.status == ChunkStatus::Meshed)
+        {
+            chunk.edit_voxel(lx, ly, lz, voxel_type, state);
             chunk.prepare_mesh();
             chunk.upload_to_gpu();
             updated = true;
@@ -309,7 +363,10 @@ impl World {
 
         for (is_border, (dx, dy, dz)) in borders.iter().copied() {
             if is_border {
-                if let Some(neighbor) = chunks.get_mut(&(cx + dx, cy + dy, cz + dz)) {
+                if let Some(neighbor) = chunks
+                    .get_mut(&(cx + dx, cy + dy, cz + dz))
+                    .filter(|neighbor| neighbor.status == ChunkStatus::Meshed)
+                {
                     neighbor.prepare_mesh();
                     neighbor.upload_to_gpu();
                 }
@@ -319,13 +376,59 @@ impl World {
         updated
     }
 
+    /// Replaces the block with air, or water if it was waterlogged, like
+    /// `set_voxel_main_thread`. Returns the contents of its block entity, or None if the
+    /// block couldn't be broken.
+    pub fn break_block(&self, wx: i32, wy: i32, wz: i32) -> Option<Vec<ItemStack>> {
+        // Block entities are only found in meshed chunks, so within the build limits
+        // emptying one always goes together with breaking its block
+        if !self.within_build_limits(wy) {
+            return None;
+        }
+        let contents = self
+            .with_block_entity(wx, wy, wz, BlockEntity::take_contents)
+            .unwrap_or_default();
+        let left = if self.get_state(wx, wy, wz).waterlogged() { VoxelType::Water } else { VoxelType::Air };
+        self.set_voxel_main_thread(wx, wy, wz, left).then_some(contents)
+    }
+
+    /// Runs `f` on the block entity at the world position, if its chunk is loaded and has one
+    pub fn with_block_entity<R>(&self, wx: i32, wy: i32, wz: i32, f: impl FnOnce(&mut BlockEntity) -> R) -> Option<R> {
+        let cs = self.chunk_size as i32;
+        let (cx, cy, cz) = (wx.div_euclid(cs), wy.div_euclid(cs), wz.div_euclid(cs));
+        let (lx, ly, lz) = (wx.rem_euclid(cs) as usize, wy.rem_euclid(cs) as usize, wz.rem_euclid(cs) as usize);
+
+        let mut chunks = self.chunks.lock().unwrap();
+        chunks
+            .get_mut(&(cx, cy, cz))
+            .filter(|chunk| chunk.status == ChunkStatus::Meshed)
+            .and_then(|chunk| chunk.block_entity_mut(lx, ly, lz))
+            .map(f)
+    }
+
+    /// Ticks the block entities of every loaded chunk. Chunks out on a worker skip the tick.
+    pub fn tick_block_entities(&self, delta_time: f32) {
+        let mut chunks = self.chunks.lock().unwrap();
+        for chunk in chunks.values_mut() {
+            if chunk.status == ChunkStatus::Meshed {
+                chunk.tick_block_entities(delta_time);
+            }
+        }
+    }
+
     pub fn set_voxel_async(&self, wx: i32, wy: i32, wz: i32, voxel_type: VoxelType) -> bool {
+        if !self.within_build_limits(wy) {
+            return false;
+        }
         let cs = self.chunk_size as i32;
         let cx = wx.div_euclid(cs);
         let cy = wy.div_euclid(cs);
         let cz = wz.div_euclid(cs);
 
         let mut chunks = self.chunks.lock().unwrap();
+        if chunks.get(&(cx, cy, cz)).map(|chunk| chunk.status) != Some(ChunkStatus::Meshed) {
+            return false;
+        }
         if let Some(chunk) = chunks.remove(&(cx, cy, cz)) {
             drop(chunks);
             self.work_sender
@@ -348,13 +451,17 @@ impl World {
         voxels: Vec<(i32, i32, i32, VoxelType)>,
         offset: (i32, i32, i32),
     ) {
+        let chunk_size = self.chunk_size as i32;
+        let (offset_x, offset_y, offset_z) = offset;
+
+        let voxels: Vec<_> = voxels
+            .into_iter()
+            .filter(|&(_, y, _, _)| self.within_build_limits(y + offset_y))
+            .collect();
         if voxels.is_empty() {
             return;
         }
 
-        let chunk_size = self.chunk_size as i32;
-        let (offset_x, offset_y, offset_z) = offset;
-
         // Determine which chunks are affected
         let mut chunk_positions = std::collections::HashSet::new();
         for (x, y, z, _) in &voxels {
@@ -372,6 +479,10 @@ impl World {
         {
             let mut chunks = self.chunks.lock().unwrap();
             for &pos in &chunk_positions {
+                let meshed = chunks.get(&pos).map(|chunk| chunk.status) == Some(ChunkStatus::Meshed);
+                if !meshed {
+                    continue;
+                }
                 if let Some(chunk) = chunks.remove(&pos) {
                     chunk_map.insert(pos, chunk);
                 }
@@ -388,6 +499,50 @@ impl World {
             .unwrap();
     }
 
+    /// Whether blocks at height wy may be placed or broken
+    pub fn within_build_limits(&self, wy: i32) -> bool {
+        let settings = &self.preset.settings;
+        wy >= settings.min_build_height && wy < settings.max_build_height
+    }
+
+    /// Lowest and highest chunk y the world generates
+    pub fn chunk_range(&self) -> (i32, i32) {
+        self.preset.settings.chunk_range(self.chunk_size)
+    }
+
+    /// Whether the chunk at pos lies within the build limits and near enough to the
+    /// estimated surface of its column to be worth generating
+    fn within_terrain_range(&self, (cx, cy, cz): (i32, i32, i32)) -> bool {
+        let (min_cy, max_cy) = self.chunk_range();
+        if cy < min_cy || cy > max_cy {
+            return false;
+        }
+
+        let cs = self.chunk_size as i32;
+        let surface = *self
+            .surface_estimates
+            .lock()
+            .unwrap()
+            .entry((cx, cz))
+            .or_insert_with(|| self.generator.surface_estimate(cx * cs + cs / 2, cz * cs + cs / 2));
+        let Some(surface) = surface else {
+            return true;
+        };
+        let surface_cy = surface.div_euclid(cs);
+        let settings = &self.preset.settings;
+        cy <= surface_cy + settings.chunk_margin_above && cy >= surface_cy - settings.chunk_margin_below
+    }
+
+    /// Chunks right next to the player are always generated (within the build limits),
+    /// so digging or flying away from the surface never reaches missing terrain
+    fn should_generate(&self, pos: (i32, i32, i32), player_cy: i32) -> bool {
+        let (min_cy, max_cy) = self.chunk_range();
+        if (pos.1 - player_cy).abs() <= 1 {
+            return pos.1 >= min_cy && pos.1 <= max_cy;
+        }
+        self.within_terrain_range(pos)
+    }
+
     pub fn get_voxel(&self, wx: i32, wy: i32, wz: i32) -> VoxelType {
         let cs = self.chunk_size as i32;
         let cx = wx.div_euclid(cs);
@@ -404,6 +559,101 @@ impl World {
             .unwrap_or(VoxelType::Air)
     }
 
+    pub fn get_state(&self, wx: i32, wy: i32, wz: i32) -> BlockState {
+        let cs = self.chunk_size as i32;
+        let (cx, cy, cz) = (wx.div_euclid(cs), wy.div_euclid(cs), wz.div_euclid(cs));
+        let (lx, ly, lz) = (wx.rem_euclid(cs) as usize, wy.rem_euclid(cs) as usize, wz.rem_euclid(cs) as usize);
+
+        let chunks = self.chunks.lock().unwrap();
+        chunks
+            .get(&(cx, cy, cz))
+            .map(|chunk| chunk.get_state(lx, ly, lz))
+            .unwrap_or_default()
+    }
+
+    /// Runs `f` with a lookup of the block and state at world positions, locking the
+    /// chunks once for all of them rather than once per lookup like `get_voxel`
+    pub fn with_blocks<R>(&self, f: impl FnOnce(&dyn Fn(i32, i32, i32) -> (VoxelType, BlockState)) -> R) -> R {
+        let cs = self.chunk_size as i32;
+        let chunks = self.chunks.lock().unwrap();
+        let block = |wx: i32, wy: i32, wz: i32| {
+            let (cx, cy, cz) = (wx.div_euclid(cs), wy.div_euclid(cs), wz.div_euclid(cs));
+            let (lx, ly, lz) = (wx.rem_euclid(cs) as usize, wy.rem_euclid(cs) as usize, wz.rem_euclid(cs) as usize);
+            chunks
+                .get(&(cx, cy, cz))
+                .map(|chunk| (chunk.get_voxel(lx, ly, lz), chunk.get_state(lx, ly, lz)))
+                .unwrap_or((VoxelType::Air, BlockState::default()))
+        };
+        f(&block)
+    }
+
+    /// Height of the highest non-air block in column (wx, wz), None until the
+    /// column has loaded or if it is empty
+    pub fn surface_height(&self, wx: i32, wz: i32) -> Option<i32> {
+        self.loaded_surface_height(wx, wz).flatten()
+    }
+
+    // Reads the heightmaps of the column's chunks from the top down.
+    // The outer None means a chunk that may hold the surface is still loading.
+    fn loaded_surface_height(&self, wx: i32, wz: i32) -> Option<Option<i32>> {
+        let cs = self.chunk_size as i32;
+        let (cx, cz) = (wx.div_euclid(cs), wz.div_euclid(cs));
+        let (lx, lz) = (wx.rem_euclid(cs) as usize, wz.rem_euclid(cs) as usize);
+        let (min_cy, max_cy) = self.chunk_range();
+
+        let chunks = self.chunks.lock().unwrap();
+        for cy in (min_cy..=max_cy).rev() {
+            match chunks
+                .get(&(cx, cy, cz))
+                .filter(|chunk| chunk.status == ChunkStatus::Meshed)
+            {
+                Some(chunk) => {
+                    if let Some(ly) = chunk.height_at(lx, lz) {
+                        return Some(Some(cy * cs + ly as i32));
+                    }
+                }
+                // Chunks far from the surface are never generated and hold nothing
+                None if self.within_terrain_range((cx, cy, cz)) => return None,
+                None => {}
+            }
+        }
+        Some(None)
+    }
+
+    /// First guess at the spawn point from the generator alone, before any chunk has loaded
+    pub fn estimate_spawn(&self) -> Option<(i32, i32, i32)> {
+        spawn::estimate_spawn(self.generator.as_ref(), &self.preset.settings)
+    }
+
+    /// Looks for the nearest dry, solid, non-leaf surface block in a spawn biome
+    /// within `radius` of (wx, wz) in the loaded terrain
+    pub fn find_spawn(&self, wx: i32, wz: i32, radius: i32) -> SpawnSearch {
+        for (dx, dz) in spawn::spiral(radius, 1) {
+            let (x, z) = (wx + dx, wz + dz);
+            if !spawn::is_spawn_biome(&self.preset.settings, self.biome_at(x, z)) {
+                continue;
+            }
+            match self.loaded_surface_height(x, z) {
+                None => return SpawnSearch::Loading,
+                Some(Some(wy)) if spawn::is_spawn_block(self.get_voxel(x, wy, z)) => {
+                    return SpawnSearch::Found((x, wy, z));
+                }
+                Some(_) => {}
+            }
+        }
+        SpawnSearch::NoneSuitable
+    }
+
+    /// Stores the world spawn in the preset so it is saved with the world
+    pub fn set_spawn(&mut self, (wx, wy, wz): (i32, i32, i32)) {
+        self.preset.spawn = Some([wx, wy, wz]);
+    }
+
+    /// Biome at world column (wx, wz), available whether or not the chunk is loaded
+    pub fn biome_at(&self, wx: i32, wz: i32) -> Biome {
+        self.generator.biome_at(wx, wz)
+    }
+
     pub fn render(&self, window: &GlWindow, shader: &Shader) {
         window.set_depth_testing(ferrousgl::DepthType::LessOrEqual);
         window.set_blend_mode(ferrousgl::BlendMode::None);
@@ -448,12 +698,35 @@ impl World {
     pub fn remove_distant_chunks(&self, cx: i32, cy: i32, cz: i32, render_distance: i32) {
         let mut chunks = self.chunks.lock().unwrap();
 
-        // Remove chunks outside render distance
-        chunks.retain(|&(x, y, z), _| {
+        // Remove chunks outside render distance, keeping their edits and block entities for later
+        let mut saved_chunks = self.saved_chunks.lock().unwrap();
+        chunks.retain(|&(x, y, z), chunk| {
+            let keep = (x - cx).abs() <= render_distance
+                && (y - cy).abs() <= render_distance
+                && (z - cz).abs() <= render_distance;
+            if !keep {
+                if let Some(saved) = chunk.save() {
+                    saved_chunks.insert((x, y, z), saved);
+                }
+            }
+            keep
+        });
+        drop(saved_chunks);
+        drop(chunks);
+
+        self.feature_starts.lock().unwrap().retain(|&(x, y, z), _| {
             (x - cx).abs() <= render_distance
                 && (y - cy).abs() <= render_distance
                 && (z - cz).abs() <= render_distance
         });
+
+        // Columns outside the horizontal render distance won't be needed again soon
+        self.column_cache.retain(|&(x, z)| {
+            (x - cx).abs() <= render_distance && (z - cz).abs() <= render_distance
+        });
+        self.surface_estimates.lock().unwrap().retain(|&(x, z), _| {
+            (x - cx).abs() <= render_distance + 1 && (z - cz).abs() <= render_distance + 1
+        });
     }
 
     pub fn generate_nearest_missing_chunk_simple(&self, cx: i32, cy: i32, cz: i32) -> bool {
@@ -480,7 +753,10 @@ impl World {
                         }
 
                         let pos = (cx + dx, cy + dy, cz + dz);
-                        if !chunks.contains_key(&pos) && !pending.contains(&pos) {
+                        if !chunks.contains_key(&pos)
+                            && !pending.contains(&pos)
+                            && self.should_generate(pos, cy)
+                        {
                             found_any = true;
                             // Track the closest position in this shell
                             if dist_sq < closest_dist_sq {
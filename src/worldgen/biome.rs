@@ -0,0 +1,306 @@
+use crate::{utils::tree_gen::TreeSpecies, voxel::VoxelType, worldgen::settings::WorldGenSettings};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use strum_macros::EnumIter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
+pub enum Biome {
+    Plains,
+    Desert,
+    Mountains,
+    Forest,
+    Jungle,
+    Swamp,
+    Savanna,
+    Tundra,
+    Badlands,
+}
+
+impl Biome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Plains => "Plains",
+            Biome::Desert => "Desert",
+            Biome::Mountains => "Mountains",
+            Biome::Forest => "Forest",
+            Biome::Jungle => "Jungle",
+            Biome::Swamp => "Swamp",
+            Biome::Savanna => "Savanna",
+            Biome::Tundra => "Tundra",
+            Biome::Badlands => "Badlands",
+        }
+    }
+}
+
+/// Climate parameters of a single column, roughly in the range 0..1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Climate {
+    pub temperature: f64,
+    pub humidity: f64,
+    /// Terrain shape axis, low values are mountainous and high values are lowland forests
+    pub continentalness: f64,
+}
+
+/// How the surface block of a biome varies with the surface detail noise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceStyle {
+    /// Always the biome's surface block
+    Uniform,
+    /// Mixes gravel, grass and stone into the surface
+    Rocky,
+    /// Replaces the surface block with the given block where the detail noise is low
+    Patches(VoxelType),
+}
+
+/// A layer of blocks below the surface, with a random thickness per column
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceLayer {
+    pub block: VoxelType,
+    /// Minimum and maximum thickness in blocks
+    pub depth: [u32; 2],
+}
+
+impl SurfaceLayer {
+    pub fn new(block: VoxelType, min_depth: u32, max_depth: u32) -> Self {
+        Self {
+            block,
+            depth: [min_depth, max_depth],
+        }
+    }
+}
+
+/// Climate range and surface rules of one biome
+pub struct BiomeDefinition {
+    pub biome: Biome,
+    pub temperature: Range<f64>,
+    pub humidity: Range<f64>,
+    pub continentalness: Range<f64>,
+    pub surface: VoxelType,
+    pub surface_style: SurfaceStyle,
+    /// Raises or lowers the terrain, blended across biome borders
+    pub height_offset: f64,
+    /// Snow starts at the first height and fully covers the surface at the second
+    pub snow_line: Option<(i32, i32)>,
+    /// Layers below the surface block, top to bottom
+    pub layers: Vec<SurfaceLayer>,
+    /// Layers of the ground below water, starting at the top block
+    pub sea_floor: Vec<SurfaceLayer>,
+    /// Layers of dry ground just above sea level, starting at the top block
+    pub beach: Vec<SurfaceLayer>,
+    /// Tree species with their chance per surface block, checked in order
+    pub trees: Vec<(TreeSpecies, f64)>,
+}
+
+impl BiomeDefinition {
+    pub fn contains(&self, climate: &Climate) -> bool {
+        self.temperature.contains(&climate.temperature)
+            && self.humidity.contains(&climate.humidity)
+            && self.continentalness.contains(&climate.continentalness)
+    }
+
+    /// Chance that a surface block at height wy is covered in snow or ice
+    pub fn snow_probability(&self, wy: i32) -> f32 {
+        match self.snow_line {
+            Some((start, _)) if wy < start => 0.0,
+            // Linear increase from 0 to 1 between the snow line start and end
+            Some((start, end)) => (wy - start) as f32 / (end - start) as f32,
+            None => 0.0,
+        }
+    }
+}
+
+/// All biomes of a world, with climate ranges taken from the world gen settings.
+/// Definitions are checked in order, so narrower climates come first.
+pub struct BiomeRegistry {
+    definitions: Vec<BiomeDefinition>,
+}
+
+impl BiomeRegistry {
+    pub fn new(settings: &WorldGenSettings) -> Self {
+        let any = f64::NEG_INFINITY..f64::INFINITY;
+        let mountains = f64::NEG_INFINITY..settings.mountain_threshold;
+        let lowlands = settings.mountain_threshold..settings.forest_threshold;
+        let forests = settings.forest_threshold..f64::INFINITY;
+        let lowlands_and_forests = settings.mountain_threshold..f64::INFINITY;
+
+        let definitions = vec![
+            BiomeDefinition {
+                biome: Biome::Mountains,
+                temperature: any.clone(),
+                humidity: any.clone(),
+                continentalness: mountains,
+                surface: VoxelType::Stone,
+                surface_style: SurfaceStyle::Rocky,
+                height_offset: 0.0,
+                snow_line: Some((settings.snow_line_start, settings.snow_line_end)),
+                layers: Vec::new(),
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Gravel, 2, 4)],
+                beach: vec![SurfaceLayer::new(VoxelType::Gravel, 2, 3)],
+                trees: vec![(TreeSpecies::Spruce, settings.spruce_chance)],
+            },
+            BiomeDefinition {
+                biome: Biome::Tundra,
+                temperature: f64::NEG_INFINITY..settings.tundra_temperature,
+                humidity: any.clone(),
+                continentalness: lowlands_and_forests.clone(),
+                surface: VoxelType::Snow,
+                surface_style: SurfaceStyle::Patches(VoxelType::Ice),
+                height_offset: 0.0,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Dirt, 2, 4)],
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Gravel, 2, 4)],
+                beach: vec![SurfaceLayer::new(VoxelType::Gravel, 2, 3)],
+                trees: vec![
+                    (TreeSpecies::Spruce, settings.tundra_spruce_chance),
+                    (TreeSpecies::DeadTree, settings.dead_tree_chance),
+                ],
+            },
+            BiomeDefinition {
+                biome: Biome::Badlands,
+                temperature: settings.desert_heat..f64::INFINITY,
+                humidity: f64::NEG_INFINITY..settings.badlands_humidity,
+                continentalness: lowlands.clone(),
+                surface: VoxelType::Sandstone,
+                surface_style: SurfaceStyle::Patches(VoxelType::Sand),
+                height_offset: settings.badlands_height_offset,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Sandstone, 4, 8)],
+                sea_floor: vec![
+                    SurfaceLayer::new(VoxelType::Sand, 1, 2),
+                    SurfaceLayer::new(VoxelType::Sandstone, 2, 3),
+                ],
+                beach: vec![SurfaceLayer::new(VoxelType::Sand, 2, 3)],
+                trees: vec![
+                    (TreeSpecies::Cactus, settings.cactus_chance),
+                    (TreeSpecies::DeadTree, settings.dead_tree_chance),
+                ],
+            },
+            BiomeDefinition {
+                biome: Biome::Savanna,
+                temperature: settings.desert_heat..f64::INFINITY,
+                humidity: settings.savanna_humidity..f64::INFINITY,
+                continentalness: lowlands.clone(),
+                surface: VoxelType::Grass,
+                surface_style: SurfaceStyle::Uniform,
+                height_offset: 0.0,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Dirt, 3, 5)],
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Sand, 2, 3)],
+                beach: vec![SurfaceLayer::new(VoxelType::Sand, 3, 4)],
+                trees: vec![
+                    (TreeSpecies::Walnut, settings.savanna_walnut_chance),
+                    (TreeSpecies::Shrub, settings.shrub_chance),
+                ],
+            },
+            BiomeDefinition {
+                biome: Biome::Desert,
+                temperature: settings.desert_heat..f64::INFINITY,
+                humidity: any.clone(),
+                continentalness: lowlands.clone(),
+                surface: VoxelType::Sand,
+                surface_style: SurfaceStyle::Uniform,
+                height_offset: 0.0,
+                snow_line: None,
+                layers: vec![
+                    SurfaceLayer::new(VoxelType::Sand, 2, 3),
+                    SurfaceLayer::new(VoxelType::Sandstone, 3, 6),
+                ],
+                sea_floor: vec![
+                    SurfaceLayer::new(VoxelType::Sand, 2, 3),
+                    SurfaceLayer::new(VoxelType::Sandstone, 2, 3),
+                ],
+                beach: vec![SurfaceLayer::new(VoxelType::Sand, 3, 4)],
+                trees: vec![
+                    (TreeSpecies::Cactus, settings.cactus_chance),
+                    (TreeSpecies::DeadTree, settings.dead_tree_chance),
+                ],
+            },
+            BiomeDefinition {
+                biome: Biome::Jungle,
+                temperature: settings.jungle_heat..f64::INFINITY,
+                humidity: any.clone(),
+                continentalness: forests.clone(),
+                surface: VoxelType::Grass,
+                surface_style: SurfaceStyle::Uniform,
+                height_offset: 0.0,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Dirt, 3, 5)],
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Sand, 2, 3)],
+                beach: vec![SurfaceLayer::new(VoxelType::Sand, 3, 4)],
+                trees: vec![
+                    (TreeSpecies::Mahogany, settings.mahogany_chance),
+                    (TreeSpecies::Palm, settings.palm_chance),
+                    (TreeSpecies::Shrub, settings.shrub_chance),
+                ],
+            },
+            BiomeDefinition {
+                biome: Biome::Swamp,
+                temperature: any.clone(),
+                humidity: settings.swamp_humidity..f64::INFINITY,
+                continentalness: lowlands_and_forests,
+                surface: VoxelType::Grass,
+                surface_style: SurfaceStyle::Patches(VoxelType::Water),
+                height_offset: settings.swamp_height_offset,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Dirt, 3, 5)],
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Dirt, 2, 3)],
+                beach: Vec::new(),
+                trees: vec![
+                    (TreeSpecies::Mahogany, settings.swamp_mahogany_chance),
+                    (TreeSpecies::DeadTree, settings.dead_tree_chance),
+                ],
+            },
+            BiomeDefinition {
+                biome: Biome::Plains,
+                temperature: any.clone(),
+                humidity: any.clone(),
+                continentalness: lowlands,
+                surface: VoxelType::Grass,
+                surface_style: SurfaceStyle::Uniform,
+                height_offset: 0.0,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Dirt, 3, 5)],
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Sand, 2, 3)],
+                beach: vec![SurfaceLayer::new(VoxelType::Sand, 3, 4)],
+                trees: vec![(TreeSpecies::Shrub, settings.shrub_chance)],
+            },
+            BiomeDefinition {
+                biome: Biome::Forest,
+                temperature: any.clone(),
+                humidity: any,
+                continentalness: forests,
+                surface: VoxelType::Grass,
+                surface_style: SurfaceStyle::Uniform,
+                height_offset: 0.0,
+                snow_line: None,
+                layers: vec![SurfaceLayer::new(VoxelType::Dirt, 3, 5)],
+                sea_floor: vec![SurfaceLayer::new(VoxelType::Gravel, 2, 3)],
+                beach: vec![SurfaceLayer::new(VoxelType::Gravel, 2, 3)],
+                trees: vec![
+                    (TreeSpecies::Walnut, settings.walnut_chance),
+                    (TreeSpecies::Birch, settings.birch_chance),
+                    (TreeSpecies::FallenLog, settings.fallen_log_chance),
+                    (TreeSpecies::Shrub, settings.shrub_chance),
+                ],
+            },
+        ];
+
+        Self { definitions }
+    }
+
+    /// Picks the first biome whose climate ranges contain `climate`
+    pub fn select(&self, climate: &Climate) -> Biome {
+        self.definitions
+            .iter()
+            .find(|definition| definition.contains(climate))
+            .map(|definition| definition.biome)
+            .unwrap_or(Biome::Plains)
+    }
+
+    pub fn get(&self, biome: Biome) -> &BiomeDefinition {
+        self.definitions
+            .iter()
+            .find(|definition| definition.biome == biome)
+            .expect("Biome missing from registry")
+    }
+}
@@ -0,0 +1,291 @@
+use crate::{
+    chunk::Chunk,
+    voxel::VoxelType,
+    worldgen::{
+        random::{RAVINE_SALT, positional_rng},
+        settings::WorldGenSettings,
+    },
+};
+use noise::{NoiseFn, Perlin};
+use rand::Rng;
+use std::f64::consts::{PI, TAU};
+
+// Only natural terrain is carved, so water, ice and trees stay intact
+fn is_carvable(v: VoxelType) -> bool {
+    matches!(
+        v,
+        VoxelType::Stone
+            | VoxelType::Dirt
+            | VoxelType::Grass
+            | VoxelType::Sand
+            | VoxelType::Sandstone
+            | VoxelType::Gravel
+            | VoxelType::Snow
+    )
+}
+
+/// Cuts caves and ravines out of the terrain after the surface pass.
+/// Everything is derived from world coordinates, so carvings line up across chunk borders.
+pub struct Carver {
+    seed: u32,
+    tunnel_a: Perlin,
+    tunnel_b: Perlin,
+    cavern: Perlin,
+    settings: WorldGenSettings,
+}
+
+impl Carver {
+    pub fn new(seed: u32, settings: &WorldGenSettings) -> Self {
+        Self {
+            seed,
+            tunnel_a: Perlin::new(seed.wrapping_add(200)),
+            tunnel_b: Perlin::new(seed.wrapping_add(201)),
+            cavern: Perlin::new(seed.wrapping_add(202)),
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn carve(&self, chunk: &mut Chunk) {
+        self.carve_caves(chunk);
+        self.carve_ravines(chunk);
+    }
+
+    // Carvings below sea level are flooded
+    fn fill_for(&self, wy: i32) -> VoxelType {
+        if wy < self.settings.sea_level {
+            VoxelType::Water
+        } else {
+            VoxelType::Air
+        }
+    }
+
+    /// Worm caves where two 3D noise fields are both close to zero,
+    /// and cheese caverns where a third field is high
+    fn carve_caves(&self, chunk: &mut Chunk) {
+        let settings = &self.settings;
+        let size = chunk.size as i32;
+        let (chunk_x, chunk_y, chunk_z) = chunk.position;
+        let max_y = settings.cave_max_y.max(settings.cavern_max_y);
+        // Skip chunks entirely above the caves
+        if chunk_y * size - 1 > max_y {
+            return;
+        }
+
+        let tunnel_f = settings.cave_frequency;
+        let cavern_f = settings.cavern_frequency;
+
+        for y in -1..=size {
+            let wy = chunk_y * size + y;
+            if wy > max_y {
+                break;
+            }
+            for x in -1..=size {
+                for z in -1..=size {
+                    if !is_carvable(chunk.get_extended_voxel(x, y, z)) {
+                        continue;
+                    }
+                    let wx = (chunk_x * size + x) as f64;
+                    let wz = (chunk_z * size + z) as f64;
+
+                    // Tunnels are squashed vertically so they mostly run sideways
+                    let mut carve = false;
+                    if wy <= settings.cave_max_y {
+                        let a = self.tunnel_a.get([wx * tunnel_f, wy as f64 * tunnel_f * 1.5, wz * tunnel_f]);
+                        if a.abs() < settings.cave_width {
+                            let b = self.tunnel_b.get([wx * tunnel_f, wy as f64 * tunnel_f * 1.5, wz * tunnel_f]);
+                            carve = b.abs() < settings.cave_width;
+                        }
+                    }
+                    if !carve && wy <= settings.cavern_max_y {
+                        carve = self.cavern.get([wx * cavern_f, wy as f64 * cavern_f * 1.6, wz * cavern_f])
+                            > settings.cavern_threshold;
+                    }
+
+                    if carve {
+                        chunk.set_extended_voxel(x, y, z, self.fill_for(wy));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Tall, narrow cuts that follow a wandering path. Ravines may start in any
+    /// chunk column within reach, so each chunk replays its neighbours' ravines
+    /// and only keeps the voxels that fall inside its own bounds.
+    fn carve_ravines(&self, chunk: &mut Chunk) {
+        let settings = &self.settings;
+        let size = chunk.size as i32;
+        let (chunk_x, chunk_y, chunk_z) = chunk.position;
+        let [min_length, max_length] = settings.ravine_length;
+
+        // Ravines drift at most 0.2 blocks per step and are up to 15 blocks tall
+        let vertical_reach = (max_length as f64 * 0.2) as i32 + 16;
+        let bottom = chunk_y * size - 1;
+        let top = chunk_y * size + size;
+        if top < settings.ravine_min_y - vertical_reach || bottom > settings.ravine_max_y + vertical_reach {
+            return;
+        }
+
+        let reach = max_length / size + 1;
+        for ox in chunk_x - reach..=chunk_x + reach {
+            for oz in chunk_z - reach..=chunk_z + reach {
+                let mut rng = positional_rng(self.seed, ox, 0, oz, RAVINE_SALT);
+                if !rng.random_bool(settings.ravine_chance) {
+                    continue;
+                }
+
+                let mut px = (ox * size + rng.random_range(0..size)) as f64;
+                let mut py = rng.random_range(settings.ravine_min_y..=settings.ravine_max_y) as f64;
+                let mut pz = (oz * size + rng.random_range(0..size)) as f64;
+                let length = rng.random_range(min_length..=max_length);
+                let max_radius = rng.random_range(2.0..4.0);
+                let mut yaw: f64 = rng.random_range(0.0..TAU);
+
+                for step in 0..length {
+                    // Widest in the middle, closing up at both ends
+                    let t = step as f64 / length as f64;
+                    let radius = 1.0 + max_radius * (t * PI).sin();
+                    self.carve_ellipsoid(chunk, (px, py, pz), radius, radius * 3.0);
+
+                    yaw += rng.random_range(-0.15..0.15);
+                    px += yaw.cos();
+                    pz += yaw.sin();
+                    py += rng.random_range(-0.2..0.2);
+                }
+            }
+        }
+    }
+
+    fn carve_ellipsoid(&self, chunk: &mut Chunk, center: (f64, f64, f64), radius: f64, height: f64) {
+        let size = chunk.size as i32;
+        let origin = (
+            chunk.position.0 * size,
+            chunk.position.1 * size,
+            chunk.position.2 * size,
+        );
+
+        // Clamp to the extended bounds of the chunk, in local coordinates
+        let x0 = ((center.0 - radius).floor() as i32 - origin.0).max(-1);
+        let x1 = ((center.0 + radius).ceil() as i32 - origin.0).min(size);
+        let y0 = ((center.1 - height).floor() as i32 - origin.1).max(-1);
+        let y1 = ((center.1 + height).ceil() as i32 - origin.1).min(size);
+        let z0 = ((center.2 - radius).floor() as i32 - origin.2).max(-1);
+        let z1 = ((center.2 + radius).ceil() as i32 - origin.2).min(size);
+
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                for z in z0..=z1 {
+                    let dx = ((origin.0 + x) as f64 + 0.5 - center.0) / radius;
+                    let dy = ((origin.1 + y) as f64 + 0.5 - center.1) / height;
+                    let dz = ((origin.2 + z) as f64 + 0.5 - center.2) / radius;
+                    if dx * dx + dy * dy + dz * dz > 1.0 {
+                        continue;
+                    }
+                    if is_carvable(chunk.get_extended_voxel(x, y, z)) {
+                        chunk.set_extended_voxel(x, y, z, self.fill_for(origin.1 + y));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worldgen::testing::{assert_deterministic, stone_chunk};
+
+    const SIZE: usize = 16;
+
+    /// A chunk of solid stone, carved with `settings`
+    fn carved_with(settings: &WorldGenSettings, seed: u32, position: (i32, i32, i32)) -> Chunk {
+        let mut chunk = stone_chunk(position, SIZE);
+        Carver::new(seed, settings).carve(&mut chunk);
+        chunk
+    }
+
+    /// A chunk of solid stone, carved with ravines in every column
+    fn carved(seed: u32, position: (i32, i32, i32)) -> Chunk {
+        let settings = WorldGenSettings {
+            ravine_chance: 1.0,
+            ..Default::default()
+        };
+        carved_with(&settings, seed, position)
+    }
+
+    #[test]
+    fn carving_is_deterministic() {
+        assert_deterministic(7, |seed| carved(seed, (0, -2, 0)).extended_voxels);
+    }
+
+    #[test]
+    fn carvings_below_sea_level_are_flooded() {
+        let sea_level = WorldGenSettings::default().sea_level;
+        let size = SIZE as i32;
+        let mut carved_voxels = [0, 0];
+        for cy in -2..=1 {
+            let chunk = carved(7, (0, cy, 0));
+            for y in 0..size {
+                let wy = cy * size + y;
+                for x in 0..SIZE {
+                    for z in 0..SIZE {
+                        let v = chunk.get_voxel(x, y as usize, z);
+                        if v == VoxelType::Stone {
+                            continue;
+                        }
+                        let expected = if wy < sea_level { VoxelType::Water } else { VoxelType::Air };
+                        assert_eq!(v, expected, "carved at y {}", wy);
+                        carved_voxels[(wy >= sea_level) as usize] += 1;
+                    }
+                }
+            }
+        }
+        assert!(carved_voxels.iter().all(|&count| count > 0), "carved {:?} below and above sea level", carved_voxels);
+    }
+
+    #[test]
+    fn caves_stay_below_their_max_height() {
+        let settings = WorldGenSettings {
+            ravine_chance: 0.0,
+            ..Default::default()
+        };
+        let size = SIZE as i32;
+        // The first chunk wholly above the caves is left alone, the one holding the limit isn't
+        let above = settings.cave_max_y.div_euclid(size) + 1;
+        assert!(carved_with(&settings, 7, (0, above, 0)).extended_voxels.iter().all(|&v| v == VoxelType::Stone));
+        let mut carved_any = false;
+        for cx in 0..4 {
+            let chunk = carved_with(&settings, 7, (cx, above - 1, 0));
+            for y in 0..size {
+                let wy = (above - 1) * size + y;
+                for x in 0..SIZE {
+                    for z in 0..SIZE {
+                        if chunk.get_voxel(x, y as usize, z) != VoxelType::Stone {
+                            assert!(wy <= settings.cave_max_y, "cave carved at y {}", wy);
+                            carved_any = true;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(carved_any, "no caves near the height limit");
+    }
+
+    #[test]
+    fn carvings_line_up_across_chunk_borders() {        let size = SIZE as i32;
+        let left = carved(7, (0, -2, 0));
+        let right = carved(7, (1, -2, 0));
+        // The border of one chunk's extended area is the first layer of its neighbour
+        let mut carved_border = 0;
+        for y in 0..size {
+            for z in 0..size {
+                assert_eq!(left.get_extended_voxel(size, y, z), right.get_extended_voxel(0, y, z));
+                assert_eq!(left.get_extended_voxel(size - 1, y, z), right.get_extended_voxel(-1, y, z));
+                if right.get_extended_voxel(0, y, z) != VoxelType::Stone {
+                    carved_border += 1;
+                }
+            }
+        }
+        assert!(carved_border > 0, "nothing was carved along the border");
+    }
+}
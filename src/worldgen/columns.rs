@@ -0,0 +1,314 @@
+use crate::utils::interpolate::{lerp, smoothstep_mid};
+use crate::worldgen::biome::{Biome, BiomeRegistry, Climate};
+use crate::worldgen::settings::WorldGenSettings;
+use noise::{NoiseFn, Perlin};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 2D noise values of a single (x, z) column
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColumnSample {
+    /// Vertical shift applied to wy before any 3D noise is sampled
+    pub height_offset: f64,
+    pub biome: f64,
+    pub plains: f64,
+    pub mountain: f64,
+    pub heat: f64,
+    pub humidity: f64,
+    /// Distance-like value to the nearest river, 0 in the middle of one
+    pub river: f64,
+}
+
+impl ColumnSample {
+    pub fn climate(&self) -> Climate {
+        Climate {
+            temperature: self.heat,
+            humidity: self.humidity,
+            continentalness: self.biome,
+        }
+    }
+}
+
+/// Samples the 2D noise fields, shared by the column cache and biome queries
+pub struct ColumnSampler {
+    perlin: Perlin,
+    height: Perlin,
+    biome: Perlin,
+    heat: Perlin,
+    humidity: Perlin,
+    river: Perlin,
+    settings: WorldGenSettings,
+}
+
+impl ColumnSampler {
+    pub fn new(seed: u32, settings: &WorldGenSettings) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            height: Perlin::new(seed.wrapping_add(16)),
+            biome: Perlin::new(seed.wrapping_add(32)),
+            heat: Perlin::new(seed.wrapping_add(64)),
+            humidity: Perlin::new(seed.wrapping_add(128)),
+            river: Perlin::new(seed.wrapping_add(256)),
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn sample(&self, wx: i32, wz: i32) -> ColumnSample {
+        let settings = &self.settings;
+        let biome_scale = settings.main_biome_scale;
+        let [biome_w1, biome_w2, biome_w3] = settings.biome_weights;
+        let [heat_f1, heat_f2, heat_f3] = settings.heat_frequencies;
+        let [heat_w1, heat_w2, heat_w3] = settings.heat_weights;
+        let [humidity_f1, humidity_f2, humidity_f3] = settings.humidity_frequencies;
+        let [humidity_w1, humidity_w2, humidity_w3] = settings.humidity_weights;
+
+        // Add global height control noise
+        let mut height_adjustment = self.height.get([
+            wx as f64 * settings.global_height_scale,
+            wz as f64 * settings.global_height_scale,
+        ]);
+        height_adjustment = height_adjustment / 2.0 + 0.5; // Normalize to 0..1
+        height_adjustment = height_adjustment * 0.99;
+        height_adjustment = smoothstep_mid(height_adjustment as f32, 32.0) as f64;
+
+        let mut biome_value1 = self.biome.get([
+            wx as f64 * settings.biome_frequency * biome_scale,
+            wz as f64 * settings.biome_frequency * biome_scale,
+        ]);
+        biome_value1 = biome_value1 / 2.0 + 0.5;
+        let mut biome_value2 = self.biome.get([
+            wx as f64 * settings.biome_frequency * biome_scale,
+            wz as f64 * 0.00 * biome_scale,
+        ]);
+        biome_value2 = biome_value2 / 2.0 + 0.5;
+        let mut biome_value3 = self.biome.get([
+            wx as f64 * settings.biome_detail_frequency * biome_scale,
+            wz as f64 * settings.biome_detail_frequency * biome_scale,
+        ]);
+        biome_value3 = biome_value3 / 2.0 + 0.5;
+
+        // Also used as the forest noise, both sample the same field
+        let plains_noise = self.perlin.get([
+            wx as f64 * settings.plains_frequency,
+            wz as f64 * settings.plains_frequency,
+        ]);
+
+        let mountain_noise_2 = self.perlin.get([
+            wx as f64 * settings.mountain_base_frequency,
+            wz as f64 * settings.mountain_base_frequency,
+        ]);
+
+        let mut heat_value_1 = self.heat.get([wx as f64 * heat_f1, wz as f64 * heat_f1]);
+        heat_value_1 = heat_value_1 / 2.0 + 0.5;
+        let mut heat_value_2 = self.heat.get([wx as f64 * heat_f2, wz as f64 * heat_f2]);
+        heat_value_2 = heat_value_2 / 2.0 + 0.5;
+        let mut heat_value_3 = self.heat.get([wx as f64 * heat_f3, wz as f64 * heat_f3]);
+        heat_value_3 = heat_value_3 / 2.0 + 0.5;
+
+        let mut humidity_value_1 =
+            self.humidity.get([wx as f64 * humidity_f1, wz as f64 * humidity_f1]);
+        humidity_value_1 = humidity_value_1 / 2.0 + 0.5;
+        let mut humidity_value_2 =
+            self.humidity.get([wx as f64 * humidity_f2, wz as f64 * humidity_f2]);
+        humidity_value_2 = humidity_value_2 / 2.0 + 0.5;
+        let mut humidity_value_3 =
+            self.humidity.get([wx as f64 * humidity_f3, wz as f64 * humidity_f3]);
+        humidity_value_3 = humidity_value_3 / 2.0 + 0.5;
+
+        // Ridged noise: rivers run along the zero crossings
+        let river = self
+            .river
+            .get([wx as f64 * settings.river_frequency, wz as f64 * settings.river_frequency])
+            .abs();
+
+        ColumnSample {
+            height_offset: height_adjustment * settings.global_height_factor,
+            biome: biome_value1 * biome_w1 + biome_value2 * biome_w2 + biome_value3 * biome_w3,
+            plains: plains_noise / 2.0 + 0.5,
+            mountain: mountain_noise_2 / 2.0 + 0.5,
+            heat: heat_value_1 * heat_w1 + heat_value_2 * heat_w2 + heat_value_3 * heat_w3,
+            humidity: humidity_value_1 * humidity_w1
+                + humidity_value_2 * humidity_w2
+                + humidity_value_3 * humidity_w3,
+            river,
+        }
+    }
+
+    /// Like `sample`, including the blended biome height offset that `ColumnData` adds.
+    /// Meant for single lookups outside of chunk generation.
+    pub fn sample_blended(&self, biomes: &BiomeRegistry, wx: i32, wz: i32) -> ColumnSample {
+        let mut sample = self.sample(wx, wz);
+        sample.height_offset += BiomeBlend::covering(self, biomes, (wx, wz), 0).height_offset(wx, wz);
+        sample
+    }
+}
+
+/// 2D noise fields and biomes that only depend on (x, z), sampled once per column
+/// for the extended ((size+2) x (size+2)) footprint of a chunk column.
+pub struct ColumnData {
+    pub width: usize, // size + 2
+    pub samples: Vec<ColumnSample>,
+    pub biomes: Vec<Biome>,
+}
+
+impl ColumnData {
+    /// Samples all 2D fields for the chunk column at (chunk_x, chunk_z)
+    pub fn generate(
+        sampler: &ColumnSampler,
+        biomes: &BiomeRegistry,
+        size: usize,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Self {
+        let width = size + 2;
+        let mut data = Self {
+            width,
+            samples: vec![ColumnSample::default(); width * width],
+            biomes: vec![Biome::Plains; width * width],
+        };
+
+        let blend = BiomeBlend::new(sampler, biomes, size, chunk_x, chunk_z);
+
+        for x in 0..width {
+            for z in 0..width {
+                let wx = (chunk_x * size as i32) + (x as i32 - 1);
+                let wz = (chunk_z * size as i32) + (z as i32 - 1);
+                let idx = data.index(x, z);
+
+                let mut sample = sampler.sample(wx, wz);
+                data.biomes[idx] = biomes.select(&sample.climate());
+                sample.height_offset += blend.height_offset(wx, wz);
+                data.samples[idx] = sample;
+            }
+        }
+
+        data
+    }
+
+    // Index into the extended column arrays
+    pub fn index(&self, x: usize, z: usize) -> usize {
+        x + self.width * z
+    }
+}
+
+// Biome height offsets are sampled on a coarse grid and averaged over
+// (2 * BLEND_RADIUS + 1)² cells so terrain eases between biomes
+const BLEND_SPACING: i32 = 4;
+const BLEND_RADIUS: i32 = 2;
+
+/// Smoothly blended biome height offsets around one chunk column
+struct BiomeBlend {
+    min_cell: (i32, i32), // World cell of grid index 0
+    cells: usize,
+    offsets: Vec<f64>,
+}
+
+impl BiomeBlend {
+    fn new(
+        sampler: &ColumnSampler,
+        biomes: &BiomeRegistry,
+        size: usize,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Self {
+        // Cover every extended column
+        let first_x = chunk_x * size as i32 - 1;
+        let first_z = chunk_z * size as i32 - 1;
+        let last_cell = (size as i32 + 1).div_euclid(BLEND_SPACING) + 1;
+        Self::covering(sampler, biomes, (first_x, first_z), last_cell)
+    }
+
+    /// Grid starting at the cell of world column `first`, spanning `last_cell` more cells
+    /// plus the next cell for interpolation and the blur radius
+    fn covering(
+        sampler: &ColumnSampler,
+        biomes: &BiomeRegistry,
+        first: (i32, i32),
+        last_cell: i32,
+    ) -> Self {
+        let min_cell = (
+            first.0.div_euclid(BLEND_SPACING) - BLEND_RADIUS,
+            first.1.div_euclid(BLEND_SPACING) - BLEND_RADIUS,
+        );
+        let cells = (last_cell + 1 + 2 * BLEND_RADIUS + 1) as usize;
+
+        let mut offsets = vec![0.0; cells * cells];
+        for cx in 0..cells {
+            for cz in 0..cells {
+                let wx = (min_cell.0 + cx as i32) * BLEND_SPACING;
+                let wz = (min_cell.1 + cz as i32) * BLEND_SPACING;
+                let biome = biomes.select(&sampler.sample(wx, wz).climate());
+                offsets[cx + cells * cz] = biomes.get(biome).height_offset;
+            }
+        }
+
+        Self {
+            min_cell,
+            cells,
+            offsets,
+        }
+    }
+
+    // Average offset of the cells around grid cell (cx, cz)
+    fn blurred(&self, cx: usize, cz: usize) -> f64 {
+        let mut total = 0.0;
+        for dx in -BLEND_RADIUS..=BLEND_RADIUS {
+            for dz in -BLEND_RADIUS..=BLEND_RADIUS {
+                let x = (cx as i32 + dx) as usize;
+                let z = (cz as i32 + dz) as usize;
+                total += self.offsets[x + self.cells * z];
+            }
+        }
+        total / ((2 * BLEND_RADIUS + 1) * (2 * BLEND_RADIUS + 1)) as f64
+    }
+
+    /// Bilinearly interpolated, blurred height offset at world column (wx, wz)
+    fn height_offset(&self, wx: i32, wz: i32) -> f64 {
+        let cx = (wx.div_euclid(BLEND_SPACING) - self.min_cell.0) as usize;
+        let cz = (wz.div_euclid(BLEND_SPACING) - self.min_cell.1) as usize;
+        let tx = wx.rem_euclid(BLEND_SPACING) as f64 / BLEND_SPACING as f64;
+        let tz = wz.rem_euclid(BLEND_SPACING) as f64 / BLEND_SPACING as f64;
+
+        let top = lerp(self.blurred(cx, cz) as f32, self.blurred(cx + 1, cz) as f32, tx as f32);
+        let bottom = lerp(
+            self.blurred(cx, cz + 1) as f32,
+            self.blurred(cx + 1, cz + 1) as f32,
+            tx as f32,
+        );
+        lerp(top, bottom, tz as f32) as f64
+    }
+}
+
+/// Shares column data between vertically stacked chunks, keyed by (chunk_x, chunk_z)
+pub struct ColumnCache {
+    columns: Mutex<HashMap<(i32, i32), Arc<ColumnData>>>,
+}
+
+impl ColumnCache {
+    pub fn new() -> Self {
+        Self {
+            columns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached column or generates it without holding the lock,
+    /// so workers on different columns never wait on each other.
+    pub fn get_or_insert_with(
+        &self,
+        key: (i32, i32),
+        generate: impl FnOnce() -> ColumnData,
+    ) -> Arc<ColumnData> {
+        if let Some(column) = self.columns.lock().unwrap().get(&key) {
+            return Arc::clone(column);
+        }
+
+        let column = Arc::new(generate());
+        let mut columns = self.columns.lock().unwrap();
+        Arc::clone(columns.entry(key).or_insert(column))
+    }
+
+    pub fn retain(&self, mut keep: impl FnMut(&(i32, i32)) -> bool) {
+        let mut columns = self.columns.lock().unwrap();
+        columns.retain(|key, _| keep(key));
+    }
+}
@@ -0,0 +1,696 @@
+use crate::{
+    chunk::Chunk,
+    utils::{
+        interpolate::{cosine_interpolate, power_curve},
+        tree_gen::TreeRegistry,
+    },
+    voxel::VoxelType,
+    worldgen::{
+        biome::{Biome, BiomeDefinition, BiomeRegistry, SurfaceLayer, SurfaceStyle},
+        carvers::Carver,
+        columns::{ColumnCache, ColumnData, ColumnSample, ColumnSampler},
+        features::{FeatureKind, FeatureStart},
+        generator::TerrainGenerator,
+        ores::OrePlacer,
+        random::{DECORATION_SALT, SURFACE_SALT, positional_rng},
+        settings::WorldGenSettings,
+        structures::{StructurePlanner, TerrainProbe},
+    },
+};
+use noise::{NoiseFn, Perlin};
+use rand::Rng;
+use std::sync::Arc;
+
+/// The original CraftMine terrain: plains, mountains and forests blended by biome noise
+pub struct DefaultGenerator {
+    seed: u32,
+    settings: WorldGenSettings,
+    sampler: ColumnSampler,
+    biomes: BiomeRegistry,
+    carver: Carver,
+    ores: OrePlacer,
+    structures: StructurePlanner,
+    trees: TreeRegistry,
+}
+
+impl DefaultGenerator {
+    pub fn new(seed: u32, settings: WorldGenSettings) -> Self {
+        Self {
+            seed,
+            sampler: ColumnSampler::new(seed, &settings),
+            biomes: BiomeRegistry::new(&settings),
+            carver: Carver::new(seed, &settings),
+            ores: OrePlacer::new(seed, &settings.ores),
+            structures: StructurePlanner::new(seed, &settings),
+            trees: TreeRegistry::new(&settings.trees),
+            settings,
+        }
+    }
+
+    fn columns(&self, chunk: &Chunk, cache: &ColumnCache) -> Arc<ColumnData> {
+        let (world_x, _, world_z) = chunk.position;
+        cache.get_or_insert_with((world_x, world_z), || {
+            ColumnData::generate(&self.sampler, &self.biomes, chunk.size, world_x, world_z)
+        })
+    }
+
+    /// Stone, air or water at a world position, before any surface rules
+    fn terrain_at(
+        &self,
+        perlin: &Perlin,
+        sample: &ColumnSample,
+        wx: i32,
+        wy: i32,
+        wz: i32,
+    ) -> VoxelType {
+        let settings = &self.settings;
+        let height_offset = sample.height_offset;
+        let biome_value = sample.biome;
+        let plains_noise = sample.plains;
+        let mountain_noise_2 = sample.mountain;
+        // The forest noise samples the same field as the plains noise
+        let forest_noise = plains_noise;
+
+        let adjusted_wy = (wy as f64 - height_offset) / settings.vertical_scale;
+
+        let plains = plains_noise
+            - (adjusted_wy - settings.plains_base_height) * settings.plains_falloff;
+
+        let mut v = if biome_value > settings.mountain_threshold
+            && biome_value < settings.forest_threshold
+        {
+            if plains > 0.0 {
+                VoxelType::Stone
+            } else {
+                VoxelType::Air
+            }
+        } else if biome_value < settings.mountain_threshold {
+            // Mountains are the only shape that needs 3D noise
+            let [offset_x, offset_z] = settings.mountain_noise_offset;
+            let mut mountain_noise = perlin.get([
+                wx as f64 * settings.mountain_frequency + offset_x,
+                adjusted_wy * settings.mountain_vertical_frequency,
+                wz as f64 * settings.mountain_frequency + offset_z,
+            ]);
+            mountain_noise = mountain_noise / 2.0 + 0.5;
+            let mut mountain_noise_3 = perlin.get([
+                wx as f64 * settings.mountain_detail_frequency,
+                adjusted_wy * settings.mountain_detail_vertical_frequency,
+                wz as f64 * settings.mountain_detail_frequency,
+            ]) - (adjusted_wy - settings.mountain_base_height) * settings.mountain_detail_falloff;
+            mountain_noise_3 = mountain_noise_3 / 2.0 + 0.5;
+            let [shape_weight, base_weight, detail_weight] = settings.mountain_weights;
+            let mountain = (mountain_noise * shape_weight
+                + mountain_noise_2 * base_weight
+                + mountain_noise_3 * detail_weight)
+                - (adjusted_wy - settings.mountain_base_height) * settings.mountain_falloff;
+
+            let biome_blend =
+                (settings.mountain_threshold - biome_value) / settings.mountain_threshold;
+            if cosine_interpolate(
+                plains as f32,
+                mountain as f32,
+                power_curve(biome_blend as f32, settings.biome_blend_exponent),
+            ) > 0.0
+            {
+                VoxelType::Stone
+            } else {
+                VoxelType::Air
+            }
+        } else {
+            let forest = (forest_noise)
+                - (adjusted_wy - settings.forest_base_height) * settings.forest_falloff;
+
+            let biome_blend = (biome_value - settings.forest_threshold)
+                / (1.0 - settings.forest_threshold);
+            let blend_amount =
+                power_curve(biome_blend as f32, settings.biome_blend_exponent);
+            if cosine_interpolate(
+                plains as f32,
+                forest as f32,
+                power_curve(blend_amount, settings.forest_blend_exponent),
+            ) > 0.0
+            {
+                VoxelType::Stone
+            } else {
+                VoxelType::Air
+            }
+        };
+
+        // Rivers cut a valley down to sea level, the water rule below fills the channel
+        if let Some(cut) = self.river_cut(sample.river) {
+            if v == VoxelType::Stone && wy as f64 > cut {
+                v = VoxelType::Air;
+            }
+        }
+
+        // Place water below sea level
+        if wy < settings.sea_level && v == VoxelType::Air {
+            v = VoxelType::Water;
+        }
+
+        v
+    }
+
+    /// Highest terrain block of a column and whether water covers it, found without
+    /// generating any chunk. Coarse steps down to the terrain, then back up to its top.
+    fn terrain_top(&self, wx: i32, wz: i32) -> Option<(i32, bool)> {
+        let perlin = Perlin::new(self.seed);
+        let sample = self.sampler.sample_blended(&self.biomes, wx, wz);
+        let top = self.settings.max_build_height;
+        let bottom = self.settings.min_build_height;
+
+        let mut wy = top;
+        while wy > bottom {
+            match self.terrain_at(&perlin, &sample, wx, wy, wz) {
+                VoxelType::Air | VoxelType::Water => wy -= 4,
+                _ => {
+                    let mut above = self.terrain_at(&perlin, &sample, wx, wy + 1, wz);
+                    while above == VoxelType::Stone && wy < top {
+                        wy += 1;
+                        above = self.terrain_at(&perlin, &sample, wx, wy + 1, wz);
+                    }
+                    return Some((wy, above == VoxelType::Water));
+                }
+            }
+        }
+        None
+    }
+
+    /// Height above which a river removes all terrain, None away from rivers.
+    /// The channel dips below sea level and the valley walls rise quadratically.
+    fn river_cut(&self, river: f64) -> Option<f64> {
+        let settings = &self.settings;
+        let sea_level = settings.sea_level as f64;
+        if river < settings.river_width {
+            let t = river / settings.river_width;
+            Some(sea_level - settings.river_depth * (1.0 - t * t))
+        } else if river < settings.river_valley_width {
+            let t = (river - settings.river_width)
+                / (settings.river_valley_width - settings.river_width);
+            Some(sea_level + t * t * settings.river_valley_height)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the stone below each surface with the biome's layers, based on the
+    /// depth below the nearest air or water. Runs of ground reaching past the top of
+    /// the chunk are continued with the density function, so layers line up across
+    /// vertically stacked chunks.
+    fn place_layers(&self, chunk: &mut Chunk, columns: &ColumnData, perlin: &Perlin) {
+        let size = chunk.size as i32;
+        let sea_level = self.settings.sea_level;
+        let (world_x, world_y, world_z) = chunk.position;
+
+        for x in 0..chunk.size + 2 {
+            for z in 0..chunk.size + 2 {
+                let idx = columns.index(x, z);
+                let sample = columns.samples[idx];
+                let biome = self.biomes.get(columns.biomes[idx]);
+                let wx = world_x * size + x as i32 - 1;
+                let wz = world_z * size + z as i32 - 1;
+                let layers =
+                    ColumnLayers::roll(self.seed, wx, wz, biome, self.settings.snow_cap_depth);
+                let max_depth = layers.max_depth();
+
+                // Count the ground above the chunk, up to the deepest layer
+                let top_wy = world_y * size + size;
+                let mut depth = 0;
+                let mut underwater = false;
+                loop {
+                    let v = self.terrain_at(perlin, &sample, wx, top_wy + 1 + depth as i32, wz);
+                    if !is_ground(v) {
+                        underwater = v == VoxelType::Water;
+                        break;
+                    }
+                    depth += 1;
+                    if depth > max_depth {
+                        break;
+                    }
+                }
+                let mut surface_wy = top_wy + depth as i32;
+
+                for y in (-1..=size).rev() {
+                    let wy = world_y * size + y;
+                    let v = chunk.get_extended_voxel(x as i32 - 1, y, z as i32 - 1);
+                    if !is_ground(v) {
+                        depth = 0;
+                        underwater = v == VoxelType::Water;
+                        surface_wy = wy - 1;
+                        continue;
+                    }
+
+                    let beach = !underwater
+                        && surface_wy >= sea_level - 1
+                        && surface_wy <= sea_level + self.settings.beach_height;
+                    if beach {
+                        // Beaches replace the surface block too
+                        if let Some(block) = ColumnLayers::find(&layers.beach, depth) {
+                            chunk.set_extended_voxel(x as i32 - 1, y, z as i32 - 1, block);
+                        }
+                    } else if v == VoxelType::Stone {
+                        let snow_capped = biome.snow_probability(surface_wy) >= 1.0;
+                        if let Some(block) = layers.block_at(depth, underwater, snow_capped) {
+                            chunk.set_extended_voxel(x as i32 - 1, y, z as i32 - 1, block);
+                        }
+                    }
+                    depth += 1;
+                }
+            }
+        }
+    }
+}
+
+// Natural terrain blocks, trees and fluids end a run of ground
+fn is_ground(v: VoxelType) -> bool {
+    matches!(
+        v,
+        VoxelType::Stone
+            | VoxelType::Dirt
+            | VoxelType::Grass
+            | VoxelType::Sand
+            | VoxelType::Sandstone
+            | VoxelType::Gravel
+            | VoxelType::Snow
+            | VoxelType::Ice
+    )
+}
+
+/// Layer thicknesses of one column, rolled from its position
+struct ColumnLayers {
+    layers: Vec<(VoxelType, u32)>,
+    sea_floor: Vec<(VoxelType, u32)>,
+    beach: Vec<(VoxelType, u32)>,
+    snow_cap: u32,
+}
+
+impl ColumnLayers {
+    fn roll(seed: u32, wx: i32, wz: i32, biome: &BiomeDefinition, snow_cap_depth: [u32; 2]) -> Self {
+        let mut rng = positional_rng(seed, wx, 0, wz, SURFACE_SALT);
+        let mut roll = |layer: &SurfaceLayer| {
+            let [min, max] = layer.depth;
+            (layer.block, rng.random_range(min..=max.max(min)))
+        };
+        let layers = biome.layers.iter().map(&mut roll).collect();
+        let sea_floor = biome.sea_floor.iter().map(&mut roll).collect();
+        let [min_snow, max_snow] = snow_cap_depth;
+        let (_, snow_cap) = roll(&SurfaceLayer::new(VoxelType::Snow, min_snow, max_snow));
+        let beach = biome.beach.iter().map(&mut roll).collect();
+        Self {
+            layers,
+            sea_floor,
+            beach,
+            snow_cap,
+        }
+    }
+
+    // Deepest block that any of the layer stacks can reach
+    fn max_depth(&self) -> u32 {
+        let total = |layers: &[(VoxelType, u32)]| layers.iter().map(|(_, depth)| depth).sum::<u32>();
+        (total(&self.layers) + 1)
+            .max(total(&self.sea_floor))
+            .max(total(&self.beach))
+            .max(self.snow_cap + 1)
+    }
+
+    /// Block at `depth` below the surface block, None keeps the stone. Land layers
+    /// start below the surface block, sea floors replace the top block as well.
+    fn block_at(&self, depth: u32, underwater: bool, snow_capped: bool) -> Option<VoxelType> {
+        if underwater {
+            return Self::find(&self.sea_floor, depth);
+        }
+        if depth == 0 {
+            return None;
+        }
+        if snow_capped {
+            return (depth <= self.snow_cap).then_some(VoxelType::Snow);
+        }
+        Self::find(&self.layers, depth - 1)
+    }
+
+    fn find(layers: &[(VoxelType, u32)], depth: u32) -> Option<VoxelType> {
+        let mut bottom = 0;
+        for &(block, thickness) in layers {
+            bottom += thickness;
+            if depth < bottom {
+                return Some(block);
+            }
+        }
+        None
+    }
+}
+
+impl TerrainProbe for DefaultGenerator {
+    fn biome(&self, wx: i32, wz: i32) -> Biome {
+        self.biomes.select(&self.sampler.sample(wx, wz).climate())
+    }
+
+    fn dry_surface(&self, wx: i32, wz: i32) -> Option<i32> {
+        match self.terrain_top(wx, wz) {
+            Some((wy, false)) => Some(wy),
+            _ => None,
+        }
+    }
+
+    fn is_solid(&self, wx: i32, wy: i32, wz: i32) -> bool {
+        let perlin = Perlin::new(self.seed);
+        let sample = self.sampler.sample_blended(&self.biomes, wx, wz);
+        self.terrain_at(&perlin, &sample, wx, wy, wz) == VoxelType::Stone
+    }
+}
+
+impl TerrainGenerator for DefaultGenerator {
+    /// Generates stone, air and water for both main and extended areas.
+    /// 2D fields come from the column cache, only true 3D noise is sampled per voxel.
+    fn density(&self, chunk: &mut Chunk, columns: &ColumnCache) {
+        let (world_x, world_y, world_z) = chunk.position;
+        let columns = self.columns(chunk, columns);
+        let perlin = Perlin::new(self.seed);
+
+        for x in 0..chunk.size + 2 {
+            for z in 0..chunk.size + 2 {
+                let sample = columns.samples[columns.index(x, z)];
+                for y in 0..chunk.size + 2 {
+                    let wx = (world_x * chunk.size as i32) + (x as i32 - 1);
+                    let wy = (world_y * chunk.size as i32) + (y as i32 - 1);
+                    let wz = (world_z * chunk.size as i32) + (z as i32 - 1);
+                    let v = self.terrain_at(&perlin, &sample, wx, wy, wz);
+
+                    let ext_idx = x + (chunk.size + 2) * (y + (chunk.size + 2) * z);
+                    chunk.extended_voxels[ext_idx] = v;
+
+                    // Only store in main voxels if within bounds
+                    if x > 0 && y > 0 && z > 0 && x <= chunk.size && y <= chunk.size && z <= chunk.size
+                    {
+                        let idx = chunk.index(x - 1, y - 1, z - 1);
+                        chunk.voxels[idx] = v;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replaces exposed stone with the biome's surface and layers, and picks tree starts.
+    /// Every roll is seeded by the block position, so the extended border matches the
+    /// neighbouring chunk.
+    fn surface(&self, chunk: &mut Chunk, columns: &ColumnCache) {
+        let (world_x, world_y, world_z) = chunk.position;
+        let columns = self.columns(chunk, columns);
+        let perlin = Perlin::new(self.seed);
+
+        chunk.feature_starts.clear();
+
+        for x in 0..chunk.size + 2 {
+            for y in 0..chunk.size + 2 {
+                for z in 0..chunk.size + 2 {
+                    let ext_idx = x + (chunk.size + 2) * (y + (chunk.size + 2) * z);
+                    if chunk.extended_voxels[ext_idx] == VoxelType::Stone {
+                        // Check if this block is adjacent to air (surface block)
+                        let mut is_surface = false;
+                        // Check all 6 directions
+                        for (dx, dy, dz) in &[
+                            (1, 0, 0),
+                            (-1, 0, 0),
+                            (0, 1, 0),
+                            (0, -1, 0),
+                            (0, 0, 1),
+                            (0, 0, -1),
+                        ] {
+                            let nx = x as i32 + dx;
+                            let ny = y as i32 + dy;
+                            let nz = z as i32 + dz;
+
+                            if nx >= 0
+                                && ny >= 0
+                                && nz >= 0
+                                && nx < (chunk.size + 2) as i32
+                                && ny < (chunk.size + 2) as i32
+                                && nz < (chunk.size + 2) as i32
+                            {
+                                let neighbor_idx = nx as usize
+                                    + (chunk.size + 2)
+                                        * (ny as usize + (chunk.size + 2) * nz as usize);
+                                if chunk.extended_voxels[neighbor_idx] == VoxelType::Air {
+                                    is_surface = true;
+                                    break;
+                                }
+                            } else {
+                                // Edge of the chunk - consider it surface
+                                is_surface = false;
+                                break;
+                            }
+                        }
+
+                        if is_surface {
+                            let wx = (world_x * chunk.size as i32) + (x as i32 - 1);
+                            let wy = (world_y * chunk.size as i32) + (y as i32 - 1);
+                            let wz = (world_z * chunk.size as i32) + (z as i32 - 1);
+
+                            let biome = self.biomes.get(columns.biomes[columns.index(x, z)]);
+                            let snow_probability = biome.snow_probability(wy);
+                            let mut rng = positional_rng(self.seed, wx, wy, wz, DECORATION_SALT);
+
+                            let mut mountain_detail_noise = 0.0;
+                            if biome.surface_style != SurfaceStyle::Uniform || snow_probability > 0.0 {
+                                let mut frequency = 0.03;
+                                let mut amplitude = 1.0;
+                                let persistence = 0.5;
+
+                                for _ in 0..3 {
+                                    mountain_detail_noise += perlin.get([
+                                        wx as f64 * frequency,
+                                        wy as f64 * frequency,
+                                        wz as f64 * frequency,
+                                    ]) * amplitude;
+
+                                    frequency *= 2.0;
+                                    amplitude *= persistence;
+                                }
+                            }
+
+                            // Random chance based on snow probability
+                            let surface = if snow_probability > 0.0
+                                && rng.random_range(0.0..1.0) < snow_probability
+                            {
+                                if mountain_detail_noise > 0.0 {
+                                    VoxelType::Snow
+                                } else {
+                                    VoxelType::Ice
+                                }
+                            } else {
+                                match biome.surface_style {
+                                    SurfaceStyle::Uniform => biome.surface,
+                                    SurfaceStyle::Rocky => {
+                                        if mountain_detail_noise > 0.3333 {
+                                            VoxelType::Gravel
+                                        } else if mountain_detail_noise < -0.3333 {
+                                            VoxelType::Grass
+                                        } else {
+                                            VoxelType::Stone
+                                        }
+                                    }
+                                    SurfaceStyle::Patches(patch) => {
+                                        if mountain_detail_noise < -0.3333 {
+                                            patch
+                                        } else {
+                                            biome.surface
+                                        }
+                                    }
+                                }
+                            };
+                            chunk.extended_voxels[ext_idx] = surface;
+
+                            // Trees only grow below the snow line, and only the chunk
+                            // owning the block starts them
+                            let in_chunk = x > 0
+                                && y > 0
+                                && z > 0
+                                && x <= chunk.size
+                                && y <= chunk.size
+                                && z <= chunk.size;
+                            if in_chunk && snow_probability <= 0.0 {
+                                for &(species, chance) in &biome.trees {
+                                    if rng.random_bool(chance) {
+                                        chunk.feature_starts.push(FeatureStart {
+                                            kind: FeatureKind::Tree(species),
+                                            position: (wx, wy + 1, wz),
+                                        });
+                                        break;
+                                    }
+                                }
+                            }
+
+                            // Update main voxels if within bounds
+                            if x > 0
+                                && y > 0
+                                && z > 0
+                                && x <= chunk.size
+                                && y <= chunk.size
+                                && z <= chunk.size
+                            {
+                                let idx = chunk.index(x - 1, y - 1, z - 1);
+                                chunk.voxels[idx] = chunk.extended_voxels[ext_idx];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Layers below the surface
+        self.place_layers(chunk, &columns, &perlin);
+    }
+
+    fn carve(&self, chunk: &mut Chunk) {
+        self.carver.carve(chunk);
+
+        // Drop trees that lost their ground to a cave or ravine
+        let size = chunk.size as i32;
+        let origin = (
+            chunk.position.0 * size,
+            chunk.position.1 * size,
+            chunk.position.2 * size,
+        );
+        let mut starts = std::mem::take(&mut chunk.feature_starts);
+        starts.retain(|start| {
+            let (x, y, z) = start.position;
+            let (lx, ly, lz) = (x - origin.0, y - origin.1, z - origin.2);
+            is_ground(chunk.get_extended_voxel(lx, ly - 1, lz))
+                && chunk.get_extended_voxel(lx, ly, lz) == VoxelType::Air
+        });
+        chunk.feature_starts = starts;
+    }
+
+    /// Ore veins, structures, then trees. Each chunk generates every start in its
+    /// neighbourhood and keeps only the voxels inside its own extended area.
+    fn decorate(&self, chunk: &mut Chunk, starts: &[FeatureStart]) {
+        self.ores.place(chunk, |wx, wz| self.biome_at(wx, wz));
+        self.structures.place(chunk, self);
+
+        let size = chunk.size as i32;
+        let origin = (
+            chunk.position.0 * size,
+            chunk.position.1 * size,
+            chunk.position.2 * size,
+        );
+        // Inclusive bounds of the extended area in world coordinates
+        let min = (origin.0 - 1, origin.1 - 1, origin.2 - 1);
+        let max = (origin.0 + size, origin.1 + size, origin.2 + size);
+        for start in starts {
+            let voxels = start.generate(self.seed, &self.trees);
+            if !voxels.intersects(min, max) {
+                continue;
+            }
+            for (x, y, z, vtype) in voxels.iter() {
+                chunk.set_extended_voxel(x - origin.0, y - origin.1, z - origin.2, vtype);
+            }
+        }
+    }
+
+    fn biome_at(&self, wx: i32, wz: i32) -> Biome {
+        self.biomes.select(&self.sampler.sample(wx, wz).climate())
+    }
+
+    fn surface_estimate(&self, wx: i32, wz: i32) -> Option<i32> {
+        self.terrain_top(wx, wz).map(|(wy, _)| wy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worldgen::testing::assert_deterministic;
+
+    const SEED: u32 = 12345;
+
+    /// Density and surface of the chunk at `position`, with a fresh column cache since
+    /// the cache is keyed by chunk position
+    fn surfaced(generator: &DefaultGenerator, position: (i32, i32, i32), size: usize) -> Chunk {
+        let mut chunk = Chunk::new(position.0, position.1, position.2, size);
+        let columns = ColumnCache::new();
+        generator.density(&mut chunk, &columns);
+        generator.surface(&mut chunk, &columns);
+        chunk
+    }
+
+    #[test]
+    fn layers_do_not_depend_on_chunk_boundaries() {
+        let generator = DefaultGenerator::new(SEED, WorldGenSettings::default());
+        let surface = generator.surface_estimate(8, 8).expect("terrain at the origin");
+        // A 32 block chunk around the surface against the two 16 block chunks it covers
+        let cy = surface.div_euclid(32);
+        let tall = surfaced(&generator, (0, cy, 0), 32);
+        let lower = surfaced(&generator, (0, cy * 2, 0), 16);
+        let upper = surfaced(&generator, (0, cy * 2 + 1, 0), 16);
+
+        let mut layered = 0;
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in 0..32 {
+                    let half = if y < 16 { &lower } else { &upper };
+                    let v = tall.get_voxel(x, y, z);
+                    assert_eq!(v, half.get_voxel(x, y % 16, z), "at ({}, {}, {})", x, y, z);
+                    if !matches!(v, VoxelType::Air | VoxelType::Water | VoxelType::Stone) {
+                        layered += 1;
+                    }
+                }
+            }
+        }
+        assert!(layered > 0, "no surface or layer blocks in the compared chunks");
+    }
+
+    #[test]
+    fn river_cut_dips_below_sea_level_and_rises_into_the_valley() {
+        let settings = WorldGenSettings::default();
+        let generator = DefaultGenerator::new(SEED, settings.clone());
+        let sea_level = settings.sea_level as f64;
+
+        assert_eq!(generator.river_cut(0.0), Some(sea_level - settings.river_depth));
+        assert_eq!(generator.river_cut(settings.river_width), Some(sea_level));
+        let valley = (settings.river_width + settings.river_valley_width) / 2.0;
+        let valley_cut = generator.river_cut(valley).expect("cut inside the valley");
+        assert!(valley_cut > sea_level && valley_cut < sea_level + settings.river_valley_height);
+        assert_eq!(generator.river_cut(settings.river_valley_width), None);
+
+        // Deeper into the channel is never higher
+        let mut previous = f64::MIN;
+        for i in 0..100 {
+            let cut = generator.river_cut(settings.river_valley_width * i as f64 / 100.0).unwrap();
+            assert!(cut >= previous);
+            previous = cut;
+        }
+    }
+
+    #[test]
+    fn river_columns_are_cut_down_and_flooded() {
+        let settings = WorldGenSettings::default();
+        let generator = DefaultGenerator::new(SEED, settings.clone());
+        let mut flooded = 0;
+        for wx in (0..4096).step_by(8) {
+            let sample = generator.sampler.sample_blended(&generator.biomes, wx, 0);
+            let Some(cut) = generator.river_cut(sample.river) else {
+                continue;
+            };
+            let (top, covered) = generator.terrain_top(wx, 0).expect("terrain under the river");
+            assert!(top as f64 <= cut, "terrain at {} rises to {} above the cut at {}", wx, top, cut);
+            // The channel itself is below sea level, so it fills with water
+            if sample.river < settings.river_width / 2.0 {
+                assert!(top < settings.sea_level && covered, "river at {} isn't flooded", wx);
+                flooded += 1;
+            }
+        }
+        assert!(flooded > 0, "no river crosses the sampled line");
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_terrain() {
+        let surface = DefaultGenerator::new(SEED, WorldGenSettings::default())
+            .surface_estimate(8, 8)
+            .expect("terrain at the origin");
+        let position = (0, surface.div_euclid(16), 0);
+        assert_deterministic(SEED, |seed| {
+            surfaced(&DefaultGenerator::new(seed, WorldGenSettings::default()), position, 16).extended_voxels
+        });
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    utils::{
+        tree_gen::{TreeRegistry, TreeSpecies},
+        voxel_set::CompactVoxels,
+    },
+    worldgen::random::{TREE_SALT, positional_rng},
+};
+
+/// What grows from a feature start
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeatureKind {
+    Tree(TreeSpecies),
+}
+
+/// A feature chosen by the chunk that contains `position`, placed later during
+/// the features stage of every chunk it reaches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeatureStart {
+    pub kind: FeatureKind,
+    pub position: (i32, i32, i32),
+}
+
+impl FeatureStart {
+    /// Voxels of the feature in world coordinates, identical for every chunk that asks
+    pub fn generate(&self, seed: u32, trees: &TreeRegistry) -> CompactVoxels {
+        let (x, y, z) = self.position;
+        match self.kind {
+            FeatureKind::Tree(species) => {
+                let mut rng = positional_rng(seed, x, y, z, TREE_SALT);
+                trees.get(species).generate(self.position, &mut rng)
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{
+    chunk::Chunk,
+    worldgen::{biome::Biome, columns::ColumnCache, features::FeatureStart},
+};
+
+/// Shapes the terrain of a freshly created chunk, one stage at a time (see `pipeline`).
+/// Implementations run on the chunk worker threads, so they must be Send + Sync.
+pub trait TerrainGenerator: Send + Sync {
+    /// Fills the main and extended voxels of `chunk` based on `chunk.position`
+    fn density(&self, chunk: &mut Chunk, columns: &ColumnCache);
+
+    /// Replaces exposed terrain with surface blocks and records feature starts
+    /// in `chunk.feature_starts`
+    fn surface(&self, _chunk: &mut Chunk, _columns: &ColumnCache) {}
+
+    /// Cuts caves and other holes into the terrain
+    fn carve(&self, _chunk: &mut Chunk) {}
+
+    /// Places the parts of `starts` (from this chunk and its neighbours) that fall
+    /// inside the extended area of `chunk`
+    fn decorate(&self, _chunk: &mut Chunk, _starts: &[FeatureStart]) {}
+
+    /// Biome of the column at world coordinates (wx, wz)
+    fn biome_at(&self, _wx: i32, _wz: i32) -> Biome {
+        Biome::Plains
+    }
+
+    /// Rough height of the terrain surface at (wx, wz), used to skip chunks far above
+    /// or below it. None generates the whole vertical chunk range.
+    fn surface_estimate(&self, _wx: i32, _wz: i32) -> Option<i32> {
+        None
+    }
+}
@@ -0,0 +1,16 @@
+pub mod biome;
+pub mod carvers;
+pub mod columns;
+pub mod default_generator;
+pub mod features;
+pub mod generator;
+pub mod ores;
+pub mod pipeline;
+pub mod presets;
+pub mod random;
+pub mod settings;
+pub mod spawn;
+pub mod structure_templates;
+pub mod structures;
+#[cfg(test)]
+pub mod testing;
@@ -0,0 +1,191 @@
+use crate::{
+    chunk::Chunk,
+    worldgen::{
+        biome::Biome,
+        random::{ORE_SALT, positional_rng},
+        settings::OreSettings,
+    },
+};
+use rand::Rng;
+
+/// Places ore veins as random-walk blobs inside their host blocks.
+/// Vein origins are seeded per chunk, and each chunk replays the veins of its
+/// neighbours so veins crossing a chunk border are identical on both sides.
+pub struct OrePlacer {
+    seed: u32,
+    ores: Vec<OreSettings>,
+}
+
+impl OrePlacer {
+    pub fn new(seed: u32, ores: &[OreSettings]) -> Self {
+        Self {
+            seed,
+            ores: ores.to_vec(),
+        }
+    }
+
+    /// `biome_at` is only asked for veins of ores with a biome restriction
+    pub fn place(&self, chunk: &mut Chunk, biome_at: impl Fn(i32, i32) -> Biome) {
+        if self.ores.is_empty() {
+            return;
+        }
+        let (chunk_x, chunk_y, chunk_z) = chunk.position;
+
+        // Veins are much smaller than a chunk, so only direct neighbours can reach into this one
+        for ox in chunk_x - 1..=chunk_x + 1 {
+            for oy in chunk_y - 1..=chunk_y + 1 {
+                for oz in chunk_z - 1..=chunk_z + 1 {
+                    self.place_veins_from(chunk, (ox, oy, oz), &biome_at);
+                }
+            }
+        }
+    }
+
+    // Replays every vein that starts in the chunk at `origin`
+    fn place_veins_from(
+        &self,
+        chunk: &mut Chunk,
+        origin: (i32, i32, i32),
+        biome_at: &impl Fn(i32, i32) -> Biome,
+    ) {
+        let size = chunk.size as i32;
+        let origin_bottom = origin.1 * size;
+        let origin_top = origin_bottom + size - 1;
+        let mut rng = positional_rng(self.seed, origin.0, origin.1, origin.2, ORE_SALT);
+
+        for ore in &self.ores {
+            // Only depends on the origin chunk, so the random stream stays deterministic
+            if ore.max_y < origin_bottom || ore.min_y > origin_top {
+                continue;
+            }
+            let min_y = ore.min_y.max(origin_bottom);
+            let max_y = ore.max_y.min(origin_top);
+
+            for _ in 0..ore.veins_per_chunk {
+                let mut x = origin.0 * size + rng.random_range(0..size);
+                let mut y = rng.random_range(min_y..=max_y);
+                let mut z = origin.2 * size + rng.random_range(0..size);
+                let [min_size, max_size] = ore.vein_size;
+                let vein_size = rng.random_range(min_size..=max_size.max(min_size));
+
+                // Always draw the walk so the stream stays aligned, even for skipped veins
+                let mut steps = Vec::with_capacity(vein_size as usize);
+                for _ in 0..vein_size {
+                    steps.push((x, y, z));
+                    x += rng.random_range(-1..=1);
+                    y += rng.random_range(-1..=1);
+                    z += rng.random_range(-1..=1);
+                }
+
+                let Some(&(start_x, _, start_z)) = steps.first() else {
+                    continue;
+                };
+                if !ore.biomes.is_empty() && !ore.biomes.contains(&biome_at(start_x, start_z)) {
+                    continue;
+                }
+
+                for (wx, wy, wz) in steps {
+                    Self::place_ore(chunk, ore, wx, wy, wz);
+                }
+            }
+        }
+    }
+
+    fn place_ore(chunk: &mut Chunk, ore: &OreSettings, wx: i32, wy: i32, wz: i32) {
+        let size = chunk.size as i32;
+        let lx = wx - chunk.position.0 * size;
+        let ly = wy - chunk.position.1 * size;
+        let lz = wz - chunk.position.2 * size;
+        // Also fill the border so neighbouring faces are culled correctly
+        if lx < -1 || ly < -1 || lz < -1 || lx > size || ly > size || lz > size {
+            return;
+        }
+        if ore.replaces.contains(&chunk.get_extended_voxel(lx, ly, lz)) {
+            chunk.set_extended_voxel(lx, ly, lz, ore.block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        voxel::VoxelType,
+        worldgen::{
+            settings::WorldGenSettings,
+            testing::{assert_deterministic, stone_chunk},
+        },
+    };
+
+    const SIZE: usize = 16;
+
+    fn with_ores(seed: u32, position: (i32, i32, i32)) -> Chunk {
+        let mut chunk = stone_chunk(position, SIZE);
+        OrePlacer::new(seed, &WorldGenSettings::default().ores).place(&mut chunk, |_, _| Biome::Mountains);
+        chunk
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_ores() {
+        assert_deterministic(3, |seed| with_ores(seed, (0, -3, 0)).extended_voxels);
+    }
+
+    #[test]
+    fn veins_stay_within_their_depth_range() {
+        // Veins start between min_y and max_y and wander at most a block per step from there
+        let ore = OreSettings {
+            block: VoxelType::CopperOre,
+            vein_size: [3, 3],
+            veins_per_chunk: 40,
+            min_y: 4,
+            max_y: 9,
+            replaces: vec![VoxelType::Stone],
+            biomes: Vec::new(),
+        };
+        let placer = OrePlacer::new(3, std::slice::from_ref(&ore));
+        let size = SIZE as i32;
+        let mut found = 0;
+        for cy in -1..=1 {
+            let mut chunk = stone_chunk((0, cy, 0), SIZE);
+            placer.place(&mut chunk, |_, _| Biome::Plains);
+            for y in 0..size {
+                let wy = cy * size + y;
+                for x in 0..SIZE {
+                    for z in 0..SIZE {
+                        if chunk.get_voxel(x, y as usize, z) == VoxelType::CopperOre {
+                            assert!((ore.min_y - 2..=ore.max_y + 2).contains(&wy), "ore at y {}", wy);
+                            found += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(found > 0, "no ore was placed");
+    }
+
+    #[test]
+    fn veins_replayed_from_a_neighbour_match() {
+        let size = SIZE as i32;
+        let below = with_ores(3, (0, -3, 0));
+        let above = with_ores(3, (0, -2, 0));
+        let mut border_ores = 0;
+        for x in 0..size {
+            for z in 0..size {
+                if above.get_extended_voxel(x, -1, z) != VoxelType::Stone {
+                    border_ores += 1;
+                }
+                assert_eq!(below.get_extended_voxel(x, size, z), above.get_extended_voxel(x, 0, z));
+                assert_eq!(below.get_extended_voxel(x, size - 1, z), above.get_extended_voxel(x, -1, z));
+            }
+        }
+        assert!(border_ores > 0, "no vein crosses the border");
+    }
+
+    #[test]
+    fn biome_restricted_ores_skip_other_biomes() {
+        let mut chunk = stone_chunk((0, -4, 0), SIZE);
+        OrePlacer::new(3, &WorldGenSettings::default().ores).place(&mut chunk, |_, _| Biome::Plains);
+        assert!(!chunk.extended_voxels.contains(&VoxelType::AmethystOre));
+        assert!(with_ores(3, (0, -4, 0)).extended_voxels.contains(&VoxelType::AmethystOre));
+    }
+}
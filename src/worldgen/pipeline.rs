@@ -0,0 +1,148 @@
+use crate::{
+    chunk::Chunk,
+    worldgen::{columns::ColumnCache, features::FeatureStart, generator::TerrainGenerator},
+};
+
+/// How far a chunk has come through world generation, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChunkStatus {
+    Empty,
+    Density,
+    Surface,
+    Carved,
+    Features,
+    Lit,
+    Meshed,
+}
+
+/// Runs the stages that only need the chunk itself: density, surface and carvers.
+/// Afterwards the chunk's feature starts are known and its neighbours may decorate.
+pub fn generate_terrain(generator: &dyn TerrainGenerator, chunk: &mut Chunk, columns: &ColumnCache) {
+    generator.density(chunk, columns);
+    chunk.status = ChunkStatus::Density;
+    generator.surface(chunk, columns);
+    chunk.status = ChunkStatus::Surface;
+    generator.carve(chunk);
+    chunk.status = ChunkStatus::Carved;
+}
+
+/// Places features, lights and meshes a carved chunk. `starts` holds the feature
+/// starts of the chunk and all of its neighbours, in `neighbourhood` order.
+pub fn finish_chunk(generator: &dyn TerrainGenerator, chunk: &mut Chunk, starts: &[FeatureStart]) {
+    generator.decorate(chunk, starts);
+    chunk.rebuild_heightmap();
+    chunk.status = ChunkStatus::Features;
+    // There is no light engine yet, so lighting is a pass-through stage
+    chunk.status = ChunkStatus::Lit;
+    chunk.prepare_mesh();
+    chunk.status = ChunkStatus::Meshed;
+}
+
+/// The chunk at `pos` and its 26 neighbours, always in the same order so features
+/// overlapping each other are placed identically no matter which chunk loaded first
+pub fn neighbourhood(pos: (i32, i32, i32)) -> impl Iterator<Item = (i32, i32, i32)> {
+    (-1..=1).flat_map(move |dx| {
+        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (pos.0 + dx, pos.1 + dy, pos.2 + dz)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::tree_gen::{TreeRegistry, TreeSpecies},
+        worldgen::{
+            default_generator::DefaultGenerator, features::FeatureKind, settings::WorldGenSettings, testing::assert_deterministic,
+        },
+    };
+    use std::collections::HashMap;
+
+    const SIZE: usize = 16;
+
+    /// Runs two horizontally adjacent chunks through the whole pipeline, each with the
+    /// feature starts of its own neighbourhood, and returns them with every start used.
+    /// The left chunk also gets a tree right at the border, so one always crosses it.
+    fn decorated_pair(seed: u32) -> (Chunk, Chunk, Vec<FeatureStart>) {
+        let settings = WorldGenSettings::default();
+        let generator = DefaultGenerator::new(seed, settings);
+        let columns = ColumnCache::new();
+        let surface = generator.surface_estimate(SIZE as i32, 8).expect("terrain near the origin");
+        let left = (0, surface.div_euclid(SIZE as i32), 0);
+        let right = (1, left.1, 0);
+
+        let mut carved = HashMap::new();
+        for pos in neighbourhood(left).chain(neighbourhood(right)) {
+            carved.entry(pos).or_insert_with(|| {
+                let mut chunk = Chunk::new(pos.0, pos.1, pos.2, SIZE);
+                generate_terrain(&generator, &mut chunk, &columns);
+                chunk
+            });
+        }
+        let border_tree = FeatureStart {
+            kind: FeatureKind::Tree(TreeSpecies::Walnut),
+            position: (SIZE as i32 - 1, surface + 1, 8),
+        };
+        let starts_around = |pos: (i32, i32, i32)| -> Vec<FeatureStart> {
+            neighbourhood(pos)
+                .flat_map(|n| {
+                    let extra = (n == left).then_some(border_tree);
+                    carved[&n].feature_starts.iter().copied().chain(extra)
+                })
+                .collect()
+        };
+        let finish = |pos: (i32, i32, i32)| {
+            let mut chunk = Chunk::new(pos.0, pos.1, pos.2, SIZE);
+            generate_terrain(&generator, &mut chunk, &columns);
+            finish_chunk(&generator, &mut chunk, &starts_around(pos));
+            chunk
+        };
+
+        let mut all_starts = starts_around(left);
+        all_starts.extend(starts_around(right));
+        (finish(left), finish(right), all_starts)
+    }
+
+    #[test]
+    fn chunks_go_through_every_stage() {
+        let (left, right, _) = decorated_pair(99);
+        assert_eq!(left.status, ChunkStatus::Meshed);
+        assert_eq!(right.status, ChunkStatus::Meshed);
+    }
+
+    #[test]
+    fn same_seed_decorates_identically() {
+        assert_deterministic(99, |seed| {
+            let (left, _, starts) = decorated_pair(seed);
+            (left.extended_voxels, starts)
+        });
+    }
+
+    #[test]
+    fn features_replayed_from_a_neighbour_match_the_original() {
+        let seed = 99;
+        let (left, right, starts) = decorated_pair(seed);
+        let trees = TreeRegistry::new(&WorldGenSettings::default().trees);
+        let size = SIZE as i32;
+        let (origin_y, left_x) = (left.position.1 * size, left.position.0 * size);
+
+        // Every feature voxel on the shared border must agree between the chunk that owns
+        // it and the neighbour that only sees it in its extended area
+        let mut compared = 0;
+        for start in &starts {
+            for (x, y, z, _) in start.generate(seed, &trees).iter() {
+                let (lx, ly, lz) = (x - left_x, y - origin_y, z);
+                if !(0..size).contains(&ly) || !(0..size).contains(&lz) {
+                    continue;
+                }
+                if lx == size {
+                    assert_eq!(left.get_extended_voxel(lx, ly, lz), right.get_extended_voxel(0, ly, lz));
+                    compared += 1;
+                } else if lx == size - 1 {
+                    assert_eq!(left.get_extended_voxel(lx, ly, lz), right.get_extended_voxel(-1, ly, lz));
+                    compared += 1;
+                }
+            }
+        }
+        assert!(compared > 0, "the border tree doesn't cross the border");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{
+    chunk::Chunk,
+    voxel::VoxelType,
+    worldgen::{
+        biome::Biome, columns::ColumnCache, default_generator::DefaultGenerator,
+        features::FeatureStart, generator::TerrainGenerator, settings::WorldGenSettings,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Superflat world built from a stack of layers, starting at y = 0
+pub struct FlatGenerator {
+    column: Vec<VoxelType>, // One entry per layer block, bottom to top
+}
+
+impl FlatGenerator {
+    pub const DEFAULT_LAYERS: &'static str = "1×Stone,3×Dirt,1×Grass";
+
+    pub fn new(layers: &[(u32, VoxelType)]) -> Self {
+        let mut column = Vec::new();
+        for &(count, voxel_type) in layers {
+            for _ in 0..count {
+                column.push(voxel_type);
+            }
+        }
+        Self { column }
+    }
+
+    /// Parses a layer list such as "1×Stone,3×Dirt,1×Grass" (bottom to top).
+    /// `x` or `*` may be used instead of `×`, and the count may be omitted. The layers
+    /// may not stack higher than `max_height`, the top of the build limits.
+    pub fn from_layers(layers: &str, max_height: i32) -> Result<Self, String> {
+        let mut parsed = Vec::new();
+        let mut height = 0u64;
+        for layer in layers.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+            let (count, name) = match layer.split_once(['×', 'x', '*']) {
+                Some((count, name)) if count.trim().chars().all(|c| c.is_ascii_digit()) => {
+                    let count = count
+                        .trim()
+                        .parse::<u32>()
+                        .map_err(|e| format!("Invalid layer count in '{}': {}", layer, e))?;
+                    (count, name.trim())
+                }
+                _ => (1, layer),
+            };
+            let voxel_type = VoxelType::from_str(name)
+                .map_err(|_| format!("Unknown block '{}' in flat layers", name))?;
+            height += count as u64;
+            if height > max_height.max(0) as u64 {
+                return Err(format!("Flat layers are higher than the build limit of {}", max_height));
+            }
+            parsed.push((count, voxel_type));
+        }
+        Ok(Self::new(&parsed))
+    }
+}
+
+impl TerrainGenerator for FlatGenerator {
+    fn density(&self, chunk: &mut Chunk, _columns: &ColumnCache) {
+        let world_y = chunk.position.1 * chunk.size as i32;
+        let top = self.column.len() as i32;
+        // Nothing to do for chunks entirely above or below the layers
+        if world_y + (chunk.size as i32) < 0 || world_y - 1 >= top {
+            return;
+        }
+
+        for y in 0..chunk.size + 2 {
+            let wy = world_y + (y as i32 - 1);
+            if wy < 0 || wy >= top {
+                continue;
+            }
+            let v = self.column[wy as usize];
+            for x in 0..chunk.size + 2 {
+                for z in 0..chunk.size + 2 {
+                    chunk.set_extended_voxel(x as i32 - 1, y as i32 - 1, z as i32 - 1, v);
+                }
+            }
+        }
+    }
+
+    fn surface_estimate(&self, _wx: i32, _wz: i32) -> Option<i32> {
+        (!self.column.is_empty()).then(|| self.column.len() as i32 - 1)
+    }
+}
+
+/// Leaves every chunk empty, used for measuring engine overhead
+pub struct VoidGenerator;
+
+impl TerrainGenerator for VoidGenerator {
+    fn density(&self, _chunk: &mut Chunk, _columns: &ColumnCache) {}
+}
+
+/// The default terrain stretched vertically for towering mountains and deep valleys
+pub struct AmplifiedGenerator {
+    inner: DefaultGenerator,
+}
+
+impl AmplifiedGenerator {
+    pub fn new(seed: u32, mut settings: WorldGenSettings) -> Self {
+        settings.vertical_scale *= 2.5;
+        Self {
+            inner: DefaultGenerator::new(seed, settings),
+        }
+    }
+}
+
+impl TerrainGenerator for AmplifiedGenerator {
+    fn density(&self, chunk: &mut Chunk, columns: &ColumnCache) {
+        self.inner.density(chunk, columns);
+    }
+
+    fn surface(&self, chunk: &mut Chunk, columns: &ColumnCache) {
+        self.inner.surface(chunk, columns);
+    }
+
+    fn carve(&self, chunk: &mut Chunk) {
+        self.inner.carve(chunk);
+    }
+
+    fn decorate(&self, chunk: &mut Chunk, starts: &[FeatureStart]) {
+        self.inner.decorate(chunk, starts);
+    }
+
+    fn biome_at(&self, wx: i32, wz: i32) -> Biome {
+        self.inner.biome_at(wx, wz)
+    }
+
+    fn surface_estimate(&self, wx: i32, wz: i32) -> Option<i32> {
+        self.inner.surface_estimate(wx, wz)
+    }
+}
+
+/// A world preset file: which generator to use and how to tune it.
+/// Saved alongside the world with the seed filled in so it regenerates identically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldPreset {
+    /// "default", "amplified", "void", "flat" or "flat:<layers>" (e.g. "flat:1×Stone,3×Dirt,1×Grass")
+    pub generator: String,
+    #[serde(default)]
+    pub seed: Option<u32>,
+    #[serde(default)]
+    pub settings: WorldGenSettings,
+    /// Block the player spawns on top of, found on first start
+    #[serde(default)]
+    pub spawn: Option<[i32; 3]>,
+}
+
+impl WorldPreset {
+    pub fn named(generator: &str) -> Self {
+        Self {
+            generator: generator.to_string(),
+            seed: None,
+            settings: WorldGenSettings::default(),
+            spawn: None,
+        }
+    }
+
+    /// Reads a preset file, rejecting settings the generator would fail on
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = std::fs::read_to_string(path)?;
+        let preset: Self = serde_json::from_str(&data)?;
+        preset.settings.validate()?;
+        Ok(preset)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Creates the terrain generator described by this preset
+    pub fn build_generator(&self, seed: u32) -> Result<Arc<dyn TerrainGenerator>, String> {
+        let (name, options) = self
+            .generator
+            .split_once(':')
+            .unwrap_or((self.generator.as_str(), ""));
+        match name.to_ascii_lowercase().as_str() {
+            "default" => Ok(Arc::new(DefaultGenerator::new(seed, self.settings.clone()))),
+            "amplified" => Ok(Arc::new(AmplifiedGenerator::new(seed, self.settings.clone()))),
+            "void" => Ok(Arc::new(VoidGenerator)),
+            "flat" => {
+                let layers = if options.is_empty() {
+                    FlatGenerator::DEFAULT_LAYERS
+                } else {
+                    options
+                };
+                Ok(Arc::new(FlatGenerator::from_layers(layers, self.settings.max_build_height)?))
+            }
+            _ => Err(format!("Unknown world generator '{}'", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_HEIGHT: i32 = 384;
+
+    fn column(layers: &str) -> Result<Vec<VoxelType>, String> {
+        FlatGenerator::from_layers(layers, MAX_HEIGHT).map(|flat| flat.column)
+    }
+
+    #[test]
+    fn bundled_presets_load() {
+        for preset in ["assets/presets/default.json", "assets/presets/flat.json"] {
+            WorldPreset::load(Path::new(preset)).unwrap();
+        }
+    }
+
+    #[test]
+    fn counts_with_x_or_times_sign() {
+        assert_eq!(column("3xStone").unwrap(), vec![VoxelType::Stone; 3]);
+        assert_eq!(column("3×Stone").unwrap(), vec![VoxelType::Stone; 3]);
+        assert_eq!(column("2*Dirt, 1×Grass").unwrap(), vec![VoxelType::Dirt, VoxelType::Dirt, VoxelType::Grass]);
+    }
+
+    #[test]
+    fn missing_count_is_one_layer() {
+        assert_eq!(column("Stone").unwrap(), vec![VoxelType::Stone]);
+    }
+
+    #[test]
+    fn empty_count_is_rejected() {
+        assert!(column("xStone").is_err());
+    }
+
+    #[test]
+    fn unknown_blocks_are_rejected() {
+        assert!(column("1×Stone,2×Cheese").is_err());
+    }
+
+    #[test]
+    fn layers_above_the_build_limit_are_rejected() {
+        assert!(column("4000000000xStone").is_err());
+        assert!(column("300×Stone,85×Dirt").is_err());
+        assert_eq!(column("300×Stone,84×Dirt").unwrap().len(), MAX_HEIGHT as usize);
+    }
+}
@@ -0,0 +1,50 @@
+use rand::SeedableRng;
+use rand_pcg::Pcg32;
+
+// Salts keep the random streams of different features independent
+pub const RAVINE_SALT: u64 = 1;
+pub const ORE_SALT: u64 = 2;
+pub const SURFACE_SALT: u64 = 3;
+pub const DECORATION_SALT: u64 = 4;
+pub const TREE_SALT: u64 = 5;
+pub const STRUCTURE_SALT: u64 = 6;
+pub const DECAY_SALT: u64 = 7;
+
+/// Deterministic RNG for a world position, so features come out the same
+/// no matter which chunk is generated first
+pub fn positional_rng(seed: u32, x: i32, y: i32, z: i32, salt: u64) -> Pcg32 {
+    let mut hash = splitmix64(seed as u64 ^ salt.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    for v in [x, y, z] {
+        hash = splitmix64(hash ^ v as u32 as u64);
+    }
+    Pcg32::seed_from_u64(hash)
+}
+
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worldgen::testing::assert_deterministic;
+    use rand::Rng;
+
+    fn draws(seed: u32, position: (i32, i32, i32), salt: u64) -> Vec<u32> {
+        let mut rng = positional_rng(seed, position.0, position.1, position.2, salt);
+        (0..8).map(|_| rng.random()).collect()
+    }
+
+    #[test]
+    fn seed_position_and_salt_all_change_the_stream() {
+        assert_deterministic(42, |seed| draws(seed, (10, -5, 3), ORE_SALT));
+        let base = draws(42, (10, -5, 3), ORE_SALT);
+        assert_ne!(base, draws(42, (11, -5, 3), ORE_SALT));
+        assert_ne!(base, draws(42, (10, -5, 3), TREE_SALT));
+        // Swapped coordinates are different positions
+        assert_ne!(draws(42, (1, 2, 3), ORE_SALT), draws(42, (3, 2, 1), ORE_SALT));
+    }
+}
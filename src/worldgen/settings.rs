@@ -0,0 +1,380 @@
+use crate::{utils::tree_gen::TreeDefinition, voxel::VoxelType, worldgen::biome::Biome};
+use serde::{Deserialize, Serialize};
+
+/// One kind of ore vein placed after the carvers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OreSettings {
+    pub block: VoxelType,
+    /// Minimum and maximum number of blocks per vein
+    pub vein_size: [u32; 2],
+    pub veins_per_chunk: u32,
+    /// Veins start between these heights
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Blocks the ore may replace
+    #[serde(default = "default_ore_hosts")]
+    pub replaces: Vec<VoxelType>,
+    /// Biomes the vein may start in, empty means everywhere
+    #[serde(default)]
+    pub biomes: Vec<Biome>,
+}
+
+fn default_ore_hosts() -> Vec<VoxelType> {
+    vec![VoxelType::Stone]
+}
+
+/// Tunable noise and biome parameters of the default terrain generator.
+/// Missing fields in a preset file fall back to the built-in values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldGenSettings {
+    // --- Global height ---
+    pub global_height_scale: f64, // Adjust this for larger/smaller features
+    pub global_height_factor: f64, // Adjust this for stronger/weaker effect
+    /// Stretches all terrain features along the y axis
+    pub vertical_scale: f64,
+    /// Air below this height is filled with water
+    pub sea_level: i32,
+
+    // --- World height ---
+    /// Lowest block that can be generated or edited, for every generator
+    pub min_build_height: i32,
+    /// Blocks can only be placed below this height
+    pub max_build_height: i32,
+    /// Chunks further than this many chunks above or below the surface estimate are
+    /// only generated when the player is next to them
+    pub chunk_margin_above: i32,
+    pub chunk_margin_below: i32,
+
+    // --- Biome noise ---
+    pub main_biome_scale: f64,
+    pub biome_frequency: f64,
+    pub biome_detail_frequency: f64,
+    pub biome_weights: [f64; 3],
+    pub heat_frequencies: [f64; 3],
+    pub heat_weights: [f64; 3],
+    pub humidity_frequencies: [f64; 3],
+    pub humidity_weights: [f64; 3],
+
+    // --- Terrain shape ---
+    pub plains_frequency: f64,
+    pub plains_base_height: f64,
+    pub plains_falloff: f64,
+    pub mountain_frequency: f64,
+    pub mountain_vertical_frequency: f64,
+    pub mountain_base_frequency: f64,
+    pub mountain_detail_frequency: f64,
+    pub mountain_detail_vertical_frequency: f64,
+    pub mountain_detail_falloff: f64,
+    /// Shifts the 3D mountain noise away from the detail noise sampled from the same field
+    pub mountain_noise_offset: [f64; 2],
+    /// Weights of the 3D shape, 2D base and 3D detail mountain noises
+    pub mountain_weights: [f64; 3],
+    pub mountain_base_height: f64,
+    pub mountain_falloff: f64,
+    pub forest_base_height: f64,
+    pub forest_falloff: f64,
+
+    // --- Rivers and beaches ---
+    pub river_frequency: f64,
+    /// River noise below this is water, the channel is deepest in the middle
+    pub river_width: f64,
+    pub river_depth: f64,
+    /// River noise below this is part of the valley sloping down to the river
+    pub river_valley_width: f64,
+    /// Height above sea level where the valley walls meet the surrounding terrain
+    pub river_valley_height: f64,
+    /// Surfaces up to this far above sea level become beach
+    pub beach_height: i32,
+
+    // --- Biome cut-offs ---
+    /// Biome values below this are mountains
+    pub mountain_threshold: f64,
+    /// Biome values above this are forests
+    pub forest_threshold: f64,
+    /// Plains hotter than this become desert
+    pub desert_heat: f64,
+    /// Forests hotter than this become jungle
+    pub jungle_heat: f64,
+    pub biome_blend_exponent: f32,
+    pub forest_blend_exponent: f32,
+    /// Lowlands and forests colder than this become tundra
+    pub tundra_temperature: f64,
+    /// Hot lowlands drier than this become badlands
+    pub badlands_humidity: f64,
+    /// Hot lowlands more humid than this become savanna
+    pub savanna_humidity: f64,
+    /// Temperate lowlands and forests more humid than this become swamp
+    pub swamp_humidity: f64,
+    /// Terrain height shift of swamps, blended smoothly into neighbouring biomes
+    pub swamp_height_offset: f64,
+    /// Terrain height shift of badlands, blended smoothly into neighbouring biomes
+    pub badlands_height_offset: f64,
+
+    // --- Surface ---
+    /// Snow starts appearing on mountains above this height
+    pub snow_line_start: i32,
+    /// Mountains are fully snow covered above this height
+    pub snow_line_end: i32,
+    /// Thickness of the snow below fully snow covered mountain tops
+    pub snow_cap_depth: [u32; 2],
+    pub spruce_chance: f64,
+    pub walnut_chance: f64,
+    pub mahogany_chance: f64,
+    pub swamp_mahogany_chance: f64,
+    pub savanna_walnut_chance: f64,
+    pub tundra_spruce_chance: f64,
+    pub birch_chance: f64,
+    pub palm_chance: f64,
+    pub cactus_chance: f64,
+    pub shrub_chance: f64,
+    pub fallen_log_chance: f64,
+    pub dead_tree_chance: f64,
+    /// Wood, leaves and shape of each tree species, missing species use the built-in look
+    pub trees: Vec<TreeDefinition>,
+
+    // --- Carvers ---
+    pub cave_frequency: f64,
+    /// Tunnels form where both tunnel noises are closer to zero than this
+    pub cave_width: f64,
+    pub cave_max_y: i32,
+    pub cavern_frequency: f64,
+    pub cavern_threshold: f64,
+    pub cavern_max_y: i32,
+    /// Chance per chunk column to start a ravine
+    pub ravine_chance: f64,
+    pub ravine_min_y: i32,
+    pub ravine_max_y: i32,
+    pub ravine_length: [i32; 2],
+
+    // --- Ores ---
+    pub ores: Vec<OreSettings>,
+
+    // --- Structures ---
+    /// Width of the square regions that each hold at most one structure of every kind
+    pub structure_region_size: i32,
+    /// Chance per region to try placing a ruin, dungeon or tower
+    pub ruin_chance: f64,
+    pub dungeon_chance: f64,
+    pub tower_chance: f64,
+    /// Dungeons start between these heights
+    pub dungeon_min_y: i32,
+    pub dungeon_max_y: i32,
+
+    // --- Spawn ---
+    /// Biomes the player may spawn in, empty means everywhere
+    pub spawn_biomes: Vec<Biome>,
+    /// How far from the origin to look for dry land before falling back to the origin.
+    /// Every probed column samples the terrain, so this stays small.
+    pub spawn_search_radius: i32,
+}
+
+impl Default for WorldGenSettings {
+    fn default() -> Self {
+        Self {
+            global_height_scale: 0.001,
+            global_height_factor: -64.0,
+            vertical_scale: 1.0,
+            sea_level: -16,
+
+            min_build_height: -192,
+            max_build_height: 384,
+            chunk_margin_above: 2,
+            chunk_margin_below: 2,
+
+            main_biome_scale: 0.5,
+            biome_frequency: 0.003,
+            biome_detail_frequency: 0.03,
+            biome_weights: [0.5, 0.3, 0.2],
+            heat_frequencies: [0.001, 0.01, 0.1],
+            heat_weights: [0.7, 0.2, 0.1],
+            humidity_frequencies: [0.0015, 0.015, 0.15],
+            humidity_weights: [0.7, 0.2, 0.1],
+
+            plains_frequency: 0.01,
+            plains_base_height: 4.0,
+            plains_falloff: 0.03,
+            mountain_frequency: 0.02,
+            mountain_vertical_frequency: 0.003,
+            mountain_base_frequency: 0.001,
+            mountain_detail_frequency: 0.06,
+            mountain_detail_vertical_frequency: 0.05,
+            mountain_detail_falloff: 0.0056,
+            mountain_noise_offset: [456.78, 234.56],
+            mountain_weights: [0.7, 0.6, 0.3],
+            mountain_base_height: 4.0,
+            mountain_falloff: 0.005,
+            forest_base_height: 12.0,
+            forest_falloff: 0.01,
+
+            river_frequency: 0.0025,
+            river_width: 0.035,
+            river_depth: 5.0,
+            river_valley_width: 0.14,
+            river_valley_height: 40.0,
+            beach_height: 2,
+
+            mountain_threshold: 0.4,
+            forest_threshold: 0.6,
+            desert_heat: 0.55,
+            jungle_heat: 0.575,
+            biome_blend_exponent: 0.3,
+            forest_blend_exponent: 1.6,
+            tundra_temperature: 0.4,
+            badlands_humidity: 0.4,
+            savanna_humidity: 0.56,
+            swamp_humidity: 0.6,
+            swamp_height_offset: -24.0,
+            badlands_height_offset: 8.0,
+
+            snow_line_start: 50,
+            snow_line_end: 80,
+            snow_cap_depth: [1, 3],
+            spruce_chance: 0.01,
+            walnut_chance: 0.05,
+            mahogany_chance: 0.005,
+            swamp_mahogany_chance: 0.004,
+            savanna_walnut_chance: 0.003,
+            tundra_spruce_chance: 0.002,
+            birch_chance: 0.02,
+            palm_chance: 0.003,
+            cactus_chance: 0.004,
+            shrub_chance: 0.008,
+            fallen_log_chance: 0.002,
+            dead_tree_chance: 0.0005,
+            trees: TreeDefinition::defaults(),
+
+            cave_frequency: 0.02,
+            cave_width: 0.05,
+            cave_max_y: 48,
+            cavern_frequency: 0.012,
+            cavern_threshold: 0.45,
+            cavern_max_y: -8,
+            ravine_chance: 0.02,
+            ravine_min_y: -48,
+            ravine_max_y: 16,
+            ravine_length: [48, 112],
+
+            ores: vec![
+                OreSettings {
+                    block: VoxelType::CopperOre,
+                    vein_size: [6, 14],
+                    veins_per_chunk: 24,
+                    min_y: -96,
+                    max_y: 64,
+                    replaces: default_ore_hosts(),
+                    biomes: Vec::new(),
+                },
+                OreSettings {
+                    block: VoxelType::AmethystOre,
+                    vein_size: [3, 7],
+                    veins_per_chunk: 6,
+                    min_y: -160,
+                    max_y: -32,
+                    replaces: default_ore_hosts(),
+                    biomes: vec![Biome::Mountains, Biome::Badlands, Biome::Tundra],
+                },
+            ],
+
+            structure_region_size: 256,
+            ruin_chance: 0.4,
+            dungeon_chance: 0.6,
+            tower_chance: 0.25,
+            dungeon_min_y: -96,
+            dungeon_max_y: -32,
+
+            spawn_biomes: vec![Biome::Plains, Biome::Forest, Biome::Savanna],
+            spawn_search_radius: 256,
+        }
+    }
+}
+
+impl WorldGenSettings {
+    /// Lowest and highest chunk y that lie within the build limits
+    pub fn chunk_range(&self, chunk_size: usize) -> (i32, i32) {
+        let size = chunk_size as i32;
+        (
+            self.min_build_height.div_euclid(size),
+            (self.max_build_height - 1).div_euclid(size),
+        )
+    }
+
+    /// Rejects values that the generator can't roll with, like chances outside 0 to 1
+    /// or ranges whose minimum is above their maximum
+    pub fn validate(&self) -> Result<(), String> {
+        let tree_chances = [
+            ("spruce_chance", self.spruce_chance),
+            ("walnut_chance", self.walnut_chance),
+            ("mahogany_chance", self.mahogany_chance),
+            ("swamp_mahogany_chance", self.swamp_mahogany_chance),
+            ("savanna_walnut_chance", self.savanna_walnut_chance),
+            ("tundra_spruce_chance", self.tundra_spruce_chance),
+            ("birch_chance", self.birch_chance),
+            ("palm_chance", self.palm_chance),
+            ("cactus_chance", self.cactus_chance),
+            ("shrub_chance", self.shrub_chance),
+            ("fallen_log_chance", self.fallen_log_chance),
+            ("dead_tree_chance", self.dead_tree_chance),
+        ];
+        for (name, chance) in tree_chances {
+            check_chance(name, chance)?;
+        }
+
+        check_chance("ravine_chance", self.ravine_chance)?;
+        check_range("ravine_min_y/ravine_max_y", self.ravine_min_y, self.ravine_max_y)?;
+        let [min_length, max_length] = self.ravine_length;
+        check_range("ravine_length", min_length, max_length)?;
+        if min_length < 0 {
+            return Err(format!("ravine_length can't be negative, got {}", min_length));
+        }
+        Ok(())
+    }
+}
+
+fn check_chance(name: &str, chance: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&chance) {
+        Ok(())
+    } else {
+        Err(format!("{} must be between 0 and 1, got {}", name, chance))
+    }
+}
+
+fn check_range(name: &str, min: i32, max: i32) -> Result<(), String> {
+    if min <= max {
+        Ok(())
+    } else {
+        Err(format!("{} has its minimum {} above its maximum {}", name, min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_settings_are_valid() {
+        assert_eq!(WorldGenSettings::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn tree_chances_are_checked() {
+        let too_high = WorldGenSettings { birch_chance: 2.0, ..Default::default() };
+        assert!(too_high.validate().unwrap_err().contains("birch_chance"));
+        let negative = WorldGenSettings { dead_tree_chance: -0.5, ..Default::default() };
+        assert!(negative.validate().unwrap_err().contains("dead_tree_chance"));
+    }
+
+    #[test]
+    fn ravine_settings_are_checked() {
+        let invalid = [
+            WorldGenSettings { ravine_chance: 1.5, ..Default::default() },
+            WorldGenSettings { ravine_chance: -0.1, ..Default::default() },
+            WorldGenSettings { ravine_min_y: 20, ravine_max_y: 10, ..Default::default() },
+            WorldGenSettings { ravine_length: [100, 50], ..Default::default() },
+            WorldGenSettings { ravine_length: [-5, 50], ..Default::default() },
+        ];
+        for settings in invalid {
+            assert!(settings.validate().is_err());
+        }
+    }
+}
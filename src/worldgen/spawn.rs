@@ -0,0 +1,93 @@
+use crate::{
+    voxel::VoxelType,
+    worldgen::{biome::Biome, generator::TerrainGenerator, settings::WorldGenSettings},
+};
+
+/// Distance between the columns probed when estimating the spawn from the generator
+const ESTIMATE_STEP: i32 = 16;
+
+/// Column offsets within `radius` on a grid of `step`, nearest first.
+/// Ties are broken by offset so the search always visits columns in the same order.
+pub fn spiral(radius: i32, step: i32) -> Vec<(i32, i32)> {
+    let steps = radius / step;
+    let mut offsets: Vec<(i32, i32)> = (-steps..=steps)
+        .flat_map(|dx| (-steps..=steps).map(move |dz| (dx * step, dz * step)))
+        .filter(|&(dx, dz)| dx * dx + dz * dz <= radius * radius)
+        .collect();
+    offsets.sort_by_key(|&(dx, dz)| (dx * dx + dz * dz, dx, dz));
+    offsets
+}
+
+pub fn is_spawn_biome(settings: &WorldGenSettings, biome: Biome) -> bool {
+    settings.spawn_biomes.is_empty() || settings.spawn_biomes.contains(&biome)
+}
+
+/// Whether the player may spawn standing on `block`
+pub fn is_spawn_block(block: VoxelType) -> bool {
+    !block.is_not_solid()
+        && !block.is_leaves()
+        && !matches!(block, VoxelType::Water | VoxelType::Ice)
+}
+
+/// Searches outward from the origin for a dry column in a spawn biome, using only
+/// the generator so it works before any chunk has loaded. Returns the estimated
+/// surface block, or None if the generator has no terrain at all.
+pub fn estimate_spawn(
+    generator: &dyn TerrainGenerator,
+    settings: &WorldGenSettings,
+) -> Option<(i32, i32, i32)> {
+    for (x, z) in spiral(settings.spawn_search_radius, ESTIMATE_STEP) {
+        if !is_spawn_biome(settings, generator.biome_at(x, z)) {
+            continue;
+        }
+        match generator.surface_estimate(x, z) {
+            Some(wy) if wy >= settings.sea_level => return Some((x, wy, z)),
+            _ => {}
+        }
+    }
+
+    // No dry land nearby, fall back to the origin even if it is underwater
+    generator
+        .surface_estimate(0, 0)
+        .map(|wy| (0, wy.max(settings.sea_level), 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chunk::Chunk,
+        worldgen::{columns::ColumnCache, default_generator::DefaultGenerator},
+    };
+    use std::collections::HashSet;
+
+    #[test]
+    fn estimated_spawn_is_dry_land_in_a_spawn_biome() {
+        let settings = WorldGenSettings::default();
+        let generator = DefaultGenerator::new(12345, settings.clone());
+        let (x, y, z) = estimate_spawn(&generator, &settings).expect("terrain to spawn on");
+        assert!(y >= settings.sea_level);
+        assert!(is_spawn_biome(&settings, generator.biome_at(x, z)));
+
+        let size = 16;
+        let mut chunk = Chunk::new(x.div_euclid(size), y.div_euclid(size), z.div_euclid(size), size as usize);
+        let columns = ColumnCache::new();
+        generator.density(&mut chunk, &columns);
+        generator.surface(&mut chunk, &columns);
+        let (lx, ly, lz) = (x.rem_euclid(size), y.rem_euclid(size), z.rem_euclid(size));
+        assert!(is_spawn_block(chunk.get_extended_voxel(lx, ly, lz)));
+        assert_eq!(chunk.get_extended_voxel(lx, ly + 1, lz), VoxelType::Air);
+    }
+
+    #[test]
+    fn spiral_visits_nearer_columns_first() {
+        let offsets = spiral(64, 16);
+        assert_eq!(offsets[0], (0, 0));
+        let distances: Vec<i32> = offsets.iter().map(|&(dx, dz)| dx * dx + dz * dz).collect();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(distances.iter().all(|&distance| distance <= 64 * 64));
+        // Every one of the 49 grid columns within four steps is visited once
+        let unique: HashSet<(i32, i32)> = offsets.iter().copied().collect();
+        assert_eq!((offsets.len(), unique.len()), (49, 49));
+    }
+}
@@ -0,0 +1,351 @@
+use crate::voxel::VoxelType;
+use std::collections::HashMap;
+
+/// Direction a connector faces, out of its piece
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North, // -z
+    East,  // +x
+    South, // +z
+    West,  // -x
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn offset(&self) -> (i32, i32, i32) {
+        match self {
+            Direction::North => (0, 0, -1),
+            Direction::East => (1, 0, 0),
+            Direction::South => (0, 0, 1),
+            Direction::West => (-1, 0, 0),
+            Direction::Up => (0, 1, 0),
+            Direction::Down => (0, -1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+        }
+    }
+
+    /// Turns a horizontal direction clockwise (seen from above) by `turns` quarter turns
+    pub fn rotate(&self, turns: u8) -> Direction {
+        let mut facing = *self;
+        for _ in 0..turns % 4 {
+            facing = match facing {
+                Direction::North => Direction::East,
+                Direction::East => Direction::South,
+                Direction::South => Direction::West,
+                Direction::West => Direction::North,
+                vertical => vertical,
+            };
+        }
+        facing
+    }
+}
+
+/// Rotates a piece-local position around the y axis, matching `Direction::rotate`
+pub fn rotate(position: (i32, i32, i32), turns: u8) -> (i32, i32, i32) {
+    let (mut x, y, mut z) = position;
+    for _ in 0..turns % 4 {
+        (x, z) = (-z, x);
+    }
+    (x, y, z)
+}
+
+/// Where another piece from `pool` may attach. The attached piece's own connector
+/// ends up on the block next to this one, facing back at it.
+#[derive(Debug, Clone, Copy)]
+pub struct Connector {
+    pub position: (i32, i32, i32),
+    pub facing: Direction,
+    pub pool: &'static str,
+}
+
+/// A fixed block layout that structures are assembled from
+pub struct StructurePiece {
+    pub name: &'static str,
+    /// Air entries clear the terrain, e.g. the inside of buried rooms
+    pub blocks: Vec<((i32, i32, i32), VoxelType)>,
+    pub connectors: Vec<Connector>,
+    pub min: (i32, i32, i32),
+    pub max: (i32, i32, i32),
+}
+
+/// Weighted choice of pieces. Connectors past the structure's depth limit use the
+/// fallback pool instead, so towers get a roof rather than an open top.
+pub struct PiecePool {
+    pub pieces: Vec<(usize, u32)>,
+    pub fallback: Option<&'static str>,
+}
+
+/// Small helper for laying out pieces block by block
+struct PieceBuilder {
+    blocks: HashMap<(i32, i32, i32), VoxelType>,
+    connectors: Vec<Connector>,
+}
+
+impl PieceBuilder {
+    fn new() -> Self {
+        Self {
+            blocks: HashMap::new(),
+            connectors: Vec::new(),
+        }
+    }
+
+    fn set(&mut self, position: (i32, i32, i32), v: VoxelType) {
+        self.blocks.insert(position, v);
+    }
+
+    fn fill(&mut self, min: (i32, i32, i32), max: (i32, i32, i32), v: VoxelType) {
+        for x in min.0..=max.0 {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    self.set((x, y, z), v);
+                }
+            }
+        }
+    }
+
+    /// Walls of `wall`, with everything inside cleared to air
+    fn hollow(&mut self, min: (i32, i32, i32), max: (i32, i32, i32), wall: VoxelType) {
+        self.fill(min, max, wall);
+        self.fill(
+            (min.0 + 1, min.1 + 1, min.2 + 1),
+            (max.0 - 1, max.1 - 1, max.2 - 1),
+            VoxelType::Air,
+        );
+    }
+
+    /// A two block high opening with a connector at its bottom block
+    fn doorway(&mut self, position: (i32, i32, i32), facing: Direction, pool: &'static str) {
+        self.set(position, VoxelType::Air);
+        self.set((position.0, position.1 + 1, position.2), VoxelType::Air);
+        self.connector(position, facing, pool);
+    }
+
+    fn connector(&mut self, position: (i32, i32, i32), facing: Direction, pool: &'static str) {
+        self.connectors.push(Connector {
+            position,
+            facing,
+            pool,
+        });
+    }
+
+    fn build(self, name: &'static str) -> StructurePiece {
+        let mut min = (i32::MAX, i32::MAX, i32::MAX);
+        let mut max = (i32::MIN, i32::MIN, i32::MIN);
+        for &(x, y, z) in self.blocks.keys() {
+            min = (min.0.min(x), min.1.min(y), min.2.min(z));
+            max = (max.0.max(x), max.1.max(y), max.2.max(z));
+        }
+        // Sorted so placement order never depends on hash order
+        let mut blocks: Vec<_> = self.blocks.into_iter().collect();
+        blocks.sort_by_key(|&((x, y, z), _)| (y, z, x));
+        StructurePiece {
+            name,
+            blocks,
+            connectors: self.connectors,
+            min,
+            max,
+        }
+    }
+}
+
+/// All structure pieces and the pools that connectors draw from
+pub struct StructureTemplates {
+    pub pieces: Vec<StructurePiece>,
+    pub pools: HashMap<&'static str, PiecePool>,
+}
+
+impl StructureTemplates {
+    pub fn new() -> Self {
+        let mut templates = Self {
+            pieces: Vec::new(),
+            pools: HashMap::new(),
+        };
+
+        let room = templates.add(dungeon_room());
+        let corridor = templates.add(dungeon_corridor());
+        templates.pool("dungeon_start", &[(room, 1)], None);
+        templates.pool("dungeon_corridor", &[(corridor, 1)], None);
+        templates.pool("dungeon", &[(room, 1), (corridor, 2)], None);
+
+        let ruin_center = templates.add(ruin_center());
+        let ruin_wing = templates.add(ruin_wing());
+        templates.pool("ruin_start", &[(ruin_center, 1)], None);
+        templates.pool("ruin_wing", &[(ruin_wing, 1)], None);
+
+        let tower_base = templates.add(tower_base());
+        let tower_floor = templates.add(tower_floor());
+        let tower_roof = templates.add(tower_roof());
+        templates.pool("tower_start", &[(tower_base, 1)], None);
+        templates.pool("tower_top", &[(tower_floor, 3), (tower_roof, 1)], Some("tower_roof"));
+        templates.pool("tower_roof", &[(tower_roof, 1)], None);
+
+        // A connector into a missing pool would silently leave the jigsaw open
+        for piece in &templates.pieces {
+            for connector in &piece.connectors {
+                assert!(
+                    templates.pools.contains_key(connector.pool),
+                    "Structure piece {} connects to unknown pool {}",
+                    piece.name,
+                    connector.pool
+                );
+            }
+        }
+
+        templates
+    }
+
+    fn add(&mut self, piece: StructurePiece) -> usize {
+        self.pieces.push(piece);
+        self.pieces.len() - 1
+    }
+
+    fn pool(&mut self, name: &'static str, pieces: &[(usize, u32)], fallback: Option<&'static str>) {
+        self.pools.insert(
+            name,
+            PiecePool {
+                pieces: pieces.to_vec(),
+                fallback,
+            },
+        );
+    }
+}
+
+// --- Dungeons ---
+
+fn dungeon_room() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    piece.hollow((0, 0, 0), (8, 5, 8), VoxelType::Stonebrick);
+    piece.fill((1, 0, 1), (7, 0, 7), VoxelType::Cobblestone);
+    piece.doorway((4, 1, 0), Direction::North, "dungeon_corridor");
+    piece.doorway((8, 1, 4), Direction::East, "dungeon_corridor");
+    piece.doorway((4, 1, 8), Direction::South, "dungeon_corridor");
+    piece.doorway((0, 1, 4), Direction::West, "dungeon_corridor");
+    piece.build("dungeon_room")
+}
+
+fn dungeon_corridor() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    piece.hollow((0, 0, 0), (4, 4, 8), VoxelType::Cobblestone);
+    piece.doorway((2, 1, 0), Direction::North, "dungeon");
+    piece.doorway((2, 1, 8), Direction::South, "dungeon");
+    piece.build("dungeon_corridor")
+}
+
+// --- Ruins ---
+
+fn ruin_center() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    // Foundation so the floor never floats on uneven ground
+    piece.fill((0, -2, 0), (8, -1, 8), VoxelType::Cobblestone);
+    for x in 0..=8 {
+        for z in 0..=8 {
+            let floor = if (x + z) % 2 == 0 {
+                VoxelType::Stonebrick
+            } else {
+                VoxelType::Cobblestone
+            };
+            piece.set((x, 0, z), floor);
+        }
+    }
+    for y in 1..=3 {
+        for i in 0..=8 {
+            piece.set((i, y, 0), VoxelType::Stonebrick);
+            piece.set((i, y, 8), VoxelType::Stonebrick);
+            piece.set((0, y, i), VoxelType::Stonebrick);
+            piece.set((8, y, i), VoxelType::Stonebrick);
+        }
+    }
+    piece.doorway((4, 1, 0), Direction::North, "ruin_wing");
+    piece.doorway((8, 1, 4), Direction::East, "ruin_wing");
+    piece.doorway((4, 1, 8), Direction::South, "ruin_wing");
+    piece.doorway((0, 1, 4), Direction::West, "ruin_wing");
+    piece.build("ruin_center")
+}
+
+fn ruin_wing() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    piece.fill((0, -2, 0), (6, -1, 5), VoxelType::Cobblestone);
+    piece.fill((0, 0, 0), (6, 0, 5), VoxelType::Cobblestone);
+    for y in 1..=2 {
+        for z in 0..=5 {
+            piece.set((0, y, z), VoxelType::Cobblestone);
+            piece.set((6, y, z), VoxelType::Cobblestone);
+        }
+        for x in 0..=6 {
+            piece.set((x, y, 5), VoxelType::Cobblestone);
+        }
+    }
+    piece.set((3, 2, 5), VoxelType::Glass);
+    piece.set((3, 1, 0), VoxelType::Air);
+    piece.connector((3, 1, 0), Direction::North, "ruin_wing");
+    piece.build("ruin_wing")
+}
+
+// --- Towers ---
+
+fn tower_base() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    piece.fill((0, -3, 0), (6, 0, 6), VoxelType::Cobblestone);
+    for y in 1..=4 {
+        for i in 0..=6 {
+            piece.set((i, y, 0), VoxelType::Stonebrick);
+            piece.set((i, y, 6), VoxelType::Stonebrick);
+            piece.set((0, y, i), VoxelType::Stonebrick);
+            piece.set((6, y, i), VoxelType::Stonebrick);
+        }
+    }
+    piece.fill((1, 1, 1), (5, 4, 5), VoxelType::Air);
+    piece.set((3, 1, 0), VoxelType::Air);
+    piece.set((3, 2, 0), VoxelType::Air);
+    piece.connector((3, 4, 3), Direction::Up, "tower_top");
+    piece.build("tower_base")
+}
+
+fn tower_floor() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    piece.fill((0, 0, 0), (6, 0, 6), VoxelType::Stonebrick);
+    for y in 1..=4 {
+        for i in 0..=6 {
+            piece.set((i, y, 0), VoxelType::Stonebrick);
+            piece.set((i, y, 6), VoxelType::Stonebrick);
+            piece.set((0, y, i), VoxelType::Stonebrick);
+            piece.set((6, y, i), VoxelType::Stonebrick);
+        }
+    }
+    piece.fill((1, 1, 1), (5, 4, 5), VoxelType::Air);
+    // A window in every wall
+    for y in 2..=3 {
+        piece.set((3, y, 0), VoxelType::Glass);
+        piece.set((3, y, 6), VoxelType::Glass);
+        piece.set((0, y, 3), VoxelType::Glass);
+        piece.set((6, y, 3), VoxelType::Glass);
+    }
+    piece.connector((3, 0, 3), Direction::Down, "tower_top");
+    piece.connector((3, 4, 3), Direction::Up, "tower_top");
+    piece.build("tower_floor")
+}
+
+fn tower_roof() -> StructurePiece {
+    let mut piece = PieceBuilder::new();
+    piece.fill((0, 0, 0), (6, 0, 6), VoxelType::Cobblestone);
+    // Crenellations around the edge
+    for i in (0..=6).step_by(2) {
+        piece.set((i, 1, 0), VoxelType::Cobblestone);
+        piece.set((i, 1, 6), VoxelType::Cobblestone);
+        piece.set((0, 1, i), VoxelType::Cobblestone);
+        piece.set((6, 1, i), VoxelType::Cobblestone);
+    }
+    piece.connector((3, 0, 3), Direction::Down, "tower_top");
+    piece.build("tower_roof")
+}
@@ -0,0 +1,506 @@
+use crate::{
+    chunk::Chunk,
+    voxel::VoxelType,
+    worldgen::{
+        biome::Biome,
+        random::{DECAY_SALT, STRUCTURE_SALT, positional_rng},
+        settings::WorldGenSettings,
+        structure_templates::{Direction, StructureTemplates, rotate},
+    },
+};
+use rand::Rng;
+use rand_pcg::Pcg32;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+// No piece may reach further than this from its structure's start, which keeps
+// the regions a chunk has to look at down to its direct neighbours
+const MAX_REACH: i32 = 48;
+
+/// Regions further than this many blocks from the newest one are dropped from the cache,
+/// which follows the chunks generated around the player like the column cache
+const CACHE_DISTANCE: i32 = 1024;
+
+/// What the structure planner needs to know about the terrain before it exists
+pub trait TerrainProbe {
+    fn biome(&self, wx: i32, wz: i32) -> Biome;
+    /// Height of the highest terrain block, None when it is under water
+    fn dry_surface(&self, wx: i32, wz: i32) -> Option<i32>;
+    fn is_solid(&self, wx: i32, wy: i32, wz: i32) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructureKind {
+    Ruin,
+    Dungeon,
+    Tower,
+}
+
+impl StructureKind {
+    const ALL: [StructureKind; 3] = [
+        StructureKind::Ruin,
+        StructureKind::Dungeon,
+        StructureKind::Tower,
+    ];
+
+    fn start_pool(&self) -> &'static str {
+        match self {
+            StructureKind::Ruin => "ruin_start",
+            StructureKind::Dungeon => "dungeon_start",
+            StructureKind::Tower => "tower_start",
+        }
+    }
+
+    fn chance(&self, settings: &WorldGenSettings) -> f64 {
+        match self {
+            StructureKind::Ruin => settings.ruin_chance,
+            StructureKind::Dungeon => settings.dungeon_chance,
+            StructureKind::Tower => settings.tower_chance,
+        }
+    }
+
+    // How many connectors deep the jigsaw may go
+    fn max_depth(&self) -> u32 {
+        match self {
+            StructureKind::Ruin => 2,
+            StructureKind::Dungeon => 5,
+            StructureKind::Tower => 4,
+        }
+    }
+
+    /// Share of blocks that survived, ruins are partly crumbled
+    fn integrity(&self) -> f64 {
+        match self {
+            StructureKind::Ruin => 0.7,
+            StructureKind::Dungeon => 0.95,
+            StructureKind::Tower => 1.0,
+        }
+    }
+
+    fn allowed_in(&self, biome: Biome) -> bool {
+        match self {
+            StructureKind::Ruin => matches!(biome, Biome::Forest | Biome::Jungle),
+            StructureKind::Dungeon => true,
+            StructureKind::Tower => matches!(
+                biome,
+                Biome::Plains | Biome::Savanna | Biome::Tundra | Biome::Mountains
+            ),
+        }
+    }
+}
+
+/// A piece of a structure at its final position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacedPiece {
+    pub piece: usize,
+    pub turns: u8,
+    pub offset: (i32, i32, i32),
+    pub min: (i32, i32, i32),
+    pub max: (i32, i32, i32),
+}
+
+impl PlacedPiece {
+    fn intersects(&self, min: (i32, i32, i32), max: (i32, i32, i32)) -> bool {
+        self.min.0 <= max.0
+            && min.0 <= self.max.0
+            && self.min.1 <= max.1
+            && min.1 <= self.max.1
+            && self.min.2 <= max.2
+            && min.2 <= self.max.2
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedStructure {
+    pub kind: StructureKind,
+    pub pieces: Vec<PlacedPiece>,
+}
+
+/// Picks structure starts per region from the seed and assembles them jigsaw style.
+/// Chunks in the features stage copy the blocks of every structure that reaches them.
+pub struct StructurePlanner {
+    seed: u32,
+    settings: WorldGenSettings,
+    templates: StructureTemplates,
+    regions: Mutex<HashMap<(i32, i32), Arc<Vec<PlacedStructure>>>>,
+}
+
+impl StructurePlanner {
+    pub fn new(seed: u32, settings: &WorldGenSettings) -> Self {
+        Self {
+            seed,
+            settings: settings.clone(),
+            templates: StructureTemplates::new(),
+            regions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Writes the structure blocks that fall inside the extended area of `chunk`
+    pub fn place(&self, chunk: &mut Chunk, terrain: &impl TerrainProbe) {
+        let size = chunk.size as i32;
+        let region_size = self.region_size();
+        let origin = (
+            chunk.position.0 * size,
+            chunk.position.1 * size,
+            chunk.position.2 * size,
+        );
+        let min = (origin.0 - 1, origin.1 - 1, origin.2 - 1);
+        let max = (origin.0 + size, origin.1 + size, origin.2 + size);
+
+        let first_x = (min.0 - MAX_REACH).div_euclid(region_size);
+        let first_z = (min.2 - MAX_REACH).div_euclid(region_size);
+        let last_x = (max.0 + MAX_REACH).div_euclid(region_size);
+        let last_z = (max.2 + MAX_REACH).div_euclid(region_size);
+        for rx in first_x..=last_x {
+            for rz in first_z..=last_z {
+                for structure in self.region(rx, rz, terrain).iter() {
+                    for placed in structure.pieces.iter().filter(|p| p.intersects(min, max)) {
+                        self.place_piece(chunk, origin, structure.kind, placed);
+                    }
+                }
+            }
+        }
+    }
+
+    fn place_piece(
+        &self,
+        chunk: &mut Chunk,
+        origin: (i32, i32, i32),
+        kind: StructureKind,
+        placed: &PlacedPiece,
+    ) {
+        let size = chunk.size as i32;
+        let piece = &self.templates.pieces[placed.piece];
+        let integrity = kind.integrity();
+        for &(position, v) in &piece.blocks {
+            let (x, y, z) = rotate(position, placed.turns);
+            let (wx, wy, wz) = (x + placed.offset.0, y + placed.offset.1, z + placed.offset.2);
+            let (lx, ly, lz) = (wx - origin.0, wy - origin.1, wz - origin.2);
+            if lx < -1 || ly < -1 || lz < -1 || lx > size || ly > size || lz > size {
+                continue;
+            }
+            // Decay is rolled per world position, so both sides of a chunk border agree
+            if v != VoxelType::Air
+                && integrity < 1.0
+                && positional_rng(self.seed, wx, wy, wz, DECAY_SALT).random_bool(1.0 - integrity)
+            {
+                continue;
+            }
+            chunk.set_extended_voxel(lx, ly, lz, v);
+        }
+    }
+
+    /// Structures starting in region (rx, rz), planned once and shared by all chunks.
+    /// Planning a region evicts those out of `CACHE_DISTANCE` of it.
+    fn region(&self, rx: i32, rz: i32, terrain: &impl TerrainProbe) -> Arc<Vec<PlacedStructure>> {
+        if let Some(region) = self.regions.lock().unwrap().get(&(rx, rz)) {
+            return Arc::clone(region);
+        }
+
+        let region = Arc::new(self.plan_region(rx, rz, terrain));
+        let radius = (CACHE_DISTANCE / self.region_size()).max(1);
+        let mut regions = self.regions.lock().unwrap();
+        regions.retain(|&(x, z), _| (x - rx).abs() <= radius && (z - rz).abs() <= radius);
+        Arc::clone(regions.entry((rx, rz)).or_insert(region))
+    }
+
+    fn plan_region(&self, rx: i32, rz: i32, terrain: &impl TerrainProbe) -> Vec<PlacedStructure> {
+        let settings = &self.settings;
+        let region_size = self.region_size();
+        // Stay clear of the region edges so structures rarely overlap their neighbours
+        let margin = (MAX_REACH / 2).min(region_size / 4);
+        let mut rng = positional_rng(self.seed, rx, 0, rz, STRUCTURE_SALT);
+        let mut structures = Vec::new();
+
+        for kind in StructureKind::ALL {
+            // Always draw the same numbers so one structure's fit never shifts another
+            let roll = rng.random_bool(kind.chance(settings).clamp(0.0, 1.0));
+            let wx = rx * region_size + rng.random_range(margin..region_size - margin);
+            let wz = rz * region_size + rng.random_range(margin..region_size - margin);
+            let depth_roll = rng.random_range(
+                settings.dungeon_min_y..=settings.dungeon_max_y.max(settings.dungeon_min_y),
+            );
+            let mut piece_rng = positional_rng(self.seed, wx, depth_roll, wz, STRUCTURE_SALT);
+
+            if !roll || !kind.allowed_in(terrain.biome(wx, wz)) {
+                continue;
+            }
+            let Some(wy) = Self::fit(kind, terrain, wx, wz, depth_roll) else {
+                continue;
+            };
+            structures.push(PlacedStructure {
+                kind,
+                pieces: self.assemble(kind, (wx, wy, wz), &mut piece_rng),
+            });
+        }
+
+        structures
+    }
+
+    fn region_size(&self) -> i32 {
+        self.settings.structure_region_size.max(16)
+    }
+
+    /// Height to start `kind` at around (wx, wz), or None if the terrain doesn't suit it
+    fn fit(
+        kind: StructureKind,
+        terrain: &impl TerrainProbe,
+        wx: i32,
+        wz: i32,
+        depth: i32,
+    ) -> Option<i32> {
+        let probes = [(0, 0), (-6, -6), (6, -6), (-6, 6), (6, 6)];
+        match kind {
+            // Buried: solid rock all around the first room
+            StructureKind::Dungeon => probes
+                .iter()
+                .all(|&(dx, dz)| terrain.is_solid(wx + dx, depth + 3, wz + dz))
+                .then_some(depth),
+            // On dry and mostly flat ground
+            StructureKind::Ruin | StructureKind::Tower => {
+                let mut heights = Vec::with_capacity(probes.len());
+                for &(dx, dz) in &probes {
+                    heights.push(terrain.dry_surface(wx + dx, wz + dz)?);
+                }
+                let lowest = *heights.iter().min()?;
+                let highest = *heights.iter().max()?;
+                (highest - lowest <= 3).then_some(lowest + 1)
+            }
+        }
+    }
+
+    /// Grows a structure from its start piece by attaching pieces to open connectors,
+    /// breadth first, skipping candidates that would overlap what is already placed
+    fn assemble(
+        &self,
+        kind: StructureKind,
+        start: (i32, i32, i32),
+        rng: &mut Pcg32,
+    ) -> Vec<PlacedPiece> {
+        let templates = &self.templates;
+        let mut placed = Vec::new();
+        let mut open = VecDeque::new();
+
+        let Some(start_piece) = self.pick(kind.start_pool(), rng) else {
+            return placed;
+        };
+        let turns = rng.random_range(0..4);
+        let first = self.placed(start_piece, turns, start);
+        placed.push(first);
+        for i in 0..templates.pieces[start_piece].connectors.len() {
+            open.push_back((first, i, 0));
+        }
+
+        while let Some((parent, connector_index, depth)) = open.pop_front() {
+            let connector = templates.pieces[parent.piece].connectors[connector_index];
+            let facing = connector.facing.rotate(parent.turns);
+            let (x, y, z) = rotate(connector.position, parent.turns);
+            let (dx, dy, dz) = facing.offset();
+            let target = (
+                x + parent.offset.0 + dx,
+                y + parent.offset.1 + dy,
+                z + parent.offset.2 + dz,
+            );
+
+            let pool = if depth + 1 < kind.max_depth() {
+                connector.pool
+            } else {
+                match templates.pools.get(connector.pool).and_then(|pool| pool.fallback) {
+                    Some(fallback) => fallback,
+                    None => continue,
+                }
+            };
+
+            if let Some((child, used)) = self.attach(pool, facing, target, start, &placed, rng) {
+                placed.push(child);
+                for i in 0..templates.pieces[child.piece].connectors.len() {
+                    if i != used {
+                        open.push_back((child, i, depth + 1));
+                    }
+                }
+            }
+        }
+
+        placed
+    }
+
+    /// Tries pieces of `pool` in random order until one has a connector facing back
+    /// at `facing` that fits at `target` without overlapping the placed pieces
+    fn attach(
+        &self,
+        pool: &str,
+        facing: Direction,
+        target: (i32, i32, i32),
+        start: (i32, i32, i32),
+        placed: &[PlacedPiece],
+        rng: &mut Pcg32,
+    ) -> Option<(PlacedPiece, usize)> {
+        let templates = &self.templates;
+        let first_piece = self.pick(pool, rng)?;
+        let first_turns = rng.random_range(0..4u8);
+
+        let pool_pieces = &templates.pools.get(pool)?.pieces;
+        let order = pool_pieces
+            .iter()
+            .map(|&(piece, _)| piece)
+            .filter(|&piece| piece != first_piece);
+        for piece in std::iter::once(first_piece).chain(order) {
+            for extra_turns in 0..4u8 {
+                let turns = (first_turns + extra_turns) % 4;
+                for (i, connector) in templates.pieces[piece].connectors.iter().enumerate() {
+                    if connector.facing.rotate(turns) != facing.opposite() {
+                        continue;
+                    }
+                    let (x, y, z) = rotate(connector.position, turns);
+                    let offset = (target.0 - x, target.1 - y, target.2 - z);
+                    let candidate = self.placed(piece, turns, offset);
+
+                    let within_reach = (candidate.min.0 - start.0).abs() <= MAX_REACH
+                        && (candidate.max.0 - start.0).abs() <= MAX_REACH
+                        && (candidate.min.2 - start.2).abs() <= MAX_REACH
+                        && (candidate.max.2 - start.2).abs() <= MAX_REACH;
+                    let overlaps = placed.iter().any(|p| p.intersects(candidate.min, candidate.max));
+                    if within_reach && !overlaps {
+                        return Some((candidate, i));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Weighted random piece from a pool
+    fn pick(&self, pool: &str, rng: &mut Pcg32) -> Option<usize> {
+        let pool = self.templates.pools.get(pool)?;
+        let total: u32 = pool.pieces.iter().map(|&(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.random_range(0..total);
+        for &(piece, weight) in &pool.pieces {
+            if roll < weight {
+                return Some(piece);
+            }
+            roll -= weight;
+        }
+        None
+    }
+
+    fn placed(&self, piece: usize, turns: u8, offset: (i32, i32, i32)) -> PlacedPiece {
+        let template = &self.templates.pieces[piece];
+        let a = rotate(template.min, turns);
+        let b = rotate(template.max, turns);
+        PlacedPiece {
+            piece,
+            turns,
+            offset,
+            min: (a.0.min(b.0) + offset.0, a.1.min(b.1) + offset.1, a.2.min(b.2) + offset.2),
+            max: (a.0.max(b.0) + offset.0, a.1.max(b.1) + offset.1, a.2.max(b.2) + offset.2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::worldgen::testing::assert_deterministic;
+
+    type Position = (i32, i32, i32);
+
+    /// Dry plains at height 64 everywhere, solid below it
+    struct FlatTerrain;
+
+    impl TerrainProbe for FlatTerrain {
+        fn biome(&self, _wx: i32, _wz: i32) -> Biome {
+            Biome::Plains
+        }
+
+        fn dry_surface(&self, _wx: i32, _wz: i32) -> Option<i32> {
+            Some(64)
+        }
+
+        fn is_solid(&self, _wx: i32, wy: i32, _wz: i32) -> bool {
+            wy <= 64
+        }
+    }
+
+    fn planner(seed: u32) -> StructurePlanner {
+        let settings = WorldGenSettings {
+            dungeon_chance: 1.0,
+            tower_chance: 1.0,
+            ..WorldGenSettings::default()
+        };
+        StructurePlanner::new(seed, &settings)
+    }
+
+    #[test]
+    fn same_seed_plans_and_places_the_same_structures() {
+        let structure = planner(7).region(0, 0, &FlatTerrain)[0].clone();
+        let (wx, wy, wz) = structure.pieces[0].offset;
+        let position = (wx.div_euclid(16), wy.div_euclid(16), wz.div_euclid(16));
+        assert_deterministic(7, |seed| {
+            let planner = planner(seed);
+            let mut planned = Vec::new();
+            for rx in -2..2 {
+                for rz in -2..2 {
+                    planned.extend(planner.region(rx, rz, &FlatTerrain).iter().cloned());
+                }
+            }
+            let mut chunk = Chunk::new(position.0, position.1, position.2, 16);
+            planner.place(&mut chunk, &FlatTerrain);
+            (planned, chunk.extended_voxels)
+        });
+    }
+
+    #[test]
+    fn pieces_attach_at_connectors_without_overlapping() {
+        let planner = planner(7);
+        // World position and direction of every connector of a placed piece
+        let connectors = |placed: &PlacedPiece| -> Vec<(Position, Direction)> {
+            planner.templates.pieces[placed.piece]
+                .connectors
+                .iter()
+                .map(|connector| {
+                    let (x, y, z) = rotate(connector.position, placed.turns);
+                    let (ox, oy, oz) = placed.offset;
+                    ((x + ox, y + oy, z + oz), connector.facing.rotate(placed.turns))
+                })
+                .collect()
+        };
+
+        let mut attached = 0;
+        for rx in -2..2 {
+            for rz in -2..2 {
+                for structure in planner.region(rx, rz, &FlatTerrain).iter() {
+                    for (i, piece) in structure.pieces.iter().enumerate().skip(1) {
+                        let earlier = &structure.pieces[..i];
+                        assert!(!earlier.iter().any(|other| other.intersects(piece.min, piece.max)));
+                        // One of its connectors faces back into a connector of an earlier piece
+                        let joined = connectors(piece).into_iter().any(|(position, facing)| {
+                            let (dx, dy, dz) = facing.offset();
+                            let facing_into = (position.0 + dx, position.1 + dy, position.2 + dz);
+                            earlier
+                                .iter()
+                                .flat_map(&connectors)
+                                .any(|(other, other_facing)| other == facing_into && other_facing == facing.opposite())
+                        });
+                        assert!(joined, "piece {} of a {:?} isn't attached to the rest", i, structure.kind);
+                        attached += 1;
+                    }
+                }
+            }
+        }
+        assert!(attached > 0, "no structure has more than one piece");
+    }
+
+    #[test]
+    fn far_regions_are_evicted() {
+        let planner = planner(7);
+        planner.region(0, 0, &FlatTerrain);
+        let far = CACHE_DISTANCE / planner.region_size() + 1;
+        planner.region(far, 0, &FlatTerrain);
+        let regions = planner.regions.lock().unwrap();
+        assert!(!regions.contains_key(&(0, 0)));
+        assert!(regions.contains_key(&(far, 0)));
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{chunk::Chunk, voxel::VoxelType};
+
+/// Asserts that `generate` gives the same result every time for `seed`, and a different
+/// one for the next seed so the comparison can't pass by generating nothing
+pub fn assert_deterministic<T: PartialEq>(seed: u32, generate: impl Fn(u32) -> T) {
+    let first = generate(seed);
+    assert!(first == generate(seed), "seed {} generated differently the second time", seed);
+    assert!(first != generate(seed.wrapping_add(1)), "seeds {} and {} generated the same", seed, seed.wrapping_add(1));
+}
+
+/// A chunk of solid stone, including the border of its extended area
+pub fn stone_chunk(position: (i32, i32, i32), size: usize) -> Chunk {
+    let mut chunk = Chunk::new(position.0, position.1, position.2, size);
+    let size = size as i32;
+    for x in -1..=size {
+        for y in -1..=size {
+            for z in -1..=size {
+                chunk.set_extended_voxel(x, y, z, VoxelType::Stone);
+            }
+        }
+    }
+    chunk
+}